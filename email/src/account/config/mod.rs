@@ -22,10 +22,10 @@
 use dirs::data_dir;
 use mail_builder::headers::address::{Address, EmailAddress};
 use mail_parser::Address::*;
-use mml::MimeInterpreterBuilder;
+use mml::{message::AttachmentPolicy, MimeInterpreterBuilder};
 #[cfg(feature = "notify")]
 use notify_rust::Notification;
-use process::Command;
+use process::{Command, TemplateVars};
 use shellexpand_utils::{shellexpand_path, shellexpand_str, try_shellexpand_path};
 
 #[cfg(feature = "pgp")]
@@ -48,6 +48,7 @@
         new::config::NewTemplateSignatureStyle,
         reply::config::{ReplyTemplatePostingStyle, ReplyTemplateSignatureStyle},
     },
+    warn,
     watch::config::WatchHook,
 };
 
@@ -103,6 +104,13 @@ pub struct AccountConfig {
     /// (usually `/tmp`).
     pub downloads_dir: Option<PathBuf>,
 
+    /// The attachment policy.
+    ///
+    /// It limits the size and the MIME types of the attachments
+    /// being downloaded. Attachments file names are always
+    /// sanitized, whatever the policy.
+    pub attachment_policy: Option<AttachmentPolicy>,
+
     /// The folder configuration.
     pub folder: Option<FolderConfig>,
 
@@ -163,29 +171,57 @@ pub fn get_downloads_dir(&self) -> PathBuf {
             .unwrap_or_else(env::temp_dir)
     }
 
+    /// Get the attachment policy, otherwise return the default one.
+    pub fn get_attachment_policy(&self) -> AttachmentPolicy {
+        self.attachment_policy.clone().unwrap_or_default()
+    }
+
     /// Build the downloadable version of the given path.
     ///
     /// The aim of this helper is to build a safe download path for a
     /// given path.
     ///
-    /// First, only the file name of the give path is taken in order
-    /// to prevent any interaction outside of the downloads directory.
+    /// First, the given attachment contents are checked against the
+    /// attachment policy (see [`AccountConfig::check_attachment`]).
+    ///
+    /// Then, only the file name of the give path is taken then
+    /// sanitized in order to prevent any interaction outside of the
+    /// downloads directory (see
+    /// [`AttachmentPolicy::sanitize_filename`]).
     ///
     /// Then, a suffix may be added to the final path if it already
     /// exists on the filesystem in order to prevent any overriding or
     /// data loss.
-    pub fn get_download_file_path(&self, path: impl AsRef<Path>) -> Result<PathBuf> {
+    pub fn get_download_file_path(
+        &self,
+        path: impl AsRef<Path>,
+        data: impl AsRef<[u8]>,
+    ) -> Result<PathBuf> {
         let path = path.as_ref();
 
+        self.check_attachment(path, data.as_ref())?;
+
         let file_name = path
             .file_name()
             .ok_or_else(|| Error::GetFileNameFromPathSyncError(path.to_owned()))?;
+        let file_name = AttachmentPolicy::sanitize_filename(&file_name.to_string_lossy());
 
         let final_path = self.get_downloads_dir().join(file_name);
 
         rename_file_if_duplicate(&final_path, |path, _count| path.is_file())
     }
 
+    /// Check the given attachment contents against the attachment
+    /// policy.
+    ///
+    /// Only the per-file constraints are checked: the file size and
+    /// the MIME type detected from the contents.
+    pub fn check_attachment(&self, path: impl AsRef<Path>, data: &[u8]) -> Result<()> {
+        self.get_attachment_policy()
+            .check(data)
+            .map_err(|reason| Error::CheckAttachmentError(reason, path.as_ref().to_owned()))
+    }
+
     /// Return `true` if the synchronization is enabled.
     #[cfg(feature = "sync")]
     pub fn is_sync_enabled(&self) -> bool {
@@ -248,22 +284,28 @@ pub async fn exec_envelope_hook(&self, hook: &WatchHook, envelope: &Envelope) {
         let recipient_name = envelope.to.name.as_deref().unwrap_or("unknown");
 
         if let Some(cmd) = hook.cmd.as_ref() {
-            let res = cmd
-                .clone()
-                .replace("{id}", &envelope.id)
-                .replace("{subject}", &envelope.subject)
-                .replace("{sender}", sender)
-                .replace("{sender.name}", sender_name)
-                .replace("{sender.address}", &envelope.from.addr)
-                .replace("{recipient}", recipient)
-                .replace("{recipient.name}", recipient_name)
-                .replace("{recipient.address}", &envelope.to.addr)
-                .run()
-                .await;
-
-            if let Err(_err) = res {
-                debug!("error while executing watch command hook");
-                debug!("{_err:?}");
+            let vars = TemplateVars::new()
+                .with_var("id", &envelope.id)
+                .with_var("subject", &envelope.subject)
+                .with_var("sender", sender)
+                .with_var("sender.name", sender_name)
+                .with_var("sender.address", &envelope.from.addr)
+                .with_var("recipient", recipient)
+                .with_var("recipient.name", recipient_name)
+                .with_var("recipient.address", &envelope.to.addr);
+
+            // hooks written for plain replacement are still accepted,
+            // see [`process::SingleCommand::render_lenient`]
+            match cmd.clone().render_lenient(&vars) {
+                Ok(cmd) => {
+                    if let Err(_err) = cmd.run().await {
+                        debug!("error while executing watch command hook");
+                        debug!("{_err:?}");
+                    }
+                }
+                Err(_err) => {
+                    warn!("cannot render watch command hook, skipping it: {_err}");
+                }
             }
         }
 
@@ -496,8 +538,9 @@ pub fn should_save_copy_sent_message(&self) -> bool {
     /// Generate a template interpreter with prefilled options from
     /// the current user account configuration.
     pub fn generate_tpl_interpreter(&self) -> MimeInterpreterBuilder {
-        let builder =
-            MimeInterpreterBuilder::new().with_save_attachments_dir(self.get_downloads_dir());
+        let builder = MimeInterpreterBuilder::new()
+            .with_save_attachments_dir(self.get_downloads_dir())
+            .with_attachment_policy(self.get_attachment_policy());
 
         #[cfg(feature = "pgp")]
         if let Some(ref pgp) = self.pgp {
@@ -691,7 +734,71 @@ pub(crate) fn rename_file_if_duplicate(
 
 #[cfg(test)]
 mod tests {
-    use std::path::PathBuf;
+    use std::{fs, path::PathBuf};
+
+    use mml::message::{AttachmentPolicy, SkipReason};
+    use process::Command;
+
+    use super::{AccountConfig, Error};
+    use crate::{
+        envelope::{Address, Envelope},
+        watch::config::WatchHook,
+    };
+
+    #[tokio::test]
+    async fn exec_envelope_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("hook");
+
+        // hooks written for plain replacement still run, with quoted
+        // placeholders and literal braces
+        let cmd = format!(
+            "printf %s \"New message: {{subject}}\" | awk '{{print $3}}' > {}",
+            path.display()
+        );
+        let hook = WatchHook {
+            cmd: Some(Command::from(cmd)),
+            notify: None,
+            callback: None,
+        };
+        let envelope = Envelope {
+            subject: String::from("Hello, world!"),
+            from: Address::new_nameless("alice@localhost"),
+            to: Address::new_nameless("bob@localhost"),
+            ..Default::default()
+        };
+
+        AccountConfig::default()
+            .exec_envelope_hook(&hook, &envelope)
+            .await;
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Hello,\n");
+    }
+
+    #[test]
+    fn get_download_file_path() {
+        let config = AccountConfig {
+            downloads_dir: Some(PathBuf::from("/downloads")),
+            attachment_policy: Some(AttachmentPolicy {
+                max_file_size: Some(4),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            config
+                .get_download_file_path("../../etc/file.txt", "data")
+                .unwrap(),
+            PathBuf::from("/downloads/file.txt"),
+        );
+
+        assert!(matches!(
+            config.get_download_file_path("file.txt", "too large"),
+            Err(Error::CheckAttachmentError(SkipReason::FileTooLarge { size: 9, max: 4 }, path))
+                if path == PathBuf::from("file.txt")
+        ));
+    }
 
     #[test]
     fn rename_file_if_duplicate() {
@@ -3,14 +3,33 @@
 //! This module contains everything related to OAuth 2.0
 //! configuration.
 
-use std::{fmt, io, net::TcpListener, vec};
+use std::{
+    fmt, io,
+    net::TcpListener,
+    sync::{Arc, Mutex as StdMutex, PoisonError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+    vec,
+};
 
-use oauth::v2_0::{AuthorizationCodeGrant, Client, RefreshAccessToken};
+use oauth::v2_0::{
+    AuthorizationCodeGrant, AuthorizationServerMetadata, Client, ClientCredentialsGrant,
+    DeviceAuthorizationGrant, JwtBearerGrant, RefreshAccessToken, Tokens,
+};
+use once_cell::sync::Lazy;
 use secret::Secret;
+use tokio::sync::Mutex;
 
 #[doc(inline)]
 pub use super::{Error, Result};
-use crate::debug;
+use crate::{debug, warn};
+
+/// Lock coalescing concurrent refreshes of the same access token,
+/// holding the time of the last successful refresh.
+type RefreshLock = Arc<Mutex<Option<Instant>>>;
+
+/// Locks used to coalesce concurrent refreshes of the same access
+/// token, for example from IMAP pool clients.
+static REFRESH_LOCKS: Lazy<StdMutex<Vec<(Secret, RefreshLock)>>> = Lazy::new(Default::default);
 
 /// The OAuth 2.0 configuration.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
@@ -24,12 +43,22 @@ pub struct OAuth2Config {
     /// for authentication.
     pub method: OAuth2Method,
 
+    /// Flow used to obtain the first access token.
+    ///
+    /// Defaults to the authorization code grant flow.
+    #[cfg_attr(feature = "derive", serde(default))]
+    pub flow: OAuth2Flow,
+
     /// Client identifier issued to the client during the registration process described by
     /// [Section 2.2](https://datatracker.ietf.org/doc/html/rfc6749#section-2.2).
     pub client_id: String,
 
     /// Client password issued to the client during the registration process described by
     /// [Section 2.2](https://datatracker.ietf.org/doc/html/rfc6749#section-2.2).
+    ///
+    /// The device authorization grant flow does not prompt for it:
+    /// when not found, the client is considered public and
+    /// authenticates with its identifier only.
     #[cfg_attr(
         feature = "derive",
         serde(default, skip_serializing_if = "Secret::is_undefined")
@@ -37,11 +66,43 @@ pub struct OAuth2Config {
     pub client_secret: Secret,
 
     /// URL of the authorization server's authorization endpoint.
+    ///
+    /// Not used by the client credentials and the JWT bearer grant
+    /// flows.
+    #[cfg_attr(feature = "derive", serde(default))]
     pub auth_url: String,
 
     /// URL of the authorization server's token endpoint.
     pub token_url: String,
 
+    /// URL of the authorization server's device authorization
+    /// endpoint.
+    ///
+    /// Required by the device authorization grant flow.
+    #[cfg_attr(feature = "derive", serde(default))]
+    pub device_authorization_url: Option<String>,
+
+    /// Private key used to sign the assertion of the JWT bearer grant
+    /// flow.
+    ///
+    /// It can be either a service account key in JSON format (as
+    /// issued by Google Cloud), or a PEM-encoded RSA private key. In
+    /// the latter case, the client identifier is used as issuer of
+    /// the assertion.
+    #[cfg_attr(
+        feature = "derive",
+        serde(default, skip_serializing_if = "Secret::is_undefined")
+    )]
+    pub service_account_key: Secret,
+
+    /// Subject the access token is requested for by the JWT bearer
+    /// grant flow.
+    ///
+    /// For Google Workspace domain-wide delegation, this is the email
+    /// address of the mailbox to access.
+    #[cfg_attr(feature = "derive", serde(default))]
+    pub subject: Option<String>,
+
     /// Access token returned by the token endpoint and used to access
     /// protected resources.
     #[cfg_attr(
@@ -58,6 +119,18 @@ pub struct OAuth2Config {
     )]
     pub refresh_token: Secret,
 
+    /// Issue time and lifetime of the access token, used to refresh
+    /// it shortly before it expires.
+    ///
+    /// Both values are stored in seconds, separated by a space. When
+    /// undefined, the access token is refreshed only once rejected by
+    /// the server.
+    #[cfg_attr(
+        feature = "derive",
+        serde(default, skip_serializing_if = "Secret::is_undefined")
+    )]
+    pub access_token_expiry: Secret,
+
     /// Enable the [PKCE](https://datatracker.ietf.org/doc/html/rfc7636) protection.
     /// The value must have a minimum length of 43 characters and a maximum length of 128 characters.
     /// Each character must be ASCII alphanumeric or one of the characters “-” / “.” / “_” / “~”.
@@ -74,6 +147,9 @@ pub struct OAuth2Config {
 impl OAuth2Config {
     pub const LOCALHOST: &'static str = "localhost";
 
+    /// How long before its expiry the access token gets refreshed.
+    pub const REFRESH_MARGIN: Duration = Duration::from_secs(60);
+
     /// Return the first available port on [`LOCALHOST`].
     pub fn get_first_available_port() -> Result<u16> {
         (49_152..65_535)
@@ -81,7 +157,39 @@ pub fn get_first_available_port() -> Result<u16> {
             .ok_or(Error::GetAvailablePortError)
     }
 
-    /// Resets the three secrets of the OAuth 2.0 configuration.
+    /// Discovers the endpoints of the given authorization server
+    /// issuer, then updates the current configuration with them.
+    ///
+    /// See [`AuthorizationServerMetadata::discover`].
+    pub async fn discover(&mut self, issuer: impl AsRef<str>) -> Result<()> {
+        let metadata = AuthorizationServerMetadata::discover(issuer)
+            .await
+            .map_err(Error::DiscoverOauthMetadataError)?;
+
+        if let Some(url) = metadata.authorization_endpoint {
+            self.auth_url = url;
+        }
+
+        if let Some(url) = metadata.token_endpoint {
+            self.token_url = url;
+        }
+
+        if let Some(url) = metadata.device_authorization_endpoint {
+            self.device_authorization_url = Some(url);
+        }
+
+        if metadata
+            .code_challenge_methods_supported
+            .iter()
+            .any(|m| m == "S256")
+        {
+            self.pkce = true;
+        }
+
+        Ok(())
+    }
+
+    /// Resets the secrets of the OAuth 2.0 configuration.
     pub async fn reset(&self) -> Result<()> {
         self.client_secret
             .delete_only_keyring()
@@ -95,12 +203,19 @@ pub async fn reset(&self) -> Result<()> {
             .delete_only_keyring()
             .await
             .map_err(Error::DeleteRefreshTokenOauthError)?;
+        self.access_token_expiry
+            .delete_only_keyring()
+            .await
+            .map_err(Error::DeleteAccessTokenExpiryOauthError)?;
         Ok(())
     }
 
-    /// If the access token is not defined, runs the authorization
-    /// code grant OAuth 2.0 flow in order to save the acces token and
-    /// the refresh token if present.
+    /// If the access token is not defined, runs the configured OAuth
+    /// 2.0 flow in order to save the acces token and the refresh
+    /// token if present.
+    ///
+    /// Unattended flows do not prompt the user: the client secret or
+    /// the service account key need to be defined beforehand.
     pub async fn configure(
         &self,
         get_client_secret: impl Fn() -> io::Result<String>,
@@ -109,17 +224,30 @@ pub async fn configure(
             return Ok(());
         }
 
-        let redirect_host = match self.redirect_host.as_ref() {
-            Some(host) => host.clone(),
-            None => OAuth2Config::LOCALHOST.to_owned(),
-        };
+        let tokens = match self.flow {
+            OAuth2Flow::AuthorizationCode => {
+                let client_secret = self.get_or_set_client_secret(get_client_secret).await?;
+                self.run_authorization_code_grant(client_secret).await
+            }
+            OAuth2Flow::DeviceAuthorization => {
+                let client_secret = self.find_client_secret().await?;
+                self.run_device_authorization_grant(client_secret).await
+            }
+            OAuth2Flow::ClientCredentials | OAuth2Flow::JwtBearer => {
+                self.run_unattended_grant().await
+            }
+        }?;
 
-        let redirect_port = match self.redirect_port {
-            Some(port) => port,
-            None => OAuth2Config::get_first_available_port()?,
-        };
+        self.save_tokens(&tokens).await
+    }
 
-        let client_secret = match self.client_secret.find().await {
+    /// Returns the client secret, prompting the user and saving it
+    /// into the keyring when not found.
+    async fn get_or_set_client_secret(
+        &self,
+        get_client_secret: impl Fn() -> io::Result<String>,
+    ) -> Result<String> {
+        match self.client_secret.find().await {
             Ok(None) => {
                 debug!("cannot find oauth2 client secret from keyring, setting it");
                 self.client_secret
@@ -131,7 +259,93 @@ pub async fn configure(
             }
             Ok(Some(client_secret)) => Ok(client_secret),
             Err(err) => Err(Error::GetClientSecretFromKeyringOauthError(err)),
-        }?;
+        }
+    }
+
+    /// Returns the client secret if existing, without prompting the
+    /// user. Public clients have no client secret.
+    async fn find_client_secret(&self) -> Result<Option<String>> {
+        self.client_secret
+            .find()
+            .await
+            .map_err(Error::GetClientSecretFromKeyringOauthError)
+    }
+
+    /// Creates a client authenticated with the given client secret,
+    /// or a public client when there is none.
+    fn new_client(&self, client_secret: Option<&str>) -> Result<Client> {
+        let client = match client_secret {
+            Some(client_secret) => Client::new(
+                self.client_id.clone(),
+                client_secret,
+                self.auth_url.clone(),
+                self.token_url.clone(),
+            ),
+            None => Client::new_public(
+                self.client_id.clone(),
+                self.auth_url.clone(),
+                self.token_url.clone(),
+            ),
+        };
+
+        client.map_err(Error::InitOauthClientError)
+    }
+
+    /// Saves the given tokens into the keyring, along with the expiry
+    /// of the access token.
+    ///
+    /// The refresh token is replaced only if a new one has been
+    /// issued (refresh token rotation).
+    async fn save_tokens(&self, tokens: &Tokens) -> Result<()> {
+        self.access_token
+            .set_only_keyring(&tokens.access_token)
+            .await
+            .map_err(Error::SetAccessTokenOauthError)?;
+
+        if let Some(refresh_token) = &tokens.refresh_token {
+            self.refresh_token
+                .set_only_keyring(refresh_token)
+                .await
+                .map_err(Error::SetRefreshTokenOauthError)?;
+        }
+
+        match tokens.expires_in {
+            Some(expires_in) => {
+                let issued_at = tokens
+                    .issued_at
+                    .duration_since(UNIX_EPOCH)
+                    .unwrap_or_default()
+                    .as_secs();
+                let expires_in = expires_in.as_secs();
+
+                self.access_token_expiry
+                    .set_only_keyring(format!("{issued_at} {expires_in}"))
+                    .await
+                    .map_err(Error::SetAccessTokenExpiryOauthError)?;
+            }
+            None => {
+                self.access_token_expiry
+                    .delete_only_keyring()
+                    .await
+                    .map_err(Error::DeleteAccessTokenExpiryOauthError)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the authorization code grant OAuth 2.0 flow, which
+    /// requires a browser and a local redirect server.
+    async fn run_authorization_code_grant(&self, client_secret: String) -> Result<Tokens> {
+        let redirect_host = match self.redirect_host.as_ref() {
+            Some(host) => host.clone(),
+            None => OAuth2Config::LOCALHOST.to_owned(),
+        };
+
+        let redirect_port = match self.redirect_port {
+            Some(port) => port,
+            None => OAuth2Config::get_first_available_port()?,
+        };
 
         let client = Client::new(
             self.client_id.clone(),
@@ -163,83 +377,300 @@ pub async fn configure(
         println!();
         println!("{}", redirect_url);
 
-        let (access_token, refresh_token) = auth_code_grant
+        auth_code_grant
             .wait_for_redirection(&client, csrf_token)
             .await
-            .map_err(Error::WaitForOauthRedirectionError)?;
+            .map_err(Error::WaitForOauthRedirectionError)
+    }
 
-        self.access_token
-            .set_only_keyring(access_token)
-            .await
-            .map_err(Error::SetAccessTokenOauthError)?;
+    /// Runs the device authorization grant OAuth 2.0 flow, which
+    /// lets the user authorize the access from another device.
+    async fn run_device_authorization_grant(
+        &self,
+        client_secret: Option<String>,
+    ) -> Result<Tokens> {
+        let device_authorization_url = self
+            .device_authorization_url
+            .as_ref()
+            .ok_or(Error::GetDeviceAuthorizationUrlOauthError)?;
 
-        if let Some(refresh_token) = &refresh_token {
-            self.refresh_token
-                .set_only_keyring(refresh_token)
-                .await
-                .map_err(Error::SetRefreshTokenOauthError)?;
+        let client = self
+            .new_client(client_secret.as_deref())?
+            .with_device_authorization_url(device_authorization_url)
+            .map_err(Error::InitOauthClientError)?
+            .build()
+            .map_err(Error::BuildOauthClientError)?;
+
+        let mut device_grant = DeviceAuthorizationGrant::new();
+
+        for scope in self.scopes.clone() {
+            device_grant = device_grant.with_scope(scope);
         }
 
-        Ok(())
+        let device_code = device_grant
+            .get_device_code(&client)
+            .await
+            .map_err(Error::GetDeviceCodeOauthError)?;
+
+        println!("To complete your OAuth 2.0 setup, go to the following link from any device:");
+        println!();
+        println!("{}", device_code.verification_uri().as_str());
+        println!();
+        println!("and enter the code {}", device_code.user_code().secret());
+
+        device_grant
+            .wait_for_access_token(&client, &device_code)
+            .await
+            .map_err(Error::WaitForOauthDeviceAccessTokenError)
     }
 
-    /// Runs the refresh access token OAuth 2.0 flow by exchanging a
-    /// refresh token with a new pair of access/refresh token.
-    pub async fn refresh_access_token(&self) -> Result<String> {
-        let redirect_port = OAuth2Config::get_first_available_port()?;
+    /// Runs an unattended OAuth 2.0 flow, which requires neither a
+    /// browser nor any user interaction.
+    ///
+    /// These flows do not issue refresh tokens, so they are also used
+    /// to renew the access token.
+    async fn run_unattended_grant(&self) -> Result<Tokens> {
+        match self.flow {
+            OAuth2Flow::JwtBearer => self.run_jwt_bearer_grant().await,
+            _ => self.run_client_credentials_grant().await,
+        }
+    }
 
+    /// Runs the client credentials grant OAuth 2.0 flow, which
+    /// authenticates the client on its own behalf.
+    async fn run_client_credentials_grant(&self) -> Result<Tokens> {
         let client_secret = self
             .client_secret
             .get()
             .await
             .map_err(Error::GetClientSecretFromKeyringOauthError)?;
 
+        // the authorization endpoint is not used by this flow, so it
+        // falls back to the token endpoint when undefined
+        let auth_url = if self.auth_url.is_empty() {
+            self.token_url.clone()
+        } else {
+            self.auth_url.clone()
+        };
+
         let client = Client::new(
             self.client_id.clone(),
             client_secret,
-            self.auth_url.clone(),
+            auth_url,
             self.token_url.clone(),
         )
         .map_err(Error::InitOauthClientError)?
-        .with_redirect_host(OAuth2Config::LOCALHOST.to_owned())
-        .with_redirect_port(redirect_port)
         .build()
         .map_err(Error::BuildOauthClientError)?;
 
-        let refresh_token = self
-            .refresh_token
-            .get()
+        let mut grant = ClientCredentialsGrant::new();
+
+        for scope in self.scopes.clone() {
+            grant = grant.with_scope(scope);
+        }
+
+        grant
+            .request_access_token(&client)
             .await
-            .map_err(Error::GetRefreshTokenOauthError)?;
+            .map_err(Error::RequestClientCredentialsAccessTokenOauthError)
+    }
 
-        let (access_token, refresh_token) = RefreshAccessToken::new()
-            .refresh_access_token(&client, refresh_token)
+    /// Runs the JWT bearer grant OAuth 2.0 flow, which exchanges an
+    /// assertion signed with the service account key for an access
+    /// token.
+    async fn run_jwt_bearer_grant(&self) -> Result<Tokens> {
+        let key = self
+            .service_account_key
+            .get()
             .await
-            .map_err(Error::RefreshAccessTokenOauthError)?;
+            .map_err(Error::GetServiceAccountKeyOauthError)?;
 
-        self.access_token
-            .set_only_keyring(&access_token)
+        let mut grant = if key.trim_start().starts_with('{') {
+            JwtBearerGrant::from_service_account_key_json(key)
+        } else {
+            JwtBearerGrant::new(&self.client_id, key)
+        }
+        .map_err(Error::InitJwtBearerGrantOauthError)?;
+
+        if let Some(subject) = &self.subject {
+            grant = grant.with_subject(subject);
+        }
+
+        for scope in self.scopes.clone() {
+            grant = grant.with_scope(scope);
+        }
+
+        grant
+            .request_access_token(&self.token_url)
             .await
-            .map_err(Error::SetAccessTokenOauthError)?;
+            .map_err(Error::RequestJwtBearerAccessTokenOauthError)
+    }
 
-        if let Some(refresh_token) = &refresh_token {
-            self.refresh_token
-                .set_only_keyring(refresh_token)
+    /// Runs the refresh access token OAuth 2.0 flow by exchanging a
+    /// refresh token with a new pair of access/refresh token.
+    ///
+    /// For unattended flows, which do not issue refresh tokens, the
+    /// flow is run again instead.
+    ///
+    /// Concurrent refreshes of the same access token are coalesced:
+    /// if the access token has been refreshed by someone else while
+    /// waiting, the new access token is returned without refreshing
+    /// it again.
+    pub async fn refresh_access_token(&self) -> Result<String> {
+        let requested_at = Instant::now();
+        let lock = self.refresh_lock();
+        let res = self.refresh_access_token_locked(&lock, requested_at).await;
+
+        // the lock is not needed anymore once nobody else waits for it
+        drop(lock);
+        evict_refresh_locks(&mut REFRESH_LOCKS.lock().unwrap_or_else(PoisonError::into_inner));
+
+        res
+    }
+
+    /// Refreshes the access token using the given refresh lock,
+    /// unless it has been refreshed since the given instant.
+    async fn refresh_access_token_locked(
+        &self,
+        lock: &RefreshLock,
+        requested_at: Instant,
+    ) -> Result<String> {
+        let mut last_refresh = lock.lock().await;
+
+        if matches!(*last_refresh, Some(at) if at >= requested_at) {
+            debug!("access token already refreshed by a concurrent task");
+            return self
+                .access_token
+                .get()
                 .await
-                .map_err(Error::SetRefreshTokenOauthError)?;
+                .map_err(Error::GetAccessTokenOauthError);
         }
 
-        Ok(access_token)
+        let tokens = if self.flow.is_unattended() {
+            self.run_unattended_grant().await?
+        } else {
+            self.run_refresh_token_grant().await?
+        };
+
+        self.save_tokens(&tokens).await?;
+        *last_refresh = Some(Instant::now());
+
+        Ok(tokens.access_token)
+    }
+
+    /// Exchanges the refresh token for a new access token and maybe a
+    /// new refresh token.
+    async fn run_refresh_token_grant(&self) -> Result<Tokens> {
+        let redirect_port = OAuth2Config::get_first_available_port()?;
+
+        let client_secret = self.find_client_secret().await?;
+
+        let client = self
+            .new_client(client_secret.as_deref())?
+            .with_redirect_host(OAuth2Config::LOCALHOST.to_owned())
+            .with_redirect_port(redirect_port)
+            .build()
+            .map_err(Error::BuildOauthClientError)?;
+
+        let refresh_token = self
+            .refresh_token
+            .get()
+            .await
+            .map_err(Error::GetRefreshTokenOauthError)?;
+
+        RefreshAccessToken::new()
+            .refresh_access_token(&client, refresh_token)
+            .await
+            .map_err(Error::RefreshAccessTokenOauthError)
     }
 
     /// Returns the access token if existing, otherwise returns an
     /// error.
+    ///
+    /// The access token is proactively refreshed when it expires in
+    /// less than [`OAuth2Config::REFRESH_MARGIN`]. For unattended
+    /// flows, the access token is requested on demand when missing.
     pub async fn access_token(&self) -> Result<String> {
+        if self.flow.is_unattended() && matches!(self.access_token.find().await, Ok(None)) {
+            debug!("access token not found, requesting it");
+            return self.refresh_access_token().await;
+        }
+
+        if self.access_token_expires_in().await == Some(Duration::ZERO) {
+            debug!("access token about to expire, refreshing it");
+
+            match self.refresh_access_token().await {
+                Ok(access_token) => return Ok(access_token),
+                Err(err) => warn!("cannot refresh access token, using current one: {err}"),
+            }
+        }
+
         self.access_token
             .get()
             .await
             .map_err(Error::GetAccessTokenOauthError)
     }
+
+    /// Returns the time left before the access token needs to be
+    /// refreshed, which is [`OAuth2Config::REFRESH_MARGIN`] before
+    /// its actual expiry.
+    ///
+    /// Returns [`None`] if the expiry of the access token is unknown.
+    pub async fn access_token_expires_in(&self) -> Option<Duration> {
+        let expiry = match self.access_token_expiry.find().await {
+            Ok(expiry) => expiry?,
+            Err(err) => {
+                debug!("cannot get access token expiry: {err}");
+                return None;
+            }
+        };
+
+        let Some((issued_at, expires_in)) = parse_access_token_expiry(&expiry) else {
+            debug!("cannot parse access token expiry {expiry:?}");
+            return None;
+        };
+
+        let refresh_at = issued_at + expires_in.saturating_sub(Self::REFRESH_MARGIN);
+        let expires_in = refresh_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default();
+
+        Some(expires_in)
+    }
+
+    /// Returns the lock coalescing refreshes of the current access
+    /// token.
+    fn refresh_lock(&self) -> RefreshLock {
+        let mut locks = REFRESH_LOCKS.lock().unwrap_or_else(PoisonError::into_inner);
+        evict_refresh_locks(&mut locks);
+
+        if let Some((_, lock)) = locks.iter().find(|(token, _)| *token == self.access_token) {
+            return lock.clone();
+        }
+
+        let lock = Arc::new(Mutex::new(None));
+        locks.push((self.access_token.clone(), lock.clone()));
+        lock
+    }
+}
+
+/// Removes the refresh locks nobody holds anymore.
+///
+/// Locks are only handed out while the locks list is locked, so a
+/// lock referenced only by the list cannot be reused meanwhile. The
+/// time of its last refresh only matters to concurrent refreshes, so
+/// it can be dropped too.
+fn evict_refresh_locks(locks: &mut Vec<(Secret, RefreshLock)>) {
+    locks.retain(|(_, lock)| Arc::strong_count(lock) > 1);
+}
+
+/// Parses the access token expiry, stored as an issue time and a
+/// lifetime in seconds separated by a space.
+fn parse_access_token_expiry(expiry: &str) -> Option<(SystemTime, Duration)> {
+    let (issued_at, expires_in) = expiry.trim().split_once(' ')?;
+    let issued_at = UNIX_EPOCH + Duration::from_secs(issued_at.parse().ok()?);
+    let expires_in = Duration::from_secs(expires_in.parse().ok()?);
+    Some((issued_at, expires_in))
 }
 
 /// Method for presenting an OAuth 2.0 bearer token to a service for
@@ -267,6 +698,181 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Built-in OAuth 2.0 configurations of well-known email providers.
+///
+/// Only the client identifier and the client secret are left to
+/// define, since they are specific to the registered application.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum OAuth2Provider {
+    /// Gmail, including Google Workspace.
+    Gmail,
+
+    /// Microsoft 365, including Outlook.com.
+    Microsoft365,
+}
+
+impl OAuth2Provider {
+    /// Finds the provider operating the given mail server hostname.
+    pub fn from_hostname(hostname: impl AsRef<str>) -> Option<Self> {
+        let hostname = hostname.as_ref().trim_end_matches('.').to_lowercase();
+        let is_subdomain_of =
+            |domain: &&str| hostname == *domain || hostname.ends_with(&format!(".{domain}"));
+
+        if ["gmail.com", "googlemail.com"].iter().any(is_subdomain_of) {
+            return Some(Self::Gmail);
+        }
+
+        if ["office365.com", "outlook.com"].iter().any(is_subdomain_of) {
+            return Some(Self::Microsoft365);
+        }
+
+        None
+    }
+
+    /// The issuer identifier of the provider's authorization server.
+    pub fn issuer(&self) -> &'static str {
+        match self {
+            Self::Gmail => "https://accounts.google.com",
+            Self::Microsoft365 => "https://login.microsoftonline.com/common/v2.0",
+        }
+    }
+
+    /// URL of the provider's authorization endpoint.
+    pub fn auth_url(&self) -> &'static str {
+        match self {
+            Self::Gmail => "https://accounts.google.com/o/oauth2/v2/auth",
+            Self::Microsoft365 => "https://login.microsoftonline.com/common/oauth2/v2.0/authorize",
+        }
+    }
+
+    /// URL of the provider's token endpoint.
+    pub fn token_url(&self) -> &'static str {
+        match self {
+            Self::Gmail => "https://oauth2.googleapis.com/token",
+            Self::Microsoft365 => "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+        }
+    }
+
+    /// URL of the provider's device authorization endpoint.
+    pub fn device_authorization_url(&self) -> &'static str {
+        match self {
+            Self::Gmail => "https://oauth2.googleapis.com/device/code",
+            Self::Microsoft365 => "https://login.microsoftonline.com/common/oauth2/v2.0/devicecode",
+        }
+    }
+
+    /// Method for presenting the access token to the IMAP and SMTP
+    /// servers of the provider.
+    pub fn method(&self) -> OAuth2Method {
+        OAuth2Method::XOAuth2
+    }
+
+    /// Scopes required to access the provider's IMAP server.
+    pub fn imap_scopes(&self) -> &'static [&'static str] {
+        match self {
+            Self::Gmail => &["https://mail.google.com/"],
+            Self::Microsoft365 => &[
+                "https://outlook.office.com/IMAP.AccessAsUser.All",
+                "offline_access",
+            ],
+        }
+    }
+
+    /// Scopes required to access the provider's SMTP server.
+    pub fn smtp_scopes(&self) -> &'static [&'static str] {
+        match self {
+            Self::Gmail => &["https://mail.google.com/"],
+            Self::Microsoft365 => &["https://outlook.office.com/SMTP.Send", "offline_access"],
+        }
+    }
+
+    /// Builds the OAuth 2.0 configuration of the provider's IMAP
+    /// server.
+    pub fn imap_config(&self) -> OAuth2Config {
+        self.config(self.imap_scopes())
+    }
+
+    /// Builds the OAuth 2.0 configuration of the provider's SMTP
+    /// server.
+    pub fn smtp_config(&self) -> OAuth2Config {
+        self.config(self.smtp_scopes())
+    }
+
+    fn config(&self, scopes: &[&str]) -> OAuth2Config {
+        OAuth2Config {
+            method: self.method(),
+            auth_url: self.auth_url().to_owned(),
+            token_url: self.token_url().to_owned(),
+            device_authorization_url: Some(self.device_authorization_url().to_owned()),
+            pkce: true,
+            scopes: OAuth2Scopes::Scopes(scopes.iter().map(ToString::to_string).collect()),
+            ..Default::default()
+        }
+    }
+}
+
+impl fmt::Display for OAuth2Provider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gmail => write!(f, "Gmail"),
+            Self::Microsoft365 => write!(f, "Microsoft 365"),
+        }
+    }
+}
+
+/// Flow used to obtain the first OAuth 2.0 access token.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum OAuth2Flow {
+    /// The authorization code grant flow, as defined in the
+    /// [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1).
+    ///
+    /// The user is redirected to a local server once the access is
+    /// authorized from the browser.
+    #[default]
+    AuthorizationCode,
+
+    /// The device authorization grant flow, as defined in the
+    /// [RFC8628](https://datatracker.ietf.org/doc/html/rfc8628).
+    ///
+    /// The user authorizes the access from another device by
+    /// entering a code. This flow suits SSH sessions and headless
+    /// servers, where no local redirection is possible.
+    DeviceAuthorization,
+
+    /// The client credentials grant flow, as defined in the
+    /// [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749#section-4.4).
+    ///
+    /// The client authenticates on its own behalf with its client
+    /// secret, without any user interaction.
+    ClientCredentials,
+
+    /// The JWT bearer grant flow, as defined in the
+    /// [RFC7523](https://datatracker.ietf.org/doc/html/rfc7523#section-2.1).
+    ///
+    /// The client authenticates with an assertion signed with the
+    /// service account key, without any user interaction. Combined
+    /// with a subject, it allows Google Workspace domain-wide
+    /// delegation.
+    JwtBearer,
+}
+
+impl OAuth2Flow {
+    /// Returns `true` if the flow requires no user interaction.
+    pub fn is_unattended(&self) -> bool {
+        matches!(self, Self::ClientCredentials | Self::JwtBearer)
+    }
+}
+
 /// Access token scope(s), as defined by the authorization server.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(
@@ -296,3 +902,256 @@ fn into_iter(self) -> Self::IntoIter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        time::{Duration, SystemTime, UNIX_EPOCH},
+    };
+
+    use secret::{
+        keyring::{set_global_backend, KeyringBackend, KeyringEntry},
+        Secret,
+    };
+    use tokio::{
+        io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+        net::TcpListener,
+        time::sleep,
+    };
+
+    use super::{OAuth2Config, OAuth2Provider};
+
+    /// Spawn a fake token endpoint counting the refreshes. The n-th
+    /// refresh returns the access token `access-n` and the rotated
+    /// refresh token `refresh-n`, valid for one hour.
+    async fn spawn_token_endpoint() -> (String, Arc<AtomicUsize>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let token_url = format!("http://{}/token", listener.local_addr().unwrap());
+        let count = Arc::new(AtomicUsize::new(0));
+
+        let counter = count.clone();
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut line = String::new();
+                while reader.read_line(&mut line).await.unwrap() > 2 {
+                    line.clear();
+                }
+
+                // leave time for concurrent refreshes to pile up
+                sleep(Duration::from_millis(100)).await;
+
+                let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                let body = format!(
+                    r#"{{"access_token":"access-{n}","token_type":"bearer","expires_in":3600,"refresh_token":"refresh-{n}"}}"#
+                );
+                let res = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                reader.get_mut().write_all(res.as_bytes()).await.unwrap();
+            }
+        });
+
+        (token_url, count)
+    }
+
+    /// Build a configuration storing its tokens in the memory
+    /// keyring, under entries prefixed by the given name.
+    async fn keyring_config(name: &str, token_url: String, expiry: SystemTime) -> OAuth2Config {
+        set_global_backend(KeyringBackend::Memory);
+
+        let entry = |key: &str| {
+            let entry = KeyringEntry::try_new(format!("{name}-{key}")).unwrap();
+            Secret::new_keyring_entry(entry)
+        };
+
+        let config = OAuth2Config {
+            client_id: String::from("id"),
+            client_secret: Secret::new_raw("secret"),
+            auth_url: String::from("http://127.0.0.1/auth"),
+            token_url,
+            access_token: entry("access-token"),
+            refresh_token: entry("refresh-token"),
+            access_token_expiry: entry("access-token-expiry"),
+            ..Default::default()
+        };
+
+        let issued_at = expiry - Duration::from_secs(3600);
+        let issued_at = issued_at.duration_since(UNIX_EPOCH).unwrap().as_secs();
+
+        config
+            .access_token
+            .set_only_keyring("access-0")
+            .await
+            .unwrap();
+        config
+            .refresh_token
+            .set_only_keyring("refresh-0")
+            .await
+            .unwrap();
+        config
+            .access_token_expiry
+            .set_only_keyring(format!("{issued_at} 3600"))
+            .await
+            .unwrap();
+
+        config
+    }
+
+    /// Spawn a fake metadata endpoint mimicking the multi-tenant
+    /// endpoint of the Microsoft identity platform, which advertises
+    /// a templated issuer.
+    async fn spawn_metadata_endpoint() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let metadata = format!(
+            r#"{{"issuer":"{base_url}/{{tenantid}}/v2.0","authorization_endpoint":"{base_url}/common/oauth2/v2.0/authorize","token_endpoint":"{base_url}/common/oauth2/v2.0/token","device_authorization_endpoint":"{base_url}/common/oauth2/v2.0/devicecode","code_challenge_methods_supported":["plain","S256"]}}"#
+        );
+
+        tokio::spawn(async move {
+            loop {
+                let (stream, _) = listener.accept().await.unwrap();
+                let mut reader = BufReader::new(stream);
+
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).await.unwrap();
+
+                let mut line = String::new();
+                while reader.read_line(&mut line).await.unwrap() > 2 {
+                    line.clear();
+                }
+
+                let (status, body) = if request_line
+                    .starts_with("GET /common/v2.0/.well-known/openid-configuration ")
+                {
+                    ("200 OK", metadata.as_str())
+                } else {
+                    ("404 Not Found", "")
+                };
+                let res = format!(
+                    "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                );
+                reader.get_mut().write_all(res.as_bytes()).await.unwrap();
+            }
+        });
+
+        base_url
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn discover() {
+        let base_url = spawn_metadata_endpoint().await;
+
+        let mut config = OAuth2Config::default();
+        config
+            .discover(format!("{base_url}/common/v2.0"))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            config.auth_url,
+            format!("{base_url}/common/oauth2/v2.0/authorize")
+        );
+        assert_eq!(
+            config.token_url,
+            format!("{base_url}/common/oauth2/v2.0/token")
+        );
+        assert_eq!(
+            config.device_authorization_url,
+            Some(format!("{base_url}/common/oauth2/v2.0/devicecode"))
+        );
+        assert!(config.pkce);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn refresh_access_token_coalesced() {
+        let (token_url, count) = spawn_token_endpoint().await;
+        let expiry = SystemTime::now() + Duration::from_secs(3600);
+        let config = keyring_config("coalesced", token_url, expiry).await;
+
+        let refreshes = (0..8).map(|_| {
+            let config = config.clone();
+            tokio::spawn(async move { config.refresh_access_token().await.unwrap() })
+        });
+
+        for refresh in refreshes.collect::<Vec<_>>() {
+            assert_eq!(refresh.await.unwrap(), "access-1");
+        }
+
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // the rotated refresh token replaces the previous one
+        let refresh_token = config.refresh_token.get().await.unwrap();
+        assert_eq!(refresh_token, "refresh-1");
+
+        // a later refresh is not coalesced
+        assert_eq!(config.refresh_access_token().await.unwrap(), "access-2");
+        assert_eq!(count.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn access_token_refreshed_before_expiry() {
+        let (token_url, count) = spawn_token_endpoint().await;
+        let expiry = SystemTime::now() + Duration::from_secs(3600);
+        let config = keyring_config("fresh", token_url, expiry).await;
+
+        // the access token is far from its expiry
+        assert!(config.access_token_expires_in().await.unwrap() > Duration::from_secs(3000));
+        assert_eq!(config.access_token().await.unwrap(), "access-0");
+        assert_eq!(count.load(Ordering::SeqCst), 0);
+
+        // the access token expires within the refresh margin
+        let (token_url, count) = spawn_token_endpoint().await;
+        let expiry = SystemTime::now() + OAuth2Config::REFRESH_MARGIN / 2;
+        let config = keyring_config("expiring", token_url, expiry).await;
+
+        assert_eq!(config.access_token_expires_in().await, Some(Duration::ZERO));
+        assert_eq!(config.access_token().await.unwrap(), "access-1");
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+
+        // the new expiry is saved along with the new access token
+        assert!(config.access_token_expires_in().await.unwrap() > Duration::from_secs(3000));
+        assert_eq!(config.access_token().await.unwrap(), "access-1");
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn provider_from_hostname() {
+        assert_eq!(
+            OAuth2Provider::from_hostname("imap.gmail.com"),
+            Some(OAuth2Provider::Gmail),
+        );
+        assert_eq!(
+            OAuth2Provider::from_hostname("smtp.googlemail.com."),
+            Some(OAuth2Provider::Gmail),
+        );
+        assert_eq!(
+            OAuth2Provider::from_hostname("outlook.office365.com"),
+            Some(OAuth2Provider::Microsoft365),
+        );
+        assert_eq!(OAuth2Provider::from_hostname("imap.notgmail.com"), None);
+        assert_eq!(OAuth2Provider::from_hostname("localhost"), None);
+    }
+
+    #[test]
+    fn parse_access_token_expiry() {
+        assert_eq!(
+            super::parse_access_token_expiry("1700000000 3600"),
+            Some((
+                UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+                Duration::from_secs(3600)
+            )),
+        );
+
+        assert_eq!(super::parse_access_token_expiry("1700000000"), None);
+        assert_eq!(super::parse_access_token_expiry("now 3600"), None);
+    }
+}
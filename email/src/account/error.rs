@@ -24,6 +24,8 @@ pub enum Error {
     ParseDownloadFileNameError(PathBuf),
     #[error("cannot get file name from path {0}")]
     GetFileNameFromPathSyncError(PathBuf),
+    #[error("cannot download attachment {1}: {0}")]
+    CheckAttachmentError(mml::message::SkipReason, PathBuf),
     #[cfg(feature = "oauth2")]
     #[error("cannot create oauth2 client")]
     InitOauthClientError(#[source] oauth::v2_0::Error),
@@ -33,6 +35,28 @@ pub enum Error {
     #[cfg(feature = "oauth2")]
     #[error("cannot wait for oauth2 redirection error")]
     WaitForOauthRedirectionError(#[source] oauth::v2_0::Error),
+    #[error("cannot find oauth2 device authorization url")]
+    GetDeviceAuthorizationUrlOauthError,
+    #[cfg(feature = "oauth2")]
+    #[error("cannot discover oauth2 authorization server metadata")]
+    DiscoverOauthMetadataError(#[source] oauth::v2_0::Error),
+    #[cfg(feature = "oauth2")]
+    #[error("cannot get oauth2 device code")]
+    GetDeviceCodeOauthError(#[source] oauth::v2_0::Error),
+    #[cfg(feature = "oauth2")]
+    #[error("cannot wait for oauth2 device access token")]
+    WaitForOauthDeviceAccessTokenError(#[source] oauth::v2_0::Error),
+    #[cfg(feature = "oauth2")]
+    #[error("cannot request oauth2 access token using client credentials")]
+    RequestClientCredentialsAccessTokenOauthError(#[source] oauth::v2_0::Error),
+    #[error("cannot get oauth2 service account key")]
+    GetServiceAccountKeyOauthError(#[source] secret::Error),
+    #[cfg(feature = "oauth2")]
+    #[error("cannot create oauth2 jwt bearer grant")]
+    InitJwtBearerGrantOauthError(#[source] oauth::v2_0::Error),
+    #[cfg(feature = "oauth2")]
+    #[error("cannot request oauth2 access token using jwt bearer assertion")]
+    RequestJwtBearerAccessTokenOauthError(#[source] oauth::v2_0::Error),
 
     #[error("cannot get oauth2 access token from global keyring")]
     GetAccessTokenOauthError(#[source] secret::Error),
@@ -50,6 +74,10 @@ pub enum Error {
     SetRefreshTokenOauthError(#[source] secret::Error),
     #[error("cannot delete oauth2 refresh token from global keyring")]
     DeleteRefreshTokenOauthError(#[source] secret::Error),
+    #[error("cannot set oauth2 access token expiry")]
+    SetAccessTokenExpiryOauthError(#[source] secret::Error),
+    #[error("cannot delete oauth2 access token expiry from global keyring")]
+    DeleteAccessTokenExpiryOauthError(#[source] secret::Error),
 
     #[error("cannot get oauth2 client secret from user")]
     GetClientSecretFromUserOauthError(#[source] io::Error),
@@ -9,6 +9,9 @@
 
 use serde::Deserialize;
 
+#[cfg(feature = "oauth2")]
+use crate::account::config::oauth2::OAuth2Provider;
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 /// The root level of the Mozilla Autoconfiguration.
@@ -40,6 +43,17 @@ pub fn email_provider(&self) -> &EmailProvider {
     pub fn oauth2(&self) -> Option<&OAuth2Config> {
         self.oauth2.as_ref()
     }
+
+    /// The built-in OAuth 2.0 provider operating the mail servers,
+    /// if any.
+    #[cfg(feature = "oauth2")]
+    pub fn oauth2_provider(&self) -> Option<OAuth2Provider> {
+        self.email_provider
+            .servers()
+            .into_iter()
+            .filter_map(Server::hostname)
+            .find_map(OAuth2Provider::from_hostname)
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -77,6 +91,26 @@ pub fn token_url(&self) -> &str {
     }
 }
 
+#[cfg(feature = "oauth2")]
+impl From<OAuth2Provider> for OAuth2Config {
+    fn from(provider: OAuth2Provider) -> Self {
+        let mut scopes = provider.imap_scopes().to_vec();
+
+        for scope in provider.smtp_scopes() {
+            if !scopes.contains(scope) {
+                scopes.push(*scope)
+            }
+        }
+
+        Self {
+            issuer: provider.issuer().to_owned(),
+            scope: scopes.join(" "),
+            auth_url: provider.auth_url().to_owned(),
+            token_url: provider.token_url().to_owned(),
+        }
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct EmailProvider {
     pub id: String,
@@ -20,6 +20,10 @@
 //!   - Check mailconf URI in example.com TXT records
 //!   - Build autoconfig from imap and submission example.com SRV records
 //!
+//! When the discovered configuration has no OAuth 2.0 section but
+//! its mail servers are operated by a built-in OAuth 2.0 provider
+//! (see `OAuth2Provider`), the section is filled from the provider.
+//!
 //! [Autoconfiguration]: https://udn.realityripple.com/docs/Mozilla/Thunderbird/Autoconfiguration
 
 pub mod config;
@@ -83,14 +87,34 @@ pub async fn from_addr(addr: impl AsRef<str>) -> Result<AutoConfig> {
         .map_err(|e| Error::ParsingEmailAddress(addr.as_ref().to_string(), e))?;
     let http = HttpClient::new()?;
 
-    match from_isps(&http, &addr).await {
-        Ok(config) => Ok(config),
+    let config = match from_isps(&http, &addr).await {
+        Ok(config) => config,
         Err(_err) => {
             trace!("{_err}");
             debug!("ISP discovery failed for {addr}, falling back to DNS");
-            from_dns(&http, &addr).await
+            from_dns(&http, &addr).await?
+        }
+    };
+
+    #[cfg(feature = "oauth2")]
+    let config = with_oauth2_provider(config);
+
+    Ok(config)
+}
+
+/// Fill the OAuth 2.0 section of the given configuration using the
+/// built-in provider operating its mail servers, if the section is
+/// missing.
+#[cfg(feature = "oauth2")]
+fn with_oauth2_provider(mut config: AutoConfig) -> AutoConfig {
+    if config.oauth2.is_none() {
+        if let Some(provider) = config.oauth2_provider() {
+            debug!("using built-in {provider} OAuth 2.0 configuration");
+            config.oauth2 = Some(provider.into());
         }
     }
+
+    config
 }
 
 /// Discover configuration associated to a given email address using
@@ -344,3 +368,61 @@ async fn from_dns_srv(
 
     Ok(config)
 }
+
+#[cfg(all(test, feature = "oauth2"))]
+mod tests {
+    use super::{
+        config::{
+            AutoConfig, EmailProvider, EmailProviderProperty, Server, ServerProperty, ServerType,
+        },
+        with_oauth2_provider,
+    };
+
+    fn autoconfig(hostname: &str) -> AutoConfig {
+        AutoConfig {
+            version: String::from("1.1"),
+            email_provider: EmailProvider {
+                id: String::from("example.com"),
+                properties: vec![EmailProviderProperty::IncomingServer(Server {
+                    r#type: ServerType::Exchange,
+                    properties: vec![ServerProperty::Hostname(hostname.to_owned())],
+                })],
+            },
+            oauth2: None,
+        }
+    }
+
+    #[test]
+    fn oauth2_from_provider() {
+        let config = with_oauth2_provider(autoconfig("outlook.office365.com"));
+        let oauth2 = config.oauth2().unwrap();
+
+        assert_eq!(
+            oauth2.issuer(),
+            "https://login.microsoftonline.com/common/v2.0"
+        );
+        assert_eq!(
+            oauth2.auth_url(),
+            "https://login.microsoftonline.com/common/oauth2/v2.0/authorize"
+        );
+        assert_eq!(
+            oauth2.token_url(),
+            "https://login.microsoftonline.com/common/oauth2/v2.0/token"
+        );
+        // IMAP and SMTP scopes are merged without duplicates
+        assert_eq!(
+            oauth2.scope(),
+            vec![
+                "https://outlook.office.com/IMAP.AccessAsUser.All",
+                "offline_access",
+                "https://outlook.office.com/SMTP.Send",
+            ]
+        );
+    }
+
+    #[test]
+    fn oauth2_without_provider() {
+        let config = with_oauth2_provider(autoconfig("imap.example.com"));
+        assert!(config.oauth2().is_none());
+    }
+}
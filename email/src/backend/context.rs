@@ -156,6 +156,7 @@ fn try_to_sync_cache_builder(
             signature: account_config.signature.clone(),
             signature_delim: account_config.signature_delim.clone(),
             downloads_dir: account_config.downloads_dir.clone(),
+            attachment_policy: account_config.attachment_policy.clone(),
             folder: account_config.folder.clone(),
             envelope: account_config.envelope.clone(),
             flag: account_config.flag.clone(),
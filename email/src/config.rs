@@ -75,6 +75,7 @@ pub fn account(&self, name: impl AsRef<str>) -> Result<AccountConfig> {
                 .as_ref()
                 .map(ToOwned::to_owned)
                 .or_else(|| self.downloads_dir.as_ref().map(ToOwned::to_owned)),
+            attachment_policy: account_config.attachment_policy.clone(),
             folder: account_config.folder.clone(),
             envelope: account_config.envelope.clone(),
             flag: account_config.flag.clone(),
@@ -109,11 +109,7 @@ macro_rules! retry {
                         #[cfg(feature = "tracing")]
 			tracing::debug!("re-connecting…");
 
-			$self.inner = $self.client_builder.build().await?;
-
-			if let Some(mbox) = &$self.mailbox {
-			    $self.inner.select(mbox.clone()).await.map_err(Error::SelectMailboxError)?;
-			}
+			$self.reconnect().await?;
 
 			retry.attempts = 0;
 			continue;
@@ -185,6 +181,9 @@ pub struct ImapClient {
 
     /// The selected mailbox.
     mailbox: Option<String>,
+
+    /// Whether the selected mailbox has been examined (read-only).
+    mailbox_read_only: bool,
 }
 
 impl ImapClient {
@@ -192,6 +191,28 @@ pub fn ext_sort_supported(&self) -> bool {
         self.inner.ext_sort_supported()
     }
 
+    /// Re-connects the client, then selects (or examines) again the
+    /// previously selected mailbox.
+    async fn reconnect(&mut self) -> Result<()> {
+        self.inner = self.client_builder.build().await?;
+
+        if let Some(mbox) = &self.mailbox {
+            if self.mailbox_read_only {
+                self.inner
+                    .examine(mbox.clone())
+                    .await
+                    .map_err(Error::ExamineMailboxError)?;
+            } else {
+                self.inner
+                    .select(mbox.clone())
+                    .await
+                    .map_err(Error::SelectMailboxError)?;
+            }
+        }
+
+        Ok(())
+    }
+
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(client = self.id)))]
     pub async fn noop(&mut self) -> Result<()> {
         retry!(self, self.inner.noop(), NoOp)
@@ -201,12 +222,16 @@ pub async fn noop(&mut self) -> Result<()> {
     pub async fn select_mailbox(&mut self, mbox: impl ToString) -> Result<SelectDataUnvalidated> {
         let data = retry!(self, self.inner.select(mbox.to_string()), SelectMailbox)?;
         self.mailbox = Some(mbox.to_string());
+        self.mailbox_read_only = false;
         Ok(data)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(client = self.id)))]
     pub async fn examine_mailbox(&mut self, mbox: impl ToString) -> Result<SelectDataUnvalidated> {
-        retry!(self, self.inner.examine(mbox.to_string()), ExamineMailbox)
+        let data = retry!(self, self.inner.examine(mbox.to_string()), ExamineMailbox)?;
+        self.mailbox = Some(mbox.to_string());
+        self.mailbox_read_only = true;
+        Ok(data)
     }
 
     #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(client = self.id)))]
@@ -369,11 +394,25 @@ pub async fn idle(
     ) -> Result<()> {
         let tag = self.inner.enqueue_idle();
 
+        // OAuth 2.0 sessions may be closed by the server once the
+        // access token expires, so IDLE is stopped beforehand in
+        // order to re-connect with a fresh access token
+        let reauth_in = self
+            .client_builder
+            .find_access_token_expires_in()
+            .await
+            .filter(|duration| !duration.is_zero());
+
         select! {
             output = self.inner.idle(tag.clone()) => {
                 output.map_err(Error::StartIdleError)?;
                 Ok(())
             },
+            _ = sleep(reauth_in.unwrap_or_default()), if reauth_in.is_some() => {
+                debug!("access token about to expire, re-connecting…");
+                self.inner.idle_done(tag.clone()).await.map_err(Error::StopIdleError)?;
+                self.reconnect().await
+            }
             _ = wait_for_shutdown_request => {
                 debug!("shutdown requested, sending done command…");
                 self.inner.idle_done(tag.clone()).await.map_err(Error::StopIdleError)?;
@@ -771,6 +810,7 @@ async fn build(self) -> AnyResult<Self::Context> {
                 client_builder,
                 inner,
                 mailbox: None,
+                mailbox_read_only: false,
             }))),
         })
         .collect::<Vec<_>>()
@@ -828,13 +868,24 @@ pub fn new(config: Arc<ImapConfig>, credentials: Option<String>) -> Self {
         }
     }
 
+    /// Returns the time left before the OAuth 2.0 access token needs
+    /// to be refreshed, if known.
+    pub async fn find_access_token_expires_in(&self) -> Option<Duration> {
+        match &self.config.auth {
+            ImapAuthConfig::Passwd(_) => None,
+            #[cfg(feature = "oauth2")]
+            ImapAuthConfig::OAuth2(oauth2) => oauth2.access_token_expires_in().await,
+        }
+    }
+
     /// Creates a new session from an IMAP configuration and optional
     /// pre-built credentials.
     ///
     /// Pre-built credentials are useful to prevent building them
     /// every time a new session is created. The main use case is for
     /// the synchronization, where multiple sessions can be created in
-    /// a row.
+    /// a row. They are not used by OAuth 2.0, since access tokens may
+    /// need to be refreshed.
     #[cfg_attr(
         feature = "tracing",
         tracing::instrument(name = "client::build", skip(self))
@@ -958,13 +1009,12 @@ pub async fn build(&mut self) -> Result<Client> {
 
                         debug!("using XOAUTH2 auth mechanism");
 
-                        let access_token = match self.credentials.as_ref() {
-                            Some(access_token) => access_token.to_string(),
-                            None => oauth2
-                                .access_token()
-                                .await
-                                .map_err(Error::RefreshAccessTokenError)?,
-                        };
+                        // pre-built credentials are not used, since the
+                        // access token may need to be refreshed
+                        let access_token = oauth2
+                            .access_token()
+                            .await
+                            .map_err(Error::RefreshAccessTokenError)?;
 
                         let auth = client
                             .authenticate_xoauth2(self.config.login.as_str(), access_token.as_str())
@@ -985,8 +1035,6 @@ pub async fn build(&mut self) -> Result<Client> {
                                 )
                                 .await
                                 .map_err(Error::AuthenticateXOauth2Error)?;
-
-                            self.credentials = Some(access_token);
                         }
                     }
                     OAuth2Method::OAuthBearer => {
@@ -997,13 +1045,12 @@ pub async fn build(&mut self) -> Result<Client> {
 
                         debug!("using OAUTHBEARER auth mechanism");
 
-                        let access_token = match self.credentials.as_ref() {
-                            Some(access_token) => access_token.to_string(),
-                            None => oauth2
-                                .access_token()
-                                .await
-                                .map_err(Error::RefreshAccessTokenError)?,
-                        };
+                        // pre-built credentials are not used, since the
+                        // access token may need to be refreshed
+                        let access_token = oauth2
+                            .access_token()
+                            .await
+                            .map_err(Error::RefreshAccessTokenError)?;
 
                         let auth = client
                             .authenticate_oauthbearer(
@@ -1031,8 +1078,6 @@ pub async fn build(&mut self) -> Result<Client> {
                                 )
                                 .await
                                 .map_err(Error::AuthenticateOAuthBearerError)?;
-
-                            self.credentials = Some(access_token);
                         }
                     }
                 }
@@ -220,6 +220,10 @@ pub fn replace_undefined_keyring_entries(&mut self, name: impl AsRef<str>) -> Re
                     .refresh_token
                     .replace_undefined_to_keyring(format!("{name}-smtp-oauth2-refresh-token"))
                     .map_err(Error::ReplacingKeyringFailed)?;
+                config
+                    .access_token_expiry
+                    .replace_undefined_to_keyring(format!("{name}-smtp-oauth2-access-token-expiry"))
+                    .map_err(Error::ReplacingKeyringFailed)?;
             }
         }
 
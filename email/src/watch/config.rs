@@ -17,8 +17,22 @@
 pub struct WatchHook {
     /// Execute the shell command.
     ///
-    /// For now, command is executed without any parameter nor
-    /// input. This may change in the future.
+    /// The command is executed without input. It is rendered as a
+    /// lenient command template (see
+    /// [`process::SingleCommand::render_lenient`]): envelope values
+    /// are quoted so that they cannot inject shell code, even when
+    /// placeholders are placed inside quotes. Other braces are kept
+    /// as they are.
+    ///
+    /// Accepted placeholders:
+    ///  - "{id}": the id of the envelope
+    ///  - "{subject}": the subject of the envelope
+    ///  - "{sender}" either the sender name or the address
+    ///  - "{sender.name}" the sender name or "unknown"
+    ///  - "{sender.address}" the sender address
+    ///  - "{recipient}" either the recipient name or the address
+    ///  - "{recipient.name}" the recipient name or "unknown"
+    ///  - "{recipient.address}" the recipient address
     pub cmd: Option<Command>,
 
     /// Send a system notification using the given
@@ -0,0 +1,227 @@
+//! # Keyring backend
+//!
+//! Module dedicated to global keyring backend management. By default,
+//! entries are stored in the native keyring of the OS. Alternative
+//! backends store entries in memory or in a JSON file, which is
+//! useful for testing or for environments without keyring (like CI
+//! or containers).
+//!
+//! Like the service name, the backend should be defined at the
+//! beginning of the program, before creating any entry.
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+use std::{
+    any::Any,
+    collections::HashMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    sync::{Mutex, PoisonError},
+};
+
+use keyring_native::{
+    credential::{Credential, CredentialApi},
+    Error as NativeError, Result as NativeResult,
+};
+use log::debug;
+use once_cell::sync::{Lazy, OnceCell};
+
+/// Entries indexed by service name, then by key.
+type Entries = HashMap<String, HashMap<String, String>>;
+
+/// The global backend, wrapped in a once cell.
+static BACKEND: OnceCell<KeyringBackend> = OnceCell::new();
+
+/// The default global backend.
+static DEFAULT_BACKEND: KeyringBackend = KeyringBackend::Native;
+
+/// Entries of the memory backend.
+static MEMORY: Lazy<Mutex<Entries>> = Lazy::new(Default::default);
+
+/// Lock serializing reads and writes of file backends.
+static FILE_LOCK: Mutex<()> = Mutex::new(());
+
+/// The keyring backend.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub enum KeyringBackend {
+    /// The native keyring of the OS, with a keyutils cache on Linux.
+    #[default]
+    Native,
+
+    /// An in-process map, lost when the process exits.
+    Memory,
+
+    /// A JSON file at the given path, in plain text. Not meant for
+    /// production use.
+    File(PathBuf),
+}
+
+impl KeyringBackend {
+    /// Return `true` if the backend is the native one.
+    pub fn is_native(&self) -> bool {
+        matches!(self, Self::Native)
+    }
+
+    /// Build the credential of the given service and key, for
+    /// non-native backends.
+    pub(crate) fn credential(&self, service: &str, key: &str) -> Option<Box<Credential>> {
+        let store = match self {
+            Self::Native => return None,
+            Self::Memory => Store::Memory,
+            Self::File(path) => Store::File(path.clone()),
+        };
+
+        Some(Box::new(MockCredential {
+            service: service.to_owned(),
+            key: key.to_owned(),
+            store,
+        }))
+    }
+}
+
+/// Get the global keyring backend.
+///
+/// If the backend is not defined, returns the native backend.
+pub fn get_global_backend() -> &'static KeyringBackend {
+    BACKEND.get().unwrap_or(&DEFAULT_BACKEND)
+}
+
+/// Replace the global keyring backend.
+///
+/// This action has no effect if a global backend has already been
+/// defined.
+pub fn set_global_backend(backend: KeyringBackend) {
+    debug!("setting global keyring backend {backend:?}");
+
+    if let Err((prev, _)) = BACKEND.try_insert(backend) {
+        debug!("cannot set global keyring backend: already set to {prev:?}");
+    }
+}
+
+#[derive(Debug)]
+enum Store {
+    Memory,
+    File(PathBuf),
+}
+
+/// Credential of the memory and file backends.
+#[derive(Debug)]
+struct MockCredential {
+    service: String,
+    key: String,
+    store: Store,
+}
+
+impl MockCredential {
+    /// Apply the given function to the entries of the store, then
+    /// save them back when the function returns `true`.
+    fn with_entries<T>(&self, f: impl FnOnce(&mut Entries) -> (T, bool)) -> NativeResult<T> {
+        match &self.store {
+            Store::Memory => {
+                let mut entries = MEMORY.lock().unwrap_or_else(PoisonError::into_inner);
+                Ok(f(&mut entries).0)
+            }
+            Store::File(path) => {
+                let _lock = FILE_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+                let mut entries = read_entries(path)?;
+                let (res, changed) = f(&mut entries);
+                if changed {
+                    write_entries(path, &entries)?;
+                }
+                Ok(res)
+            }
+        }
+    }
+}
+
+impl CredentialApi for MockCredential {
+    fn set_password(&self, password: &str) -> NativeResult<()> {
+        self.with_entries(|entries| {
+            entries
+                .entry(self.service.clone())
+                .or_default()
+                .insert(self.key.clone(), password.to_owned());
+            ((), true)
+        })
+    }
+
+    fn get_password(&self) -> NativeResult<String> {
+        self.with_entries(|entries| {
+            let secret = entries
+                .get(&self.service)
+                .and_then(|entries| entries.get(&self.key))
+                .cloned();
+            (secret, false)
+        })?
+        .ok_or(NativeError::NoEntry)
+    }
+
+    fn delete_password(&self) -> NativeResult<()> {
+        let deleted = self.with_entries(|entries| {
+            let deleted = entries
+                .get_mut(&self.service)
+                .and_then(|entries| entries.remove(&self.key))
+                .is_some();
+            (deleted, deleted)
+        })?;
+
+        if deleted {
+            Ok(())
+        } else {
+            Err(NativeError::NoEntry)
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+fn read_entries(path: &Path) -> NativeResult<Entries> {
+    match fs::read(path) {
+        Ok(contents) if contents.is_empty() => Ok(Entries::new()),
+        Ok(contents) => serde_json::from_slice(&contents)
+            .map_err(|err| NativeError::PlatformFailure(Box::new(err))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Entries::new()),
+        Err(err) => Err(NativeError::NoStorageAccess(Box::new(err))),
+    }
+}
+
+/// Write the given entries to the given path.
+///
+/// Entries are first written to a temporary file only readable by
+/// the current user, which then replaces the given path. This way,
+/// the file is never left half-written.
+fn write_entries(path: &Path, entries: &Entries) -> NativeResult<()> {
+    let no_storage_access = |err| NativeError::NoStorageAccess(Box::new(err));
+
+    let contents = serde_json::to_vec_pretty(entries)
+        .map_err(|err| NativeError::PlatformFailure(Box::new(err)))?;
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(no_storage_access)?;
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+    let tmp_path = PathBuf::from(tmp_path);
+
+    // a leftover temporary file may have looser permissions
+    match fs::remove_file(&tmp_path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(no_storage_access(err)),
+        _ => (),
+    }
+
+    let mut opts = OpenOptions::new();
+    opts.write(true).create_new(true);
+    #[cfg(unix)]
+    opts.mode(0o600);
+
+    let mut file = opts.open(&tmp_path).map_err(no_storage_access)?;
+    file.write_all(&contents)
+        .and_then(|()| file.sync_all())
+        .map_err(no_storage_access)?;
+
+    fs::rename(&tmp_path, path).map_err(no_storage_access)
+}
@@ -9,7 +9,11 @@
 //! library to manage credentials. The main structure is
 //! [`KeyringEntry`]. Cache is enabled on Linux only, using the kernel
 //! [`keyutils`] keyring.
+//!
+//! Entries can also be stored in memory or in a JSON file instead of
+//! the native keyring, see [`set_global_backend`].
 
+mod backend;
 mod error;
 #[cfg(target_os = "linux")]
 mod keyutils;
@@ -25,6 +29,7 @@
 pub use crate::keyutils::KeyutilsEntry;
 #[doc(inline)]
 pub use crate::{
+    backend::{get_global_backend, set_global_backend, KeyringBackend},
     error::{Error, Result},
     service::{get_global_service_name, set_global_service_name},
 };
@@ -33,7 +38,7 @@
 ///
 /// This struct is a simple wrapper around [`keyring_native::Entry`]
 /// that holds a keyring entry key, as well as a keyutils entry on
-/// Linux for cache.
+/// Linux for cache. The cache is only used by the native backend.
 #[derive(Clone, Debug)]
 #[cfg_attr(
     feature = "derive",
@@ -49,7 +54,7 @@ pub struct KeyringEntry {
 
     /// The cache keyutils entry.
     #[cfg(target_os = "linux")]
-    cache_entry: KeyutilsEntry,
+    cache_entry: Option<KeyutilsEntry>,
 }
 
 impl Eq for KeyringEntry {}
@@ -71,17 +76,19 @@ pub async fn get_secret(&self) -> Result<String> {
         let key = &self.key;
 
         #[cfg(target_os = "linux")]
-        match self.cache_entry.find_secret().await {
-            Ok(Some(secret)) => {
-                debug!("found secret from cache matching `{key}`");
-                return Ok(secret);
-            }
-            Ok(None) => {
-                debug!("no secret found from cache matching `{key}`");
-            }
-            Err(err) => {
-                debug!("cannot find secret from cache matching `{key}`");
-                trace!("{err:?}");
+        if let Some(cache_entry) = &self.cache_entry {
+            match cache_entry.find_secret().await {
+                Ok(Some(secret)) => {
+                    debug!("found secret from cache matching `{key}`");
+                    return Ok(secret);
+                }
+                Ok(None) => {
+                    debug!("no secret found from cache matching `{key}`");
+                }
+                Err(err) => {
+                    debug!("cannot find secret from cache matching `{key}`");
+                    trace!("{err:?}");
+                }
             }
         }
 
@@ -102,17 +109,19 @@ pub async fn find_secret(&self) -> Result<Option<String>> {
         let key = &self.key;
 
         #[cfg(target_os = "linux")]
-        match self.cache_entry.find_secret().await {
-            Ok(Some(secret)) => {
-                debug!("found secret from cache matching `{key}`");
-                return Ok(Some(secret));
-            }
-            Ok(None) => {
-                debug!("no secret found from cache matching `{key}`");
-            }
-            Err(err) => {
-                debug!("cannot find secret from cache matching `{key}`");
-                trace!("{err:?}");
+        if let Some(cache_entry) = &self.cache_entry {
+            match cache_entry.find_secret().await {
+                Ok(Some(secret)) => {
+                    debug!("found secret from cache matching `{key}`");
+                    return Ok(Some(secret));
+                }
+                Ok(None) => {
+                    debug!("no secret found from cache matching `{key}`");
+                }
+                Err(err) => {
+                    debug!("cannot find secret from cache matching `{key}`");
+                    trace!("{err:?}");
+                }
             }
         }
 
@@ -136,7 +145,9 @@ pub async fn set_secret(&self, secret: impl ToString) -> Result<()> {
         debug!("setting keyring secret for key `{key}`");
 
         #[cfg(target_os = "linux")]
-        self.cache_entry.set_secret(&secret).await?;
+        if let Some(cache_entry) = &self.cache_entry {
+            cache_entry.set_secret(&secret).await?;
+        }
 
         let entry = self.entry.clone();
         task::spawn_blocking(move || entry.set_password(&secret))
@@ -160,7 +171,9 @@ pub async fn delete_secret(&self) -> Result<()> {
         debug!("deleting keyring secret for key `{key}`");
 
         #[cfg(target_os = "linux")]
-        self.cache_entry.delete_secret().await?;
+        if let Some(cache_entry) = &self.cache_entry {
+            cache_entry.delete_secret().await?;
+        }
 
         let entry = self.entry.clone();
         task::spawn_blocking(move || entry.delete_password())
@@ -176,14 +189,22 @@ impl TryFrom<String> for KeyringEntry {
 
     fn try_from(key: String) -> Result<Self> {
         let service = get_global_service_name();
+        let backend = get_global_backend();
 
-        let entry = match keyring_native::Entry::new(service, &key) {
-            Ok(entry) => Ok(Arc::new(entry)),
-            Err(err) => Err(Error::BuildEntryError(err, key.clone())),
-        }?;
+        let entry = match backend.credential(service, &key) {
+            Some(credential) => Arc::new(keyring_native::Entry::new_with_credential(credential)),
+            None => match keyring_native::Entry::new(service, &key) {
+                Ok(entry) => Ok(Arc::new(entry)),
+                Err(err) => Err(Error::BuildEntryError(err, key.clone())),
+            }?,
+        };
 
         #[cfg(target_os = "linux")]
-        let cache_entry = KeyutilsEntry::try_new(&key)?;
+        let cache_entry = if backend.is_native() {
+            Some(KeyutilsEntry::try_new(&key)?)
+        } else {
+            None
+        };
 
         Ok(Self {
             key,
@@ -0,0 +1,49 @@
+use std::fs;
+
+use keyring::{set_global_backend, set_global_service_name, KeyringBackend, KeyringEntry};
+use tempfile::tempdir;
+
+#[tokio::test]
+async fn test_file_backend() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("keyring/keyring.json");
+
+    set_global_service_name("example");
+    set_global_backend(KeyringBackend::File(path.clone()));
+
+    let entry = KeyringEntry::try_new("key")
+        .unwrap()
+        .try_with_secret("secret")
+        .await
+        .unwrap();
+    assert_eq!(entry.get_secret().await.unwrap(), "secret");
+
+    // entries are stored by service name, then by key
+    let json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(json["example"]["key"], "secret");
+
+    // the file is only readable by the current user
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    KeyringEntry::try_new("key2")
+        .unwrap()
+        .set_secret("secret2")
+        .await
+        .unwrap();
+
+    entry.delete_secret().await.unwrap();
+    assert_eq!(entry.find_secret().await.unwrap(), None);
+
+    let json: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+    assert_eq!(json["example"]["key"], serde_json::Value::Null);
+    assert_eq!(json["example"]["key2"], "secret2");
+
+    // the temporary file has been moved to the final path
+    let entries = fs::read_dir(path.parent().unwrap()).unwrap().count();
+    assert_eq!(entries, 1);
+}
@@ -0,0 +1,24 @@
+use keyring::{get_global_backend, set_global_backend, KeyringBackend, KeyringEntry};
+
+#[tokio::test]
+async fn test_memory_backend() {
+    set_global_backend(KeyringBackend::Memory);
+    assert_eq!(get_global_backend(), &KeyringBackend::Memory);
+    set_global_backend(KeyringBackend::Native);
+    assert_eq!(get_global_backend(), &KeyringBackend::Memory);
+
+    let entry = KeyringEntry::try_new("key").unwrap();
+    assert_eq!(entry.find_secret().await.unwrap(), None);
+    assert!(entry.get_secret().await.is_err());
+
+    entry.set_secret("secret").await.unwrap();
+    assert_eq!(entry.get_secret().await.unwrap(), "secret");
+
+    // entries sharing the same key share the same secret
+    let other_entry = KeyringEntry::try_new("key").unwrap();
+    assert_eq!(other_entry.get_secret().await.unwrap(), "secret");
+
+    other_entry.delete_secret().await.unwrap();
+    assert_eq!(entry.find_secret().await.unwrap(), None);
+    assert!(entry.delete_secret().await.is_err());
+}
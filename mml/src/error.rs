@@ -3,18 +3,24 @@
 use std::{io, path::PathBuf, result};
 use thiserror::Error;
 
+#[cfg(feature = "compiler")]
+use crate::message::body::compiler::Diagnostic;
+
 /// The global `Result` alias of the library.
 pub type Result<T> = result::Result<T, Error>;
 
 /// The global `Error` enum of the library.
 #[derive(Debug, Error)]
 pub enum Error {
-    #[error("cannot parse MML body")]
-    ParseMmlError(Vec<chumsky::error::Rich<'static, char>>, String),
+    #[cfg(feature = "compiler")]
+    #[error("cannot parse MML body:\n{}", .0.iter().map(ToString::to_string).collect::<Vec<_>>().join("\n"))]
+    ParseMmlError(Vec<Diagnostic>, String),
     #[error("cannot compile template")]
     WriteCompiledPartToVecError(#[source] io::Error),
     #[error("cannot read attachment at {1:?}")]
     ReadAttachmentError(#[source] io::Error, PathBuf),
+    #[error("cannot read inline image at {1:?}")]
+    ReadInlineImageError(#[source] io::Error, PathBuf),
     #[cfg(feature = "pgp")]
     #[error("cannot sign part using pgp: missing sender")]
     PgpSignMissingSenderError,
@@ -0,0 +1,397 @@
+//! # Attachment policy
+//!
+//! Module dedicated to attachments saving. Attachments come from
+//! untrusted messages: their declared filename may try to escape the
+//! downloads directory, their size may fill up the disk and their
+//! declared MIME type may lie. The [`AttachmentPolicy`] decides which
+//! attachments can be saved, and where.
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+/// The filename used when the declared one is missing or empty once
+/// sanitized.
+const DEFAULT_FILENAME: &str = "noname";
+
+/// The maximum length of a sanitized filename, in bytes.
+const MAX_FILENAME_LEN: usize = 255;
+
+/// Filenames reserved by Windows, whatever the extension.
+const RESERVED_FILENAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// The attachment policy.
+///
+/// Filenames are always sanitized and never overwrite existing
+/// files. Other restrictions are opt-in.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case", deny_unknown_fields)
+)]
+pub struct AttachmentPolicy {
+    /// The maximum size of a single attachment, in bytes.
+    pub max_file_size: Option<u64>,
+
+    /// The maximum size of all the attachments of a message, in
+    /// bytes.
+    pub max_total_size: Option<u64>,
+
+    /// Filter attachments by MIME type.
+    ///
+    /// The MIME type is detected from the attachment content, the
+    /// declared one cannot be trusted.
+    pub mime_types: Option<MimeTypesFilter>,
+}
+
+impl AttachmentPolicy {
+    /// Sanitize the given attachment filename.
+    ///
+    /// Only the last component of the filename is kept, so that it
+    /// cannot escape the downloads directory. Control characters and
+    /// characters reserved by Windows are removed, as well as leading
+    /// and trailing dots and spaces. Names reserved by Windows are
+    /// prefixed by an underscore. The result is never empty.
+    pub fn sanitize_filename(filename: &str) -> String {
+        let filename = filename
+            .rsplit(['/', '\\'])
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .filter(|c| !c.is_control() && !matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+            .collect::<String>();
+        let filename = filename.trim_matches(|c: char| c == '.' || c.is_whitespace());
+
+        if filename.is_empty() {
+            return String::from(DEFAULT_FILENAME);
+        }
+
+        let (stem, ext) = match filename.rsplit_once('.') {
+            Some((stem, ext)) => (stem, format!(".{ext}")),
+            None => (filename, String::new()),
+        };
+
+        let is_reserved = RESERVED_FILENAMES
+            .iter()
+            .any(|reserved| reserved.eq_ignore_ascii_case(stem.split('.').next().unwrap_or(stem)));
+
+        let mut stem = if is_reserved {
+            format!("_{stem}")
+        } else {
+            stem.to_owned()
+        };
+
+        // extensions are kept, unless they are too long themselves
+        let ext = if ext.len() < MAX_FILENAME_LEN / 2 {
+            ext
+        } else {
+            String::new()
+        };
+
+        while stem.len() + ext.len() > MAX_FILENAME_LEN {
+            stem.pop();
+        }
+
+        stem + &ext
+    }
+
+    /// Build a path for the given sanitized filename inside the given
+    /// directory, that is not taken yet.
+    ///
+    /// A numbered suffix is added to the file stem until the path is
+    /// not taken anymore: `file.ext`, `file_1.ext`, `file_2.ext` …
+    pub fn resolve_path(dir: &Path, filename: &str, is_taken: impl Fn(&Path) -> bool) -> PathBuf {
+        let (stem, ext) = match filename.rsplit_once('.') {
+            Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{ext}")),
+            _ => (filename, String::new()),
+        };
+
+        let mut path = dir.join(filename);
+        let mut count = 0;
+
+        while is_taken(&path) {
+            count += 1;
+            path = dir.join(format!("{stem}_{count}{ext}"));
+        }
+
+        path
+    }
+
+    /// Check the given attachment content against the size and the
+    /// MIME type restrictions of the policy.
+    pub fn check(&self, data: &[u8]) -> Result<(), SkipReason> {
+        let size = data.len() as u64;
+
+        if let Some(max) = self.max_file_size {
+            if size > max {
+                return Err(SkipReason::FileTooLarge { size, max });
+            }
+        }
+
+        if let Some(filter) = &self.mime_types {
+            let mime_type = tree_magic_mini::from_u8(data);
+
+            match filter {
+                MimeTypesFilter::Allow(patterns) if !matches_mime_type(patterns, mime_type) => {
+                    return Err(SkipReason::MimeTypeNotAllowed(mime_type.to_owned()));
+                }
+                MimeTypesFilter::Block(patterns) if matches_mime_type(patterns, mime_type) => {
+                    return Err(SkipReason::MimeTypeBlocked(mime_type.to_owned()));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Apply the policy to the given attachment.
+    ///
+    /// Return the path the attachment should be saved at, or [`None`]
+    /// if the attachment is skipped. The decision is recorded in the
+    /// given report. When `check_fs` is true, paths of existing files
+    /// are considered as taken.
+    pub(crate) fn apply(
+        &self,
+        report: &mut AttachmentsReport,
+        dir: &Path,
+        filename: Option<&str>,
+        mime_type: &str,
+        data: &[u8],
+        check_fs: bool,
+    ) -> Option<PathBuf> {
+        let size = data.len() as u64;
+
+        let res = self.check(data).and_then(|()| match self.max_total_size {
+            Some(max) if report.total_size + size > max => {
+                let size = report.total_size + size;
+                Err(SkipReason::TotalSizeExceeded { size, max })
+            }
+            _ => Ok(()),
+        });
+
+        if let Err(reason) = res {
+            report.skipped.push(SkippedAttachment {
+                filename: filename.map(ToOwned::to_owned),
+                mime_type: mime_type.to_owned(),
+                reason,
+            });
+            return None;
+        }
+
+        let filename = Self::sanitize_filename(filename.unwrap_or_default());
+        let path = Self::resolve_path(dir, &filename, |path| {
+            report.accepted.iter().any(|p| p == path) || (check_fs && path.exists())
+        });
+
+        report.total_size += size;
+        report.accepted.push(path.clone());
+
+        Some(path)
+    }
+}
+
+/// Filter attachments by MIME type.
+///
+/// Patterns are either full MIME types like `image/png` or wildcards
+/// like `image/*`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum MimeTypesFilter {
+    /// Only allow attachments matching one of the given patterns.
+    Allow(Vec<String>),
+
+    /// Block attachments matching one of the given patterns.
+    Block(Vec<String>),
+}
+
+/// Return `true` if the given MIME type matches one of the given
+/// patterns.
+fn matches_mime_type(patterns: &[String], mime_type: &str) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix("/*") {
+            Some(ctype) => mime_type
+                .split_once('/')
+                .map(|(this_ctype, _)| this_ctype.eq_ignore_ascii_case(ctype))
+                .unwrap_or_default(),
+            None => pattern.eq_ignore_ascii_case(mime_type),
+        })
+}
+
+/// The reason why an attachment has been skipped.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum SkipReason {
+    /// The attachment exceeds the maximum file size.
+    FileTooLarge { size: u64, max: u64 },
+
+    /// The attachment makes the message attachments exceed the
+    /// maximum total size.
+    TotalSizeExceeded { size: u64, max: u64 },
+
+    /// The detected MIME type is not part of the allowed ones.
+    MimeTypeNotAllowed(String),
+
+    /// The detected MIME type is part of the blocked ones.
+    MimeTypeBlocked(String),
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::FileTooLarge { size, max } => {
+                write!(f, "file too large ({size} bytes, max {max} bytes)")
+            }
+            Self::TotalSizeExceeded { size, max } => {
+                write!(f, "total size exceeded ({size} bytes, max {max} bytes)")
+            }
+            Self::MimeTypeNotAllowed(mime_type) => write!(f, "MIME type {mime_type} not allowed"),
+            Self::MimeTypeBlocked(mime_type) => write!(f, "MIME type {mime_type} blocked"),
+        }
+    }
+}
+
+/// An attachment skipped by the [`AttachmentPolicy`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SkippedAttachment {
+    /// The filename declared by the message, before sanitization.
+    pub filename: Option<String>,
+
+    /// The MIME type declared by the message.
+    pub mime_type: String,
+
+    /// Why the attachment has been skipped.
+    pub reason: SkipReason,
+}
+
+/// The attachments report of an interpreted message.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AttachmentsReport {
+    /// Paths of attachments accepted by the policy.
+    ///
+    /// Attachments are only written at these paths when saving
+    /// attachments is enabled.
+    pub accepted: Vec<PathBuf>,
+
+    /// Attachments skipped by the policy.
+    pub skipped: Vec<SkippedAttachment>,
+
+    /// The total size of accepted attachments, in bytes.
+    total_size: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use super::{AttachmentPolicy, AttachmentsReport, MimeTypesFilter, SkipReason};
+
+    #[test]
+    fn sanitize_filename() {
+        let sanitize = AttachmentPolicy::sanitize_filename;
+
+        assert_eq!(sanitize("file.txt"), "file.txt");
+        assert_eq!(sanitize("../../.ssh/authorized_keys"), "authorized_keys");
+        assert_eq!(sanitize("..\\..\\Windows\\win.ini"), "win.ini");
+        assert_eq!(sanitize("/etc/passwd"), "passwd");
+        assert_eq!(sanitize(".bashrc"), "bashrc");
+        assert_eq!(sanitize(".."), "noname");
+        assert_eq!(sanitize(""), "noname");
+        assert_eq!(sanitize("a\0b\nc?*.txt"), "abc.txt");
+        assert_eq!(sanitize("con.txt"), "_con.txt");
+        assert_eq!(sanitize("LPT1"), "_LPT1");
+        assert_eq!(sanitize("console.txt"), "console.txt");
+        assert_eq!(sanitize(&"a".repeat(300)).len(), 255);
+        assert_eq!(
+            sanitize(&format!("{}.pdf", "a".repeat(300))),
+            format!("{}.pdf", "a".repeat(251))
+        );
+    }
+
+    #[test]
+    fn resolve_path() {
+        let dir = Path::new("downloads");
+        let taken = [
+            PathBuf::from("downloads/file.txt"),
+            PathBuf::from("downloads/file_1.txt"),
+        ];
+
+        let path = AttachmentPolicy::resolve_path(dir, "file.txt", |path| {
+            taken.contains(&path.to_owned())
+        });
+        assert_eq!(path, PathBuf::from("downloads/file_2.txt"));
+
+        let path =
+            AttachmentPolicy::resolve_path(dir, "other", |path| taken.contains(&path.to_owned()));
+        assert_eq!(path, PathBuf::from("downloads/other"));
+    }
+
+    #[test]
+    fn apply() {
+        let policy = AttachmentPolicy {
+            max_file_size: Some(10),
+            max_total_size: Some(15),
+            mime_types: Some(MimeTypesFilter::Block(vec![
+                "application/x-executable".into()
+            ])),
+        };
+
+        let dir = Path::new("downloads");
+        let mut report = AttachmentsReport::default();
+
+        let path = policy.apply(
+            &mut report,
+            dir,
+            Some("../a.txt"),
+            "text/plain",
+            b"12345678",
+            false,
+        );
+        assert_eq!(path, Some(PathBuf::from("downloads/a.txt")));
+
+        let path = policy.apply(
+            &mut report,
+            dir,
+            Some("a.txt"),
+            "text/plain",
+            b"12345678",
+            false,
+        );
+        assert_eq!(path, None);
+
+        let path = policy.apply(
+            &mut report,
+            dir,
+            Some("a.txt"),
+            "text/plain",
+            b"1234",
+            false,
+        );
+        assert_eq!(path, Some(PathBuf::from("downloads/a_1.txt")));
+
+        let path = policy.apply(&mut report, dir, None, "text/plain", b"12345678901", false);
+        assert_eq!(path, None);
+
+        assert_eq!(report.accepted.len(), 2);
+        assert_eq!(
+            report
+                .skipped
+                .iter()
+                .map(|skipped| skipped.reason.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                SkipReason::TotalSizeExceeded { size: 16, max: 15 },
+                SkipReason::FileTooLarge { size: 11, max: 10 },
+            ]
+        );
+    }
+}
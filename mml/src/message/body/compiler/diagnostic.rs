@@ -0,0 +1,228 @@
+//! # MML diagnostics
+//!
+//! Module dedicated to MML parsing errors reporting. Errors emitted by
+//! the [parsers](super::parsers) are turned into [`Diagnostic`]s,
+//! which locate the error in the MML body and, when possible, suggest
+//! a way to fix it.
+
+use chumsky::error::{Rich, RichPattern};
+use std::{fmt, ops::Range};
+
+use crate::message::body::{GREATER_THAN, MULTIPART_BEGIN, MULTIPART_END, PART_BEGIN, PART_END};
+
+/// A MML parsing error, located in the MML body.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    /// The byte range of the error in the MML body.
+    pub span: Range<usize>,
+
+    /// The line of the error, starting from 1.
+    pub line: usize,
+
+    /// The column of the error in chars, starting from 1.
+    pub column: usize,
+
+    /// The line of the MML body containing the error.
+    pub source_line: String,
+
+    /// What went wrong.
+    pub message: String,
+
+    /// How to fix it, if it can be guessed.
+    pub hint: Option<String>,
+}
+
+impl Diagnostic {
+    /// Build a diagnostic from the given parser error and the MML
+    /// body it comes from.
+    pub(crate) fn new(err: &Rich<'_, char>, mml: &str) -> Self {
+        let start = err.span().start.min(mml.len());
+        let end = err.span().end.clamp(start, mml.len());
+
+        let line_start = mml[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = mml[start..]
+            .find('\n')
+            .map(|i| start + i)
+            .unwrap_or(mml.len());
+
+        Self {
+            span: start..end,
+            line: mml[..start].matches('\n').count() + 1,
+            column: mml[line_start..start].chars().count() + 1,
+            source_line: mml[line_start..line_end].to_owned(),
+            message: build_message(err),
+            hint: guess_hint(err, mml, start),
+        }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self.line.to_string();
+        let gutter = " ".repeat(line.len());
+        let carets = self.source_line[self.column_offset()..]
+            .chars()
+            .take(self.span.len())
+            .count()
+            .max(1);
+
+        writeln!(f, "{line}:{}: {}", self.column, self.message)?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{line} | {}", self.source_line)?;
+        write!(
+            f,
+            "{gutter} | {}{}",
+            " ".repeat(self.column - 1),
+            "^".repeat(carets)
+        )?;
+
+        if let Some(hint) = &self.hint {
+            write!(f, "\n{gutter} = hint: {hint}")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Diagnostic {
+    /// Byte offset of the error column inside the source line.
+    fn column_offset(&self) -> usize {
+        self.source_line
+            .char_indices()
+            .nth(self.column - 1)
+            .map(|(i, _)| i)
+            .unwrap_or(self.source_line.len())
+    }
+}
+
+/// Build a human-readable message from the given parser error.
+fn build_message(err: &Rich<'_, char>) -> String {
+    let found = match err.found() {
+        Some(c) => format!("unexpected {c:?}"),
+        None => String::from("unexpected end of input"),
+    };
+
+    let mut expected: Vec<String> = err
+        .expected()
+        .map(|pattern| match pattern {
+            RichPattern::Token(token) => format!("{:?}", &**token),
+            RichPattern::Label(label) => label.to_string(),
+            RichPattern::EndOfInput => String::from("end of input"),
+            #[allow(unreachable_patterns)]
+            pattern => pattern.to_string(),
+        })
+        .collect();
+    expected.sort();
+    expected.dedup();
+
+    match expected.as_slice() {
+        [] => found,
+        [expected] => format!("{found}, expected {expected}"),
+        expected => format!("{found}, expected one of {}", expected.join(", ")),
+    }
+}
+
+/// Guess how to fix the given parser error, based on where it
+/// occurred in the MML body.
+fn guess_hint(err: &Rich<'_, char>, mml: &str, offset: usize) -> Option<String> {
+    let before = &mml[..offset];
+
+    let opening_tag = [PART_BEGIN, MULTIPART_BEGIN]
+        .into_iter()
+        .filter_map(|tag| before.rfind(tag))
+        .max();
+
+    if let Some(tag_start) = opening_tag {
+        if !before[tag_start..].contains(GREATER_THAN) {
+            return Some(String::from(
+                "properties of opening tags follow the form key=value: check the property name and value, and quote values containing spaces",
+            ));
+        }
+    }
+
+    if err.found().is_none()
+        && mml.matches(MULTIPART_BEGIN).count() > mml.matches(MULTIPART_END).count()
+    {
+        return Some(format!(
+            "a multipart is never closed, add the missing closing tag {MULTIPART_END}"
+        ));
+    }
+
+    // the error may be reported in the middle of the tag, after the
+    // common prefix `<#` shared by all tags
+    let tag_start = before
+        .rfind('<')
+        .filter(|i| offset - i <= 2)
+        .unwrap_or(offset);
+
+    for (closing_tag, opening_tag) in [(MULTIPART_END, MULTIPART_BEGIN), (PART_END, PART_BEGIN)] {
+        if mml[tag_start..].starts_with(closing_tag) {
+            return Some(format!(
+                "the closing tag {closing_tag} does not match any opening tag {opening_tag}>"
+            ));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use chumsky::Parser;
+    use concat_with::concat_line;
+
+    use crate::message::body::compiler::parsers;
+
+    use super::Diagnostic;
+
+    fn diagnose(mml: &str) -> Diagnostic {
+        let res = parsers::parts().parse(mml);
+        let err = res.errors().next().expect("MML body should be invalid");
+        Diagnostic::new(err, mml)
+    }
+
+    #[test]
+    fn invalid_property() {
+        let mml = concat_line!("Hello,", "<#part type=text/plain unknown=prop>", "world!");
+        let diag = diagnose(mml);
+
+        assert_eq!(diag.line, 2);
+        assert_eq!(diag.column, 24);
+        assert_eq!(diag.source_line, "<#part type=text/plain unknown=prop>");
+        assert!(diag.hint.unwrap().contains("key=value"));
+    }
+
+    #[test]
+    fn unclosed_multipart() {
+        let mml = concat_line!("<#multipart type=mixed>", "Hello, world!", "");
+        let diag = diagnose(mml);
+
+        assert_eq!(diag.line, 3);
+        assert_eq!(diag.column, 1);
+        assert!(diag.message.starts_with("unexpected end of input"));
+        assert!(diag.hint.unwrap().contains("<#/multipart>"));
+    }
+
+    #[test]
+    fn unmatched_closing_tag() {
+        let diag = diagnose("Hello, world!<#/multipart>");
+
+        let hint = diag.hint.unwrap();
+
+        assert_eq!(diag.line, 1);
+        assert!(hint.starts_with("the closing tag <#/multipart>"));
+    }
+
+    #[test]
+    fn display() {
+        let mml = "<#multipart type=unknown>Hello<#/multipart>";
+        let diag = diagnose(mml).to_string();
+        let mut lines = diag.lines();
+
+        assert!(lines.next().unwrap().starts_with("1:18: unexpected 'u'"));
+        assert_eq!(lines.next(), Some("  |"));
+        assert_eq!(lines.next(), Some(&*format!("1 | {mml}")));
+        assert_eq!(lines.next(), Some(&*format!("  | {}^", " ".repeat(17))));
+        assert!(lines.next().unwrap().starts_with("  = hint: properties"));
+    }
+}
@@ -0,0 +1,236 @@
+//! # HTML composition
+//!
+//! Module dedicated to HTML parts composition: local images
+//! referenced by HTML parts are replaced by `cid:` URLs so they can
+//! be embedded inline next to the HTML part, inside a
+//! `multipart/related` part.
+
+use std::{
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A local image referenced by a HTML part.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(crate) struct InlineImage {
+    /// The canonical path of the image.
+    pub path: PathBuf,
+
+    /// The generated Content-ID of the image, without angle
+    /// brackets.
+    pub cid: String,
+}
+
+/// Replaces sources of `<img>` tags pointing to local files inside
+/// the given base directory by `cid:` URLs.
+///
+/// Only relative paths are resolved, against the base directory.
+/// Sources starting by a URL scheme (`cid:`, `http:`, `file:` …),
+/// absolute paths, paths starting by `~` and paths that do not point
+/// to an existing file inside the base directory are left
+/// untouched. Images referenced multiple times share the same
+/// Content-ID.
+pub(crate) fn replace_local_img_srcs(html: &str, base_dir: &Path) -> (String, Vec<InlineImage>) {
+    let lowercase_html = html.to_ascii_lowercase();
+    let unique_id = gen_unique_id();
+
+    let mut images: Vec<InlineImage> = Vec::new();
+    let mut output = String::with_capacity(html.len());
+    let mut cursor = 0;
+
+    while let Some((start, end)) = find_next_img_src(&lowercase_html, cursor) {
+        let src = &html[start..end];
+
+        let path = match parse_local_path(src, base_dir) {
+            Some(path) if path.is_file() => path,
+            _ => {
+                output.push_str(&html[cursor..end]);
+                cursor = end;
+                continue;
+            }
+        };
+
+        let cid = match images.iter().find(|image| image.path == path) {
+            Some(image) => image.cid.clone(),
+            None => {
+                let cid = format!("part{}.{unique_id}@localhost", images.len() + 1);
+                images.push(InlineImage {
+                    path,
+                    cid: cid.clone(),
+                });
+                cid
+            }
+        };
+
+        output.push_str(&html[cursor..start]);
+        output.push_str("cid:");
+        output.push_str(&cid);
+        cursor = end;
+    }
+
+    output.push_str(&html[cursor..]);
+
+    (output, images)
+}
+
+/// Finds the byte range of the next `<img>` source value, starting
+/// from the given position.
+///
+/// The given HTML needs to be lowercased, so that byte positions
+/// match the original HTML.
+fn find_next_img_src(lowercase_html: &str, from: usize) -> Option<(usize, usize)> {
+    let mut from = from;
+
+    loop {
+        let tag_start = from + lowercase_html[from..].find("<img")?;
+        let tag_end = lowercase_html[tag_start..]
+            .find('>')
+            .map(|i| tag_start + i)
+            .unwrap_or(lowercase_html.len());
+        let tag = &lowercase_html[tag_start..tag_end];
+
+        let src = tag
+            .match_indices("src")
+            .filter(|(i, _)| {
+                tag[..*i]
+                    .chars()
+                    .next_back()
+                    .map(char::is_whitespace)
+                    .unwrap_or(false)
+            })
+            .find_map(|(i, _)| {
+                let rest = &tag[i + 3..];
+                let rest_trimmed = rest.trim_start();
+                let rest_trimmed = rest_trimmed.strip_prefix('=')?;
+                let value = rest_trimmed.trim_start();
+                let offset = tag.len() - value.len();
+
+                match value.chars().next()? {
+                    quote @ ('"' | '\'') => {
+                        let len = value[1..].find(quote)?;
+                        Some((offset + 1, offset + 1 + len))
+                    }
+                    _ => {
+                        let len = value.find(char::is_whitespace).unwrap_or(value.len());
+                        Some((offset, offset + len))
+                    }
+                }
+            });
+
+        match src {
+            Some((start, end)) => return Some((tag_start + start, tag_start + end)),
+            None => from = tag_end,
+        }
+    }
+}
+
+/// Parses the given image source as a path relative to the given
+/// base directory.
+///
+/// Returns [`None`] if the source starts by a URL scheme, if it is
+/// not a relative path or if it does not resolve to an existing path
+/// inside the base directory (after following `..` and symlinks).
+fn parse_local_path(src: &str, base_dir: &Path) -> Option<PathBuf> {
+    let src = src.trim();
+
+    if src.is_empty() || src.starts_with(['/', '\\', '~']) {
+        return None;
+    }
+
+    let has_scheme = src
+        .split_once(':')
+        .map(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+        })
+        .unwrap_or(false);
+
+    if has_scheme || Path::new(src).is_absolute() {
+        return None;
+    }
+
+    let base_dir = base_dir.canonicalize().ok()?;
+    let path = base_dir.join(src).canonicalize().ok()?;
+
+    if path.starts_with(&base_dir) {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+/// Generates an identifier unique enough to build Content-IDs.
+fn gen_unique_id() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("{nanos:x}.{:x}", process::id())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use tempfile::tempdir;
+
+    use super::replace_local_img_srcs;
+
+    #[test]
+    fn replace_local_srcs_only() {
+        let dir = tempdir().unwrap();
+        let base_dir = dir.path().join("base");
+        fs::create_dir_all(base_dir.join("imgs")).unwrap();
+        fs::write(base_dir.join("imgs/img.png"), "image").unwrap();
+        fs::write(dir.path().join("outside.png"), "image").unwrap();
+
+        let outside_path = dir.path().join("outside.png");
+        let outside_path = outside_path.to_string_lossy();
+
+        let html = format!(
+            "<p><img alt=\"a\" src=\"imgs/img.png\"><IMG SRC='./imgs/img.png' />\
+             <img src=\"https://localhost/img.png\"><img src=\"cid:id@localhost\">\
+             <img src=\"imgs/missing.png\"><img src=\"{outside_path}\">\
+             <img src=\"file://{outside_path}\"><img src=\"../outside.png\">\
+             <img src=\"~/outside.png\"></p>"
+        );
+
+        let (output, images) = replace_local_img_srcs(&html, &base_dir);
+
+        assert_eq!(images.len(), 1);
+        assert_eq!(
+            images[0].path,
+            base_dir.join("imgs/img.png").canonicalize().unwrap()
+        );
+
+        let cid = &images[0].cid;
+        let expected_html = format!(
+            "<p><img alt=\"a\" src=\"cid:{cid}\"><IMG SRC='cid:{cid}' />\
+             <img src=\"https://localhost/img.png\"><img src=\"cid:id@localhost\">\
+             <img src=\"imgs/missing.png\"><img src=\"{outside_path}\">\
+             <img src=\"file://{outside_path}\"><img src=\"../outside.png\">\
+             <img src=\"~/outside.png\"></p>"
+        );
+
+        assert_eq!(output, expected_html);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn ignore_symlinks_outside_base_dir() {
+        let dir = tempdir().unwrap();
+        let base_dir = dir.path().join("base");
+        fs::create_dir(&base_dir).unwrap();
+        fs::write(dir.path().join("outside.png"), "image").unwrap();
+        std::os::unix::fs::symlink(dir.path().join("outside.png"), base_dir.join("link.png"))
+            .unwrap();
+
+        let html = "<img src=\"link.png\">";
+        let (output, images) = replace_local_img_srcs(html, &base_dir);
+
+        assert!(images.is_empty());
+        assert_eq!(output, html);
+    }
+}
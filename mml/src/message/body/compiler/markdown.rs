@@ -0,0 +1,100 @@
+//! # Markdown composition
+//!
+//! Module dedicated to markdown parts composition. A markdown part
+//! is compiled into a `multipart/alternative` part, where the
+//! markdown source is kept as the `text/plain` part and the rendered
+//! markdown becomes the `text/html` part. Markdown parts already
+//! belonging to a `multipart/alternative` part only become the
+//! `text/html` part.
+
+use pulldown_cmark::{html, Event, Options, Parser};
+
+/// Renders the given markdown as HTML.
+///
+/// Tables, footnotes, strikethrough and task lists extensions are
+/// enabled. Fenced code blocks are rendered as `<pre><code>` blocks
+/// with a `language-*` class when the language is specified.
+///
+/// Raw HTML is passed through, unless `escape_html` is true: it is
+/// then escaped and rendered as text, so that the markdown cannot
+/// inject HTML.
+pub(crate) fn to_html(markdown: &str, escape_html: bool) -> String {
+    let opts = Options::ENABLE_TABLES
+        | Options::ENABLE_FOOTNOTES
+        | Options::ENABLE_STRIKETHROUGH
+        | Options::ENABLE_TASKLISTS;
+
+    let mut html = String::with_capacity(markdown.len() * 3 / 2);
+    let events = Parser::new_ext(markdown, opts).map(|event| match event {
+        Event::Html(raw) if escape_html => Event::Text(raw),
+        event => event,
+    });
+
+    html::push_html(&mut html, events);
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use concat_with::concat_line;
+
+    #[test]
+    fn to_html() {
+        let markdown = concat_line!(
+            "# Title",
+            "",
+            "Some *emphasized* and ~~striked~~ text.",
+            "",
+            "```rust",
+            "let x = 1;",
+            "```",
+        );
+
+        let expected_html = concat_line!(
+            "<h1>Title</h1>",
+            "<p>Some <em>emphasized</em> and <del>striked</del> text.</p>",
+            "<pre><code class=\"language-rust\">let x = 1;",
+            "</code></pre>",
+            "",
+        );
+
+        assert_eq!(super::to_html(markdown, false), expected_html);
+    }
+
+    #[test]
+    fn raw_html() {
+        let markdown = concat_line!(
+            "<div align=\"center\">",
+            "",
+            "Inline <b>HTML</b> and ![image](image.png).",
+            "",
+            "</div>",
+        );
+
+        let expected_html = concat_line!(
+            "<div align=\"center\">",
+            "<p>Inline <b>HTML</b> and <img src=\"image.png\" alt=\"image\" />.</p>",
+            "</div>",
+            "",
+        );
+
+        assert_eq!(super::to_html(markdown, false), expected_html);
+    }
+
+    #[test]
+    fn escape_raw_html() {
+        let markdown = concat_line!(
+            "<img src=\"/etc/passwd\">",
+            "",
+            "Inline <b>HTML</b> and ![image](image.png).",
+        );
+
+        let expected_html = concat_line!(
+            "&lt;img src=&quot;/etc/passwd&quot;&gt;",
+            "<p>Inline &lt;b&gt;HTML&lt;/b&gt; and <img src=\"image.png\" alt=\"image\" />.</p>",
+            "",
+        );
+
+        assert_eq!(super::to_html(markdown, true), expected_html);
+    }
+}
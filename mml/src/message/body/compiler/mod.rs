@@ -2,6 +2,10 @@
 //!
 //! Module dedicated to MML → MIME message body compilation.
 
+mod diagnostic;
+mod html;
+#[cfg(feature = "markdown")]
+mod markdown;
 mod parsers;
 mod tokens;
 
@@ -9,26 +13,37 @@
 #[allow(unused_imports)]
 use log::{debug, warn};
 use mail_builder::{
+    headers::{content_type::ContentType, text::Text},
     mime::{BodyPart, MimePart},
     MessageBuilder,
 };
+use nanohtml2text::html2text;
 use shellexpand_utils::shellexpand_path;
-use std::{ffi::OsStr, fs, ops::Deref};
+use std::{ffi::OsStr, fs, ops::Deref, path::PathBuf};
 
 #[cfg(feature = "pgp")]
 use crate::pgp::Pgp;
 use crate::{Error, Result};
 
+#[cfg(feature = "markdown")]
+use super::TEXT_MARKDOWN;
 use super::{
-    ALTERNATIVE, ATTACHMENT, DISPOSITION, ENCODING, ENCODING_7BIT, ENCODING_8BIT, ENCODING_BASE64,
-    ENCODING_QUOTED_PRINTABLE, FILENAME, INLINE, MIXED, MULTIPART_BEGIN, MULTIPART_BEGIN_ESCAPED,
-    MULTIPART_END, MULTIPART_END_ESCAPED, NAME, PART_BEGIN, PART_BEGIN_ESCAPED, PART_END,
-    PART_END_ESCAPED, RECIPIENT_FILENAME, RELATED, TYPE,
+    ALTERNATIVE, ATTACHMENT, CHARSET, DATA_ENCODING, DESCRIPTION, DISPOSITION, ENCODING,
+    ENCODING_7BIT, ENCODING_8BIT, ENCODING_BASE64, ENCODING_QUOTED_PRINTABLE, FILENAME, ID, INLINE,
+    MIXED, MULTIPART_BEGIN, MULTIPART_BEGIN_ESCAPED, MULTIPART_END, MULTIPART_END_ESCAPED, NAME,
+    PART_BEGIN, PART_BEGIN_ESCAPED, PART_END, PART_END_ESCAPED, RECIPIENT_FILENAME, RELATED,
+    TEXT_HTML, TEXT_PLAIN, TYPE,
 };
 #[cfg(feature = "pgp")]
 use super::{ENCRYPT, PGP_MIME, SIGN};
 
-use self::{parsers::prelude::*, tokens::Part};
+use self::{
+    parsers::prelude::*,
+    tokens::{Part, Props},
+};
+
+#[doc(inline)]
+pub use self::diagnostic::Diagnostic;
 
 /// MML → MIME message body compiler.
 ///
@@ -36,6 +51,30 @@
 /// is named `compile`.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 pub struct MmlBodyCompiler {
+    /// Compose HTML parts when true.
+    ///
+    /// A `text/plain` alternative is generated from the HTML, unless
+    /// the HTML part already belongs to a `multipart/alternative`
+    /// part. This only applies to HTML parts with an inline body (no
+    /// `filename`, `disposition` or `data-encoding` property).
+    compose_html: bool,
+
+    /// Escape raw HTML of markdown parts when true.
+    ///
+    /// Raw HTML is escaped and rendered as text, instead of being
+    /// passed through to the `text/html` part.
+    #[cfg(feature = "markdown")]
+    escape_markdown_html: bool,
+
+    /// The directory local images of composed parts are embedded
+    /// from.
+    ///
+    /// When defined, images referenced by composed parts with a path
+    /// relative to this directory are embedded inline under an
+    /// automatically generated `multipart/related` part. Other
+    /// images are never embedded.
+    inline_images_dir: Option<PathBuf>,
+
     #[cfg(feature = "pgp")]
     pgp: Option<Pgp>,
     #[cfg(feature = "pgp")]
@@ -50,6 +89,66 @@ pub fn new() -> Self {
         Self::default()
     }
 
+    pub fn set_compose_html(&mut self, b: bool) {
+        self.compose_html = b;
+    }
+
+    /// Compose HTML parts, following the builder pattern.
+    ///
+    /// A `text/plain` alternative is generated from the HTML, and
+    /// local images are embedded inline when enabled via
+    /// [`MmlBodyCompiler::with_inline_images_dir`]:
+    ///
+    /// ```text
+    /// multipart/alternative
+    ///   text/plain
+    ///   multipart/related
+    ///     text/html
+    ///     image/png (inline)
+    /// ```
+    ///
+    /// HTML parts already belonging to a `multipart/alternative` part
+    /// are kept as they are, only their local images are embedded.
+    pub fn with_compose_html(mut self, b: bool) -> Self {
+        self.set_compose_html(b);
+        self
+    }
+
+    #[cfg(feature = "markdown")]
+    pub fn set_escape_markdown_html(&mut self, b: bool) {
+        self.escape_markdown_html = b;
+    }
+
+    /// Escape raw HTML of markdown parts, following the builder
+    /// pattern.
+    ///
+    /// By default, raw HTML is passed through to the rendered
+    /// `text/html` part, as defined by CommonMark. When enabled, raw
+    /// HTML is escaped and rendered as text instead, which suits
+    /// markdown coming from untrusted sources.
+    #[cfg(feature = "markdown")]
+    pub fn with_escape_markdown_html(mut self, b: bool) -> Self {
+        self.set_escape_markdown_html(b);
+        self
+    }
+
+    pub fn set_inline_images_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.inline_images_dir = Some(dir.into());
+    }
+
+    /// Embed local images of composed parts from the given
+    /// directory, following the builder pattern.
+    ///
+    /// Only images referenced by a path relative to this directory
+    /// are embedded, for example `<img src="images/logo.png">`.
+    /// Absolute paths, paths starting by `~`, `file:` URLs and paths
+    /// resolving outside of the directory are left untouched, so
+    /// that a message cannot embed arbitrary local files.
+    pub fn with_inline_images_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.set_inline_images_dir(dir);
+        self
+    }
+
     #[cfg(feature = "pgp")]
     pub fn set_pgp(&mut self, pgp: impl Into<Pgp>) {
         self.pgp = Some(pgp.into());
@@ -216,6 +315,117 @@ fn unescape_mml_markup(text: impl AsRef<str>) -> String {
             .replace(MULTIPART_END_ESCAPED, MULTIPART_END)
     }
 
+    /// Apply properties shared by all kinds of parts to the given
+    /// MIME part: the description (Content-Description) and the
+    /// identifier (Content-ID).
+    fn compile_shared_props(props: &Props, mut part: MimePart<'a>) -> MimePart<'a> {
+        if let Some(desc) = props.get(DESCRIPTION) {
+            part = part.header("Content-Description", Text::new(desc.to_string()));
+        }
+
+        if let Some(id) = props.get(ID) {
+            part = part.cid(id.to_string());
+        }
+
+        part
+    }
+
+    /// Return `true` if the given part properties match a part that
+    /// can be composed.
+    ///
+    /// HTML parts are composed when enabled via
+    /// [`MmlBodyCompiler::with_compose_html`], markdown parts are
+    /// always composed. Only parts with
+    /// an inline body (no `filename`, `disposition` or
+    /// `data-encoding` property) can be composed.
+    fn is_composable(&self, props: &Props) -> bool {
+        if props.contains_key(FILENAME)
+            || props.contains_key(DISPOSITION)
+            || props.contains_key(DATA_ENCODING)
+        {
+            return false;
+        }
+
+        match props.get(TYPE).map(Deref::deref) {
+            Some(TEXT_HTML) => self.compose_html,
+            #[cfg(feature = "markdown")]
+            Some(TEXT_MARKDOWN) => true,
+            _ => false,
+        }
+    }
+
+    /// Compose the given part body, according to its type.
+    ///
+    /// HTML and markdown parts belonging to a `multipart/alternative`
+    /// part do not get a plain text alternative, since they already
+    /// have one. See [`MmlBodyCompiler::is_composable`].
+    fn compose_part(
+        &self,
+        props: &Props,
+        body: &str,
+        in_alternative: bool,
+    ) -> Result<MimePart<'a>> {
+        match props.get(TYPE).map(Deref::deref) {
+            #[cfg(feature = "markdown")]
+            Some(TEXT_MARKDOWN) => {
+                let html = markdown::to_html(body, self.escape_markdown_html);
+                if in_alternative {
+                    self.compose_html_part(&html)
+                } else {
+                    self.compose_alternative_part(body.to_owned(), &html)
+                }
+            }
+            _ if in_alternative => self.compose_html_part(body),
+            _ => self.compose_alternative_part(html2text(body), body),
+        }
+    }
+
+    /// Compose a `multipart/alternative` part from the given plain
+    /// text and HTML.
+    fn compose_alternative_part(&self, text: String, html: &str) -> Result<MimePart<'a>> {
+        let text_part = MimePart::new(TEXT_PLAIN, text);
+        let html_part = self.compose_html_part(html)?;
+        let part = MimePart::new("multipart/alternative", vec![text_part, html_part]);
+        Ok(part)
+    }
+
+    /// Compose a HTML part.
+    ///
+    /// Local images referenced by the HTML are replaced by `cid:`
+    /// URLs and embedded inline next to the HTML part, inside a
+    /// `multipart/related` part. See
+    /// [`MmlBodyCompiler::with_inline_images_dir`].
+    fn compose_html_part(&self, html: &str) -> Result<MimePart<'a>> {
+        let (html, images) = match &self.inline_images_dir {
+            Some(dir) => html::replace_local_img_srcs(html, dir),
+            None => (html.to_owned(), Vec::new()),
+        };
+
+        let mut html_part = MimePart::new(TEXT_HTML, html);
+
+        if !images.is_empty() {
+            let mut related_parts = vec![html_part];
+
+            for image in images {
+                let contents = fs::read(&image.path)
+                    .map_err(|err| Error::ReadInlineImageError(err, image.path.clone()))?;
+                debug!("embedding inline image {:?} as <{}>", image.path, image.cid);
+
+                let mut ctype = ContentType::new(tree_magic_mini::from_u8(&contents).to_owned());
+                if let Some(fname) = image.path.file_name().and_then(OsStr::to_str) {
+                    ctype = ctype.attribute("name", fname.to_owned());
+                }
+
+                let part = MimePart::new(ctype, contents).inline().cid(image.cid);
+                related_parts.push(part);
+            }
+
+            html_part = MimePart::new("multipart/related", related_parts);
+        }
+
+        Ok(html_part)
+    }
+
     /// Compile given parts parsed from a MML body to a
     /// [MessageBuilder].
     async fn compile_parts(&'a self, parts: Vec<Part<'a>>) -> Result<MessageBuilder> {
@@ -223,12 +433,15 @@ async fn compile_parts(&'a self, parts: Vec<Part<'a>>) -> Result<MessageBuilder>
 
         builder = match parts.len() {
             0 => builder.text_body(String::new()),
-            1 => builder.body(self.compile_part(parts.into_iter().next().unwrap()).await?),
+            1 => builder.body(
+                self.compile_part(parts.into_iter().next().unwrap(), false)
+                    .await?,
+            ),
             _ => {
                 let mut compiled_parts = Vec::new();
 
                 for part in parts {
-                    let part = self.compile_part(part).await?;
+                    let part = self.compile_part(part, false).await?;
                     compiled_parts.push(part);
                 }
 
@@ -240,16 +453,20 @@ async fn compile_parts(&'a self, parts: Vec<Part<'a>>) -> Result<MessageBuilder>
     }
 
     /// Compile the given part parsed from MML body to a [MimePart].
+    ///
+    /// The `in_alternative` argument tells if the part belongs to a
+    /// `multipart/alternative` part.
     #[async_recursion]
-    async fn compile_part(&'a self, part: Part<'a>) -> Result<MimePart> {
+    async fn compile_part(&'a self, part: Part<'a>, in_alternative: bool) -> Result<MimePart> {
         match part {
             Part::Multi(props, parts) => {
                 let no_parts = BodyPart::Multipart(Vec::new());
+                let is_alternative = props.get(TYPE).map(Deref::deref) == Some(ALTERNATIVE);
 
-                let mut multi_part = match props.get(TYPE) {
-                    Some(&MIXED) | None => MimePart::new("multipart/mixed", no_parts),
-                    Some(&ALTERNATIVE) => MimePart::new("multipart/alternative", no_parts),
-                    Some(&RELATED) => MimePart::new("multipart/related", no_parts),
+                let mut multi_part = match props.get(TYPE).map(Deref::deref) {
+                    Some(MIXED) | None => MimePart::new("multipart/mixed", no_parts),
+                    Some(ALTERNATIVE) => MimePart::new("multipart/alternative", no_parts),
+                    Some(RELATED) => MimePart::new("multipart/related", no_parts),
                     Some(unknown) => {
                         debug!("unknown multipart type {unknown}, falling back to mixed");
                         MimePart::new("multipart/mixed", no_parts)
@@ -257,26 +474,47 @@ async fn compile_part(&'a self, part: Part<'a>) -> Result<MimePart> {
                 };
 
                 for part in parts {
-                    multi_part.add_part(self.compile_part(part).await?)
+                    multi_part.add_part(self.compile_part(part, is_alternative).await?)
                 }
 
+                multi_part = Self::compile_shared_props(&props, multi_part);
+
                 #[cfg(feature = "pgp")]
                 {
-                    multi_part = match props.get(SIGN) {
-                        Some(&PGP_MIME) => self.try_sign_part(multi_part).await,
+                    multi_part = match props.get(SIGN).map(Deref::deref) {
+                        Some(PGP_MIME) => self.try_sign_part(multi_part).await,
                         _ => multi_part,
                     };
 
-                    multi_part = match props.get(ENCRYPT) {
-                        Some(&PGP_MIME) => self.try_encrypt_part(multi_part).await,
+                    multi_part = match props.get(ENCRYPT).map(Deref::deref) {
+                        Some(PGP_MIME) => self.try_encrypt_part(multi_part).await,
                         _ => multi_part,
                     };
                 }
 
                 Ok(multi_part)
             }
+            Part::Single(ref props, body) if self.is_composable(props) => {
+                #[allow(unused_mut)]
+                let mut part = self.compose_part(props, body, in_alternative)?;
+
+                #[cfg(feature = "pgp")]
+                {
+                    part = match props.get(SIGN).map(Deref::deref) {
+                        Some(PGP_MIME) => self.try_sign_part(part).await,
+                        _ => part,
+                    };
+
+                    part = match props.get(ENCRYPT).map(Deref::deref) {
+                        Some(PGP_MIME) => self.try_encrypt_part(part).await,
+                        _ => part,
+                    };
+                };
+
+                Ok(part)
+            }
             Part::Single(ref props, body) => {
-                let fpath = props.get(FILENAME).map(shellexpand_path);
+                let fpath = props.get(FILENAME).map(Deref::deref).map(shellexpand_path);
 
                 let mut part = match &fpath {
                     Some(fpath) => {
@@ -284,7 +522,12 @@ async fn compile_part(&'a self, part: Part<'a>) -> Result<MimePart> {
                             .map_err(|err| Error::ReadAttachmentError(err, fpath.clone()))?;
                         let mut ctype = Part::get_or_guess_content_type(props, &contents).into();
                         if let Some(name) = props.get(NAME) {
-                            ctype = ctype.attribute("name", *name);
+                            ctype = ctype.attribute("name", name.clone());
+                        }
+                        // the file is attached as it is, so its
+                        // charset can only be declared
+                        if let Some(charset) = props.get(CHARSET) {
+                            ctype = ctype.attribute("charset", charset.clone());
                         }
                         MimePart::new(ctype, contents)
                     }
@@ -292,25 +535,30 @@ async fn compile_part(&'a self, part: Part<'a>) -> Result<MimePart> {
                         let mut ctype =
                             Part::get_or_guess_content_type(props, body.as_bytes()).into();
                         if let Some(name) = props.get(NAME) {
-                            ctype = ctype.attribute("name", *name);
+                            ctype = ctype.attribute("name", name.clone());
+                        }
+                        if let Some(charset) = props.get(CHARSET) {
+                            debug!("ignoring charset {charset}: inline bodies are UTF-8 encoded");
                         }
-                        MimePart::new(ctype, body)
+                        MimePart::new(ctype, Self::unescape_mml_markup(body))
                     }
                 };
 
-                part = match props.get(ENCODING) {
-                    Some(&ENCODING_7BIT) => part.transfer_encoding(ENCODING_7BIT),
-                    Some(&ENCODING_8BIT) => part.transfer_encoding(ENCODING_8BIT),
-                    Some(&ENCODING_QUOTED_PRINTABLE) => {
+                part = Self::compile_shared_props(props, part);
+
+                part = match props.get(ENCODING).map(Deref::deref) {
+                    Some(ENCODING_7BIT) => part.transfer_encoding(ENCODING_7BIT),
+                    Some(ENCODING_8BIT) => part.transfer_encoding(ENCODING_8BIT),
+                    Some(ENCODING_QUOTED_PRINTABLE) => {
                         part.transfer_encoding(ENCODING_QUOTED_PRINTABLE)
                     }
-                    Some(&ENCODING_BASE64) => part.transfer_encoding(ENCODING_BASE64),
+                    Some(ENCODING_BASE64) => part.transfer_encoding(ENCODING_BASE64),
                     _ => part,
                 };
 
-                part = match props.get(DISPOSITION) {
-                    Some(&INLINE) => part.inline(),
-                    Some(&ATTACHMENT) => part.attachment(
+                part = match props.get(DISPOSITION).map(Deref::deref) {
+                    Some(INLINE) => part.inline(),
+                    Some(ATTACHMENT) => part.attachment(
                         props
                             .get(RECIPIENT_FILENAME)
                             .map(Deref::deref)
@@ -339,13 +587,13 @@ async fn compile_part(&'a self, part: Part<'a>) -> Result<MimePart> {
 
                 #[cfg(feature = "pgp")]
                 {
-                    part = match props.get(SIGN) {
-                        Some(&PGP_MIME) => self.try_sign_part(part).await,
+                    part = match props.get(SIGN).map(Deref::deref) {
+                        Some(PGP_MIME) => self.try_sign_part(part).await,
                         _ => part,
                     };
 
-                    part = match props.get(ENCRYPT) {
-                        Some(&PGP_MIME) => self.try_encrypt_part(part).await,
+                    part = match props.get(ENCRYPT).map(Deref::deref) {
+                        Some(PGP_MIME) => self.try_encrypt_part(part).await,
                         _ => part,
                     };
                 };
@@ -366,8 +614,11 @@ pub async fn compile(&'a self, mml_body: &'a str) -> Result<MessageBuilder> {
         if let Some(parts) = res.output() {
             Ok(self.compile_parts(parts.to_owned()).await?)
         } else {
-            let errs = res.errors().map(|err| err.clone().into_owned()).collect();
-            Err(Error::ParseMmlError(errs, mml_body.to_owned()))
+            let diagnostics = res
+                .errors()
+                .map(|err| Diagnostic::new(err, mml_body))
+                .collect();
+            Err(Error::ParseMmlError(diagnostics, mml_body.to_owned()))
         }
     }
 }
@@ -375,6 +626,7 @@ pub async fn compile(&'a self, mml_body: &'a str) -> Result<MessageBuilder> {
 #[cfg(test)]
 mod tests {
     use concat_with::concat_line;
+    use mail_parser::{MessageParser, MimeHeaders};
     use std::io::prelude::*;
     use tempfile::Builder;
 
@@ -438,6 +690,37 @@ async fn html() {
         assert_eq!(msg, expected_msg);
     }
 
+    #[tokio::test]
+    async fn escaped_markup_in_part() {
+        let mml_body = concat_line!(
+            "<#part type=\"text/plain\">",
+            "<#!part>This should be unescaped<#!/part>",
+            "<#/part>",
+        );
+
+        let msg = MmlBodyCompiler::new()
+            .compile(mml_body)
+            .await
+            .unwrap()
+            .message_id("id@localhost")
+            .date(0_u64)
+            .write_to_string()
+            .unwrap();
+
+        let expected_msg = concat_line!(
+            "Message-ID: <id@localhost>\r",
+            "Date: Thu, 1 Jan 1970 00:00:00 +0000\r",
+            "MIME-Version: 1.0\r",
+            "Content-Type: text/plain; charset=\"utf-8\"\r",
+            "Content-Transfer-Encoding: 7bit\r",
+            "\r",
+            "<#part>This should be unescaped<#/part>\r",
+            "",
+        );
+
+        assert_eq!(msg, expected_msg);
+    }
+
     #[tokio::test]
     async fn attachment() {
         let mut attachment = Builder::new()
@@ -475,4 +758,163 @@ async fn attachment() {
 
         assert_eq!(msg, expected_msg);
     }
+
+    #[tokio::test]
+    async fn composed_html() {
+        let img_dir = concat!(env!("CARGO_MANIFEST_DIR"), "/examples");
+        let mml_body =
+            "<#part type=text/html><h1>Hello, world!</h1><img src=\"attachment.png\"><#/part>";
+
+        let msg = MmlBodyCompiler::new()
+            .with_compose_html(true)
+            .with_inline_images_dir(img_dir)
+            .compile(mml_body)
+            .await
+            .unwrap()
+            .write_to_string()
+            .unwrap();
+
+        let msg = MessageParser::new().parse(msg.as_bytes()).unwrap();
+
+        let ctype = |id: usize| {
+            let ctype = msg.part(id).unwrap().content_type().unwrap();
+            format!("{}/{}", ctype.ctype(), ctype.subtype().unwrap())
+        };
+
+        assert_eq!(ctype(0), "multipart/alternative");
+        assert_eq!(ctype(1), "text/plain");
+        assert_eq!(ctype(2), "multipart/related");
+        assert_eq!(ctype(3), "text/html");
+        assert_eq!(ctype(4), "image/png");
+
+        let cid = msg.part(4).unwrap().content_id().unwrap();
+        let html = msg.body_html(0).unwrap();
+        assert!(html.contains(&format!("<img src=\"cid:{cid}\">")));
+        assert!(!html.contains("attachment.png"));
+
+        let text = msg.body_text(0).unwrap();
+        assert!(text.contains("Hello, world!"));
+    }
+
+    #[tokio::test]
+    async fn composed_html_without_inline_images_dir() {
+        let img_path = concat!(env!("CARGO_MANIFEST_DIR"), "/examples/attachment.png");
+        let mml_body = format!("<#part type=text/html><img src=\"{img_path}\"><#/part>");
+
+        let msg = MmlBodyCompiler::new()
+            .with_compose_html(true)
+            .compile(&mml_body)
+            .await
+            .unwrap()
+            .write_to_string()
+            .unwrap();
+
+        let msg = MessageParser::new().parse(msg.as_bytes()).unwrap();
+
+        assert_eq!(msg.parts.len(), 3);
+        assert!(msg.body_html(0).unwrap().contains(img_path));
+    }
+
+    #[tokio::test]
+    async fn composed_html_in_alternative() {
+        let mml_body = concat_line!(
+            "<#multipart type=alternative>",
+            "<#part type=text/plain>",
+            "Hello, world!",
+            "<#part type=text/html>",
+            "<h1>Hello, world!</h1>",
+            "<#/part>",
+            "<#/multipart>",
+        );
+
+        let msg = MmlBodyCompiler::new()
+            .with_compose_html(true)
+            .compile(mml_body)
+            .await
+            .unwrap()
+            .write_to_string()
+            .unwrap();
+
+        let msg = MessageParser::new().parse(msg.as_bytes()).unwrap();
+
+        let ctype = |id: usize| {
+            let ctype = msg.part(id).unwrap().content_type().unwrap();
+            format!("{}/{}", ctype.ctype(), ctype.subtype().unwrap())
+        };
+
+        assert_eq!(msg.parts.len(), 3);
+        assert_eq!(ctype(0), "multipart/alternative");
+        assert_eq!(ctype(1), "text/plain");
+        assert_eq!(ctype(2), "text/html");
+    }
+
+    #[cfg(feature = "markdown")]
+    #[tokio::test]
+    async fn markdown() {
+        let mml_body = concat_line!(
+            "<#part type=text/markdown>",
+            "# Hello",
+            "",
+            "This is *markdown*.",
+            "<#/part>",
+        );
+
+        let msg = MmlBodyCompiler::new()
+            .compile(mml_body)
+            .await
+            .unwrap()
+            .write_to_string()
+            .unwrap();
+
+        let msg = MessageParser::new().parse(msg.as_bytes()).unwrap();
+
+        let ctype = msg.root_part().content_type().unwrap();
+        assert_eq!(ctype.ctype(), "multipart");
+        assert_eq!(ctype.subtype(), Some("alternative"));
+
+        let text = msg.body_text(0).unwrap();
+        assert_eq!(text.replace('\r', ""), "# Hello\n\nThis is *markdown*.\n");
+
+        let html = msg.body_html(0).unwrap();
+        assert_eq!(
+            html.replace('\r', ""),
+            "<h1>Hello</h1>\n<p>This is <em>markdown</em>.</p>\n"
+        );
+    }
+
+    #[cfg(feature = "markdown")]
+    #[tokio::test]
+    async fn markdown_in_alternative() {
+        let mml_body = concat_line!(
+            "<#multipart type=alternative>",
+            "<#part type=text/plain>",
+            "Hello, world!",
+            "<#part type=text/markdown>",
+            "# Hello, <b>world</b>!",
+            "<#/part>",
+            "<#/multipart>",
+        );
+
+        let msg = MmlBodyCompiler::new()
+            .compile(mml_body)
+            .await
+            .unwrap()
+            .write_to_string()
+            .unwrap();
+
+        let msg = MessageParser::new().parse(msg.as_bytes()).unwrap();
+
+        let ctype = |id: usize| {
+            let ctype = msg.part(id).unwrap().content_type().unwrap();
+            format!("{}/{}", ctype.ctype(), ctype.subtype().unwrap())
+        };
+
+        assert_eq!(msg.parts.len(), 3);
+        assert_eq!(ctype(0), "multipart/alternative");
+        assert_eq!(ctype(1), "text/plain");
+        assert_eq!(ctype(2), "text/html");
+
+        let html = msg.body_html(0).unwrap();
+        assert_eq!(html.replace('\r', ""), "<h1>Hello, <b>world</b>!</h1>\n");
+    }
 }
@@ -1,3 +1,50 @@
+//! # MML parsers
+//!
+//! Module dedicated to MML body parsing. The grammar of a MML body,
+//! expressed in [EBNF], is the following:
+//!
+//! ```text
+//! body           = { multipart | part | text } ;
+//!
+//! multipart      = "<#multipart" { multipart-prop } ">" [ "\n" ]
+//!                  { multipart | part | text }
+//!                  "<#/multipart>" [ "\n" ] ;
+//! part           = "<#part" { part-prop } ">" [ "\n" ]
+//!                  [ text ]
+//!                  [ "<#/part>" [ "\n" ] ] ;
+//! text           = ? any sequence of chars not containing "<#part",
+//!                    "<#/part>", "<#multipart" or "<#/multipart>" ? ;
+//!
+//! multipart-prop = ws ( "type" "=" multipart-type
+//!                     | ( "description" | "id" ) "=" value
+//!                     | ( "sign" | "encrypt" ) "=" const("pgpmime") ) ws ;
+//! part-prop      = ws ( ( "type" | "filename" | "recipient-filename"
+//!                       | "name" | "charset" | "creation-date"
+//!                       | "modification-date" | "read-date"
+//!                       | "description" | "id" ) "=" value
+//!                     | "disposition" "=" disposition
+//!                     | "encoding" "=" encoding
+//!                     | "data-encoding" "=" data-encoding
+//!                     | ( "sign" | "encrypt" ) "=" const("pgpmime") ) ws ;
+//!
+//! multipart-type = const("mixed") | const("alternative") | const("related") ;
+//! disposition    = const("inline") | const("attachment") ;
+//! encoding       = const("7bit") | const("8bit") | data-encoding ;
+//! data-encoding  = const("quoted-printable") | const("base64") ;
+//! const(v)       = v | '"' v '"' ;
+//!
+//! value          = quoted-value | unquoted-value ;
+//! quoted-value   = '"' { ? any char except '\\' and '"' ? | '\\' ( '\\' | '"' ) } '"' ;
+//! unquoted-value = ( ? any char except '\\', ' ' and '>' ? | '\\' ( '\\' | ' ' | '>' ) )
+//!                  { ? any char except '\\', ' ' and '>' ? | '\\' ( '\\' | ' ' | '>' ) } ;
+//! ws             = { ? whitespace ? } ;
+//! ```
+//!
+//! Parsing errors are reported as
+//! [`Diagnostic`](super::diagnostic::Diagnostic)s.
+//!
+//! [EBNF]: https://en.wikipedia.org/wiki/Extended_Backus%E2%80%93Naur_form
+
 mod parts;
 mod props;
 mod vals;
@@ -13,6 +60,8 @@ pub(crate) mod prelude {
 
     pub(crate) use chumsky::prelude::*;
 
+    use crate::message::body::compiler::tokens::Val;
+
     use super::maybe_quoted_const_val;
 
     pub type ParserError<'a> = extra::Err<Rich<'a, char>>;
@@ -47,36 +96,36 @@ pub(crate) fn multipart_end<'a>() -> impl Parser<'a, &'a str, &'a str, ParserErr
         just(MULTIPART_END).labelled("multipart closing tag '<#/multipart>'")
     }
 
-    pub(crate) fn inline<'a>() -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone {
+    pub(crate) fn inline<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone {
         maybe_quoted_const_val(INLINE).labelled(INLINE)
     }
 
-    pub(crate) fn attachment<'a>() -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone {
+    pub(crate) fn attachment<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone {
         maybe_quoted_const_val(ATTACHMENT).labelled(ATTACHMENT)
     }
 
-    pub(crate) fn encoding_7bit<'a>() -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone
+    pub(crate) fn encoding_7bit<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone
     {
         maybe_quoted_const_val(ENCODING_7BIT).labelled(ENCODING_7BIT)
     }
 
-    pub(crate) fn encoding_8bit<'a>() -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone
+    pub(crate) fn encoding_8bit<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone
     {
         maybe_quoted_const_val(ENCODING_8BIT).labelled(ENCODING_8BIT)
     }
 
     pub(crate) fn encoding_quoted_printable<'a>(
-    ) -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone {
+    ) -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone {
         maybe_quoted_const_val(ENCODING_QUOTED_PRINTABLE).labelled(ENCODING_QUOTED_PRINTABLE)
     }
 
-    pub(crate) fn encoding_base64<'a>() -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone
+    pub(crate) fn encoding_base64<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone
     {
         maybe_quoted_const_val(ENCODING_BASE64).labelled(ENCODING_BASE64)
     }
 
     #[cfg(feature = "pgp")]
-    pub(crate) fn pgp_mime<'a>() -> impl Parser<'a, &'a str, &'a str, ParserError<'a>> + Clone {
+    pub(crate) fn pgp_mime<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone {
         maybe_quoted_const_val(PGP_MIME).labelled(PGP_MIME)
     }
 }
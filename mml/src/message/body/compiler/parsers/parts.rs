@@ -6,8 +6,8 @@
 };
 
 use super::{
-    creation_date, data_encoding, description, disposition, encoding, filename, modification_date,
-    multipart_type, name, part_type, prelude::*, read_date, recipient_filename,
+    charset, creation_date, data_encoding, description, disposition, encoding, filename, id,
+    modification_date, multipart_type, name, part_type, prelude::*, read_date, recipient_filename,
 };
 #[cfg(feature = "pgp")]
 use super::{encrypt, sign};
@@ -36,6 +36,7 @@ pub(crate) fn multipart<'a>() -> impl Parser<'a, &'a str, Part<'a>, ParserError<
                 choice((
                     multipart_type(),
                     description(),
+                    id(),
                     #[cfg(feature = "pgp")]
                     encrypt(),
                     #[cfg(feature = "pgp")]
@@ -70,12 +71,14 @@ pub(crate) fn part<'a>() -> impl Parser<'a, &'a str, Part<'a>, ParserError<'a>>
                 filename(),
                 recipient_filename(),
                 name(),
+                charset(),
                 encoding(),
                 data_encoding(),
                 creation_date(),
                 modification_date(),
                 read_date(),
                 description(),
+                id(),
                 disposition(),
                 #[cfg(feature = "pgp")]
                 encrypt(),
@@ -112,7 +115,7 @@ mod tests {
 
     use crate::message::body::{
         compiler::{parsers::prelude::*, tokens::Part},
-        DISPOSITION, FILENAME, NAME, TYPE,
+        CHARSET, DESCRIPTION, DISPOSITION, FILENAME, ID, NAME, TYPE,
     };
 
     #[test]
@@ -177,7 +180,7 @@ fn single_html_part() {
                 ))
                 .into_result(),
             Ok(Part::Single(
-                HashMap::from_iter([(TYPE, "text/html")]),
+                HashMap::from_iter([(TYPE, "text/html".into())]),
                 "<h1>This is a HTML text part.</h1>\n",
             )),
         );
@@ -191,9 +194,27 @@ fn attachment() {
                 .into_result(),
             Ok(Part::Single(
                 HashMap::from_iter([
-                    (TYPE, "image/jpeg"),
-                    (FILENAME, "~/rms.jpg"),
-                    (DISPOSITION, "inline")
+                    (TYPE, "image/jpeg".into()),
+                    (FILENAME, "~/rms.jpg".into()),
+                    (DISPOSITION, "inline".into())
+                ]),
+                ""
+            )),
+        );
+    }
+
+    #[test]
+    fn round_trip_props() {
+        assert_eq!(
+            super::part()
+                .parse("<#part type=text/plain charset=utf-8 description=\"A description\" id=id@localhost><#/part>")
+                .into_result(),
+            Ok(Part::Single(
+                HashMap::from_iter([
+                    (TYPE, "text/plain".into()),
+                    (CHARSET, "utf-8".into()),
+                    (DESCRIPTION, "A description".into()),
+                    (ID, "id@localhost".into())
                 ]),
                 ""
             )),
@@ -315,11 +336,11 @@ fn simple_mml() {
                 ))
                 .into_result(),
             Ok(vec![Part::Multi(
-                HashMap::from_iter([(TYPE, "alternative")]),
+                HashMap::from_iter([(TYPE, "alternative".into())]),
                 vec![
                     Part::PlainText("This is a plain text part.\n"),
                     Part::Single(
-                        HashMap::from_iter([(TYPE, "text/enriched")]),
+                        HashMap::from_iter([(TYPE, "text/enriched".into())]),
                         "<center>This is a centered enriched part</center>\n"
                     )
                 ]
@@ -349,24 +370,24 @@ fn advanced_mml() {
                 ))
                 .unwrap(),
             vec![Part::Multi(
-                HashMap::from_iter([(TYPE, "mixed")]),
+                HashMap::from_iter([(TYPE, "mixed".into())]),
                 vec![
                     Part::Single(
                         HashMap::from_iter([
-                            (TYPE, "image/jpeg"),
-                            (FILENAME, "~/rms.jpg"),
-                            (DISPOSITION, "inline")
+                            (TYPE, "image/jpeg".into()),
+                            (FILENAME, "~/rms.jpg".into()),
+                            (DISPOSITION, "inline".into())
                         ]),
                         ""
                     ),
                     Part::Multi(
-                        HashMap::from_iter([(TYPE, "alternative")]),
+                        HashMap::from_iter([(TYPE, "alternative".into())]),
                         vec![
                             Part::PlainText("This is a plain text part.\n"),
                             Part::Single(
                                 HashMap::from_iter([
-                                    (TYPE, "text/enriched"),
-                                    (NAME, "enriched.txt")
+                                    (TYPE, "text/enriched".into()),
+                                    (NAME, "enriched.txt".into())
                                 ]),
                                 "<center>This is a centered enriched part</center>\n",
                             )
@@ -374,7 +395,7 @@ fn advanced_mml() {
                     ),
                     Part::PlainText("This is a new plain text part.\n"),
                     Part::Single(
-                        HashMap::from_iter([(DISPOSITION, "attachment")]),
+                        HashMap::from_iter([(DISPOSITION, "attachment".into())]),
                         "This plain text part is an attachment.\n",
                     )
                 ]
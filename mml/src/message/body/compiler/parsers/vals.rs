@@ -4,6 +4,8 @@
 //! MML message bodies: [val], [quoted_val] and
 //! [maybe_quoted_const_val].
 
+use std::borrow::Cow;
+
 use crate::message::body::{compiler::tokens::Val, BACKSLASH, DOUBLE_QUOTE, GREATER_THAN, SPACE};
 
 use super::prelude::*;
@@ -12,8 +14,8 @@
 ///
 /// It parses all characters except the backslack, the space and the
 /// greater-than characters. They still can be parsed by escaping them
-/// with a backslash.
-pub(crate) fn val<'a>() -> impl Parser<'a, &'a str, String, ParserError<'a>> + Clone {
+/// with a backslash, backslashes are removed from the parsed value.
+pub(crate) fn val<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone {
     let escapable_chars = [BACKSLASH, SPACE, GREATER_THAN];
 
     choice((
@@ -22,14 +24,15 @@ pub(crate) fn val<'a>() -> impl Parser<'a, &'a str, String, ParserError<'a>> + C
     ))
     .repeated()
     .at_least(1)
-    .collect()
+    .to_slice()
+    .map(unescape)
 }
 
 /// The quoted property value parser.
 ///
 /// It parses all characters except the backslack and the double quote
 /// characters. They still can be parsed by escaping them with a
-/// backslack.
+/// backslack, backslashes are removed from the parsed value.
 pub(crate) fn quoted_val<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<'a>> + Clone {
     let escapable_chars = [BACKSLASH, DOUBLE_QUOTE];
 
@@ -39,6 +42,7 @@ pub(crate) fn quoted_val<'a>() -> impl Parser<'a, &'a str, Val<'a>, ParserError<
     ))
     .repeated()
     .to_slice()
+    .map(unescape)
     .delimited_by(dquote(), dquote())
 }
 
@@ -51,6 +55,31 @@ pub(crate) fn maybe_quoted_const_val(val: &str) -> impl Parser<&str, Val, Parser
         just(val).to_slice().delimited_by(dquote(), dquote()),
         just(val).to_slice(),
     ))
+    .map(Cow::Borrowed)
+}
+
+/// Remove the backslashes escaping the characters of the given
+/// value.
+///
+/// The value is borrowed back when it does not contain any escaped
+/// character.
+fn unescape(val: &str) -> Val<'_> {
+    if !val.contains(BACKSLASH) {
+        return Cow::Borrowed(val);
+    }
+
+    let mut unescaped = String::with_capacity(val.len());
+    let mut chars = val.chars();
+
+    while let Some(c) = chars.next() {
+        if c == BACKSLASH {
+            unescaped.extend(chars.next());
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    Cow::Owned(unescaped)
 }
 
 #[cfg(test)]
@@ -78,18 +107,21 @@ fn val() {
 
     #[test]
     fn quoted_val() {
-        assert_eq!(super::quoted_val().parse("\"\"").into_result(), Ok(""));
+        assert_eq!(
+            super::quoted_val().parse("\"\"").into_result(),
+            Ok("".into())
+        );
 
         assert_eq!(
             super::quoted_val().parse("\"quoted val\"").into_result(),
-            Ok("quoted val"),
+            Ok("quoted val".into()),
         );
 
         assert_eq!(
             super::quoted_val()
                 .parse("\"\\\\quoted \\\"val\\\"\"")
                 .into_result(),
-            Ok("\\\\quoted \\\"val\\\""),
+            Ok("\\quoted \"val\"".into()),
         );
     }
 
@@ -99,14 +131,14 @@ fn maybe_quoted_val() {
             super::maybe_quoted_const_val("key")
                 .parse("key")
                 .into_result(),
-            Ok("key")
+            Ok("key".into())
         );
 
         assert_eq!(
             super::maybe_quoted_const_val("key")
                 .parse("\"key\"")
                 .into_result(),
-            Ok("key")
+            Ok("key".into())
         );
     }
 }
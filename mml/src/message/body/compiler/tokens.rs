@@ -1,11 +1,11 @@
 use log::debug;
 use mail_builder::headers::content_type::ContentType;
-use std::collections::HashMap;
+use std::{borrow::Cow, collections::HashMap};
 
 use super::TYPE;
 
 pub(crate) type Key<'a> = &'a str;
-pub(crate) type Val<'a> = &'a str;
+pub(crate) type Val<'a> = Cow<'a, str>;
 pub(crate) type Body<'a> = &'a str;
 pub(crate) type Prop<'a> = (Key<'a>, Val<'a>);
 pub(crate) type Props<'a> = HashMap<Key<'a>, Val<'a>>;
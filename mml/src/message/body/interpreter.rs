@@ -8,15 +8,17 @@
 use mail_builder::MessageBuilder;
 use mail_parser::{Message, MessageParser, MessagePart, MimeHeaders, PartType};
 use nanohtml2text::html2text;
-use std::{env, fs, path::PathBuf};
+use std::{env, fs::OpenOptions, io::Write, path::PathBuf, ptr};
 
 #[cfg(feature = "pgp")]
 use crate::pgp::Pgp;
 use crate::{Error, Result};
 
 use super::{
-    MULTIPART_BEGIN, MULTIPART_BEGIN_ESCAPED, MULTIPART_END, MULTIPART_END_ESCAPED, PART_BEGIN,
-    PART_BEGIN_ESCAPED, PART_END, PART_END_ESCAPED,
+    attachment::{AttachmentPolicy, AttachmentsReport},
+    BACKSLASH, CHARSET, DESCRIPTION, DOUBLE_QUOTE, GREATER_THAN, ID, MULTIPART_BEGIN,
+    MULTIPART_BEGIN_ESCAPED, MULTIPART_END, MULTIPART_END_ESCAPED, NAME, PART_BEGIN,
+    PART_BEGIN_ESCAPED, PART_END, PART_END_ESCAPED, TEXT_PLAIN,
 };
 
 /// Filters parts to show by MIME type.
@@ -105,6 +107,25 @@ pub struct MimeBodyInterpreter {
     /// default temporary one given by [`std::env::temp_dir()`].
     save_attachments_dir: PathBuf,
 
+    /// The policy applied to attachments before showing or saving
+    /// them.
+    ///
+    /// Filenames are always sanitized and never overwrite existing
+    /// files, see [`AttachmentPolicy`].
+    attachment_policy: AttachmentPolicy,
+
+    /// Interpret MIME parts as faithfully as possible when true, so
+    /// that compiling back the resulting MML gives the same MIME
+    /// body.
+    ///
+    /// The multipart structure is always shown (except for the
+    /// `multipart/mixed` root part the compiler generates itself),
+    /// all the alternatives of `multipart/alternative` parts are
+    /// kept, HTML parts are not converted to plain text and parts
+    /// descriptions, identifiers, names and charsets are kept as
+    /// properties. See [`MimeBodyInterpreter::with_round_trip`].
+    round_trip: bool,
+
     #[cfg(feature = "pgp")]
     pgp: Option<Pgp>,
     #[cfg(feature = "pgp")]
@@ -123,6 +144,8 @@ fn default() -> Self {
             show_inline_attachments: true,
             save_attachments: Default::default(),
             save_attachments_dir: Self::default_save_attachments_dir(),
+            attachment_policy: Default::default(),
+            round_trip: false,
             #[cfg(feature = "pgp")]
             pgp: Default::default(),
             #[cfg(feature = "pgp")]
@@ -177,6 +200,32 @@ pub fn with_save_attachments_dir(mut self, dir: impl Into<PathBuf>) -> Self {
         self
     }
 
+    pub fn with_attachment_policy(mut self, policy: AttachmentPolicy) -> Self {
+        self.attachment_policy = policy;
+        self
+    }
+
+    /// Interpret MIME parts so that compiling back the resulting MML
+    /// gives the same MIME body, following the builder pattern.
+    ///
+    /// The round trip is byte-equivalent (modulo multipart
+    /// boundaries) for MIME bodies made of:
+    ///
+    ///  - `multipart/mixed`, `multipart/alternative` and
+    ///    `multipart/related` parts,
+    ///  - UTF-8 text parts, HTML parts included,
+    ///  - attachments, as long as they are saved (see
+    ///    [`MimeBodyInterpreter::with_save_attachments`]),
+    ///  - Content-Description and Content-ID headers, as long as
+    ///    their value contains neither backslashes nor double quotes.
+    ///
+    /// Other headers are dropped, and text parts are always compiled
+    /// back as UTF-8.
+    pub fn with_round_trip(mut self, b: bool) -> Self {
+        self.round_trip = b;
+        self
+    }
+
     #[cfg(feature = "pgp")]
     pub fn set_pgp(&mut self, pgp: impl Into<Pgp>) {
         self.pgp = Some(pgp.into());
@@ -220,9 +269,48 @@ fn escape_mml_markup(text: String) -> String {
             .replace(MULTIPART_END, MULTIPART_END_ESCAPED)
     }
 
+    /// Interpret the given part headers as MML properties, starting
+    /// with a space.
+    ///
+    /// Headers are only interpreted in round trip mode, see
+    /// [`MimeBodyInterpreter::with_round_trip`].
+    fn interpret_props(&self, part: &MessagePart) -> String {
+        let mut props = String::new();
+
+        if !self.round_trip {
+            return props;
+        }
+
+        if let PartType::Binary(_) | PartType::InlineBinary(_) = part.body {
+            let ctype = part.content_type();
+
+            if let Some(name) = ctype.and_then(|ctype| ctype.attribute("name")) {
+                props.push_str(&format_prop(NAME, name));
+            }
+
+            if let Some(charset) = ctype.and_then(|ctype| ctype.attribute("charset")) {
+                props.push_str(&format_prop(CHARSET, charset));
+            }
+        }
+
+        if let Some(desc) = part.content_description() {
+            props.push_str(&format_prop(DESCRIPTION, desc));
+        }
+
+        if let Some(id) = part.content_id() {
+            props.push_str(&format_prop(ID, id));
+        }
+
+        props
+    }
+
     /// Decrypt the given [MessagePart] using PGP.
     #[cfg(feature = "pgp")]
-    async fn decrypt_part(&self, encrypted_part: &MessagePart<'_>) -> Result<String> {
+    async fn decrypt_part(
+        &self,
+        report: &mut AttachmentsReport,
+        encrypted_part: &MessagePart<'_>,
+    ) -> Result<String> {
         match &self.pgp {
             None => {
                 debug!("cannot decrypt part: pgp not configured");
@@ -238,7 +326,9 @@ async fn decrypt_part(&self, encrypted_part: &MessagePart<'_>) -> Result<String>
                 let clear_part = MessageParser::new()
                     .parse(&decrypted_part)
                     .ok_or(Error::ParsePgpDecryptedPartError)?;
-                let tpl = self.interpret_msg(&clear_part).await?;
+                let tpl = self
+                    .interpret_part(report, &clear_part, clear_part.root_part())
+                    .await?;
                 Ok(tpl)
             }
         }
@@ -272,21 +362,58 @@ async fn verify_msg(&self, msg: &Message<'_>, ids: &[usize]) -> Result<()> {
         Ok(())
     }
 
-    fn interpret_attachment(&self, ctype: &str, part: &MessagePart, data: &[u8]) -> Result<String> {
+    /// Apply the attachment policy to the given attachment, then
+    /// save it if enabled.
+    ///
+    /// Return the path of the attachment, or [`None`] if it has been
+    /// skipped by the policy.
+    fn save_attachment(
+        &self,
+        report: &mut AttachmentsReport,
+        ctype: &str,
+        fname: Option<&str>,
+        data: &[u8],
+    ) -> Result<Option<PathBuf>> {
+        let dir = &self.save_attachments_dir;
+        let policy = &self.attachment_policy;
+
+        let Some(fpath) = policy.apply(report, dir, fname, ctype, data, self.save_attachments)
+        else {
+            debug!("skipping attachment {fname:?} ({ctype}) according to policy");
+            return Ok(None);
+        };
+
+        // the file is created atomically, so that a file created
+        // after the policy checked the path is never overwritten
+        if self.save_attachments {
+            OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&fpath)
+                .and_then(|mut file| file.write_all(data))
+                .map_err(|err| Error::WriteAttachmentError(err, fpath.clone()))?;
+        }
+
+        Ok(Some(fpath))
+    }
+
+    fn interpret_attachment(
+        &self,
+        report: &mut AttachmentsReport,
+        ctype: &str,
+        part: &MessagePart,
+        data: &[u8],
+    ) -> Result<String> {
         let mut tpl = String::new();
 
         if self.show_attachments && self.filter_parts.contains(ctype) {
-            let fname = self
-                .save_attachments_dir
-                .join(part.attachment_name().unwrap_or("noname"));
+            let fname = part.attachment_name();
 
-            if self.save_attachments {
-                fs::write(&fname, data)
-                    .map_err(|err| Error::WriteAttachmentError(err, fname.clone()))?;
+            if let Some(fpath) = self.save_attachment(report, ctype, fname, data)? {
+                let fpath = fpath.to_string_lossy();
+                let props = self.interpret_props(part);
+                tpl = format!("<#part type={ctype} filename=\"{fpath}\"{props}><#/part>\n");
             }
-
-            let fname = fname.to_string_lossy();
-            tpl = format!("<#part type={ctype} filename=\"{fname}\"><#/part>\n");
         }
 
         Ok(tpl)
@@ -294,6 +421,7 @@ fn interpret_attachment(&self, ctype: &str, part: &MessagePart, data: &[u8]) ->
 
     fn interpret_inline_attachment(
         &self,
+        report: &mut AttachmentsReport,
         ctype: &str,
         part: &MessagePart,
         data: &[u8],
@@ -302,25 +430,21 @@ fn interpret_inline_attachment(
 
         if self.show_inline_attachments && self.filter_parts.contains(ctype) {
             let ctype = get_ctype(part);
-            let fname = self.save_attachments_dir.join(
-                part.attachment_name()
-                    .or(part.content_id())
-                    .unwrap_or("noname"),
-            );
-
-            if self.save_attachments {
-                fs::write(&fname, data)
-                    .map_err(|err| Error::WriteAttachmentError(err, fname.clone()))?;
+            let fname = part.attachment_name().or(part.content_id());
+
+            if let Some(fpath) = self.save_attachment(report, &ctype, fname, data)? {
+                let fpath = fpath.to_string_lossy();
+                let props = self.interpret_props(part);
+                tpl = format!(
+                    "<#part type={ctype} disposition=inline filename=\"{fpath}\"{props}><#/part>\n"
+                );
             }
-
-            let fname = fname.to_string_lossy();
-            tpl = format!("<#part type={ctype} disposition=inline filename=\"{fname}\"><#/part>\n");
         }
 
         Ok(tpl)
     }
 
-    fn interpret_text(&self, ctype: &str, text: &str) -> String {
+    fn interpret_text(&self, ctype: &str, props: &str, text: &str) -> String {
         let mut tpl = String::new();
 
         if self.filter_parts.contains(ctype) {
@@ -330,7 +454,7 @@ fn interpret_text(&self, ctype: &str, text: &str) -> String {
             if self.filter_parts.only(ctype) {
                 tpl.push_str(&text);
             } else {
-                tpl.push_str(&format!("<#part type={ctype}>\n"));
+                tpl.push_str(&format!("<#part type={ctype}{props}>\n"));
                 tpl.push_str(&text);
                 tpl.push_str("<#/part>\n");
             }
@@ -359,7 +483,7 @@ fn interpret_text_plain(&self, plain: &str) -> String {
         tpl
     }
 
-    fn interpret_text_html(&self, html: &str) -> String {
+    fn interpret_text_html(&self, props: &str, html: &str) -> String {
         let mut tpl = String::new();
 
         if self.filter_parts.contains("text/html") {
@@ -368,9 +492,13 @@ fn interpret_text_html(&self, html: &str) -> String {
                 let html = Self::escape_mml_markup(html);
                 tpl.push_str(&html);
             } else {
-                let html = html2text(html);
+                let html = if self.round_trip {
+                    html.replace('\r', "")
+                } else {
+                    html2text(html)
+                };
                 let html = Self::escape_mml_markup(html);
-                tpl.push_str("<#part type=text/html>\n");
+                tpl.push_str(&format!("<#part type=text/html{props}>\n"));
                 tpl.push_str(&html);
                 tpl.push_str("<#/part>\n");
             }
@@ -380,30 +508,47 @@ fn interpret_text_html(&self, html: &str) -> String {
     }
 
     #[async_recursion]
-    async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Result<String> {
+    async fn interpret_part(
+        &self,
+        report: &mut AttachmentsReport,
+        msg: &Message<'_>,
+        part: &MessagePart<'_>,
+    ) -> Result<String> {
         let mut tpl = String::new();
         let ctype = get_ctype(part);
 
         match &part.body {
             PartType::Text(plain) if ctype == "text/plain" => {
-                tpl.push_str(&self.interpret_text_plain(plain));
+                let props = self.interpret_props(part);
+
+                // in round trip mode, empty plain text parts and
+                // plain text parts carrying properties cannot be
+                // represented as raw text
+                if props.is_empty() && !(self.round_trip && plain.is_empty()) {
+                    tpl.push_str(&self.interpret_text_plain(plain));
+                } else {
+                    tpl.push_str(&self.interpret_text(&ctype, &props, plain));
+                }
             }
             PartType::Text(text) => {
-                tpl.push_str(&self.interpret_text(&ctype, text));
+                let props = self.interpret_props(part);
+                tpl.push_str(&self.interpret_text(&ctype, &props, text));
             }
             PartType::Html(html) => {
-                tpl.push_str(&self.interpret_text_html(html));
+                let props = self.interpret_props(part);
+                tpl.push_str(&self.interpret_text_html(&props, html));
             }
             PartType::Binary(data) => {
-                tpl.push_str(&self.interpret_attachment(&ctype, part, data)?);
+                tpl.push_str(&self.interpret_attachment(report, &ctype, part, data)?);
             }
             PartType::InlineBinary(data) => {
-                tpl.push_str(&self.interpret_inline_attachment(&ctype, part, data)?);
+                tpl.push_str(&self.interpret_inline_attachment(report, &ctype, part, data)?);
             }
             PartType::Message(msg) => {
-                tpl.push_str(&self.interpret_msg(msg).await?);
+                let part = msg.root_part();
+                tpl.push_str(&self.interpret_part(report, msg, part).await?);
             }
-            PartType::Multipart(ids) if ctype == "multipart/alternative" => {
+            PartType::Multipart(ids) if ctype == "multipart/alternative" && !self.round_trip => {
                 let mut parts = ids.iter().filter_map(|id| msg.part(*id));
 
                 let part = match &self.filter_parts {
@@ -421,7 +566,7 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
                             .or_else(|| {
                                 parts.clone().find_map(|part| match &part.body {
                                     PartType::Html(html) if !html.trim().is_empty() => {
-                                        Some(Ok(self.interpret_text_html(html)))
+                                        Some(Ok(self.interpret_text_html("", html)))
                                     }
                                     _ => None,
                                 })
@@ -431,7 +576,7 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
                                     let ctype = get_ctype(part);
                                     match &part.body {
                                         PartType::Text(text) if !text.trim().is_empty() => {
-                                            Some(Ok(self.interpret_text(&ctype, text)))
+                                            Some(Ok(self.interpret_text(&ctype, "", text)))
                                         }
                                         _ => None,
                                     }
@@ -441,20 +586,20 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
                         match part {
                             Some(part) => Some(part),
                             None => match parts.next() {
-                                Some(part) => Some(self.interpret_part(msg, part).await),
+                                Some(part) => Some(self.interpret_part(report, msg, part).await),
                                 None => None,
                             },
                         }
                     }
                     FilterParts::Only(ctype) => {
                         match parts.clone().find(|part| &get_ctype(part) == ctype) {
-                            Some(part) => Some(self.interpret_part(msg, part).await),
+                            Some(part) => Some(self.interpret_part(report, msg, part).await),
                             None => None,
                         }
                     }
                     FilterParts::Include(ctypes) => {
                         match parts.clone().find(|part| ctypes.contains(&get_ctype(part))) {
-                            Some(part) => Some(self.interpret_part(msg, part).await),
+                            Some(part) => Some(self.interpret_part(report, msg, part).await),
                             None => None,
                         }
                     }
@@ -463,7 +608,7 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
                             .clone()
                             .find(|part| !ctypes.contains(&get_ctype(part)))
                         {
-                            Some(part) => Some(self.interpret_part(msg, part).await),
+                            Some(part) => Some(self.interpret_part(report, msg, part).await),
                             None => None,
                         }
                     }
@@ -475,7 +620,7 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
             }
             #[cfg(feature = "pgp")]
             PartType::Multipart(ids) if ctype == "multipart/encrypted" => {
-                match self.decrypt_part(msg.part(ids[1]).unwrap()).await {
+                match self.decrypt_part(report, msg.part(ids[1]).unwrap()).await {
                     Ok(ref clear_part) => tpl.push_str(clear_part),
                     Err(err) => {
                         debug!("cannot decrypt email part using pgp: {err}");
@@ -496,7 +641,7 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
                 }
 
                 let signed_part = msg.part(ids[0]).unwrap();
-                let clear_part = &self.interpret_part(msg, signed_part).await?;
+                let clear_part = &self.interpret_part(report, msg, signed_part).await?;
                 tpl.push_str(clear_part);
             }
             PartType::Multipart(_) if ctype == "application/pgp-encrypted" => {
@@ -506,23 +651,50 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
                 // nothing to do, signature already verified above
             }
             PartType::Multipart(ids) => {
-                if self.show_multiparts {
+                let props = self.interpret_props(part);
+
+                let show_multipart = if self.round_trip {
+                    // the compiler wraps multiple root parts into a
+                    // multipart/mixed part by itself
+                    let is_root = ptr::eq(part, msg.root_part());
+                    !(is_root && ctype == "multipart/mixed" && ids.len() > 1 && props.is_empty())
+                } else {
+                    self.show_multiparts
+                };
+
+                if show_multipart {
                     let stype = part
                         .content_type()
                         .and_then(|p| p.subtype())
                         .unwrap_or("mixed");
-                    tpl.push_str(&format!("<#multipart type={stype}>\n"));
+                    tpl.push_str(&format!("<#multipart type={stype}{props}>\n"));
                 }
 
+                let mut prev_is_plain = false;
+
                 for id in ids {
                     if let Some(part) = msg.part(*id) {
-                        tpl.push_str(&self.interpret_part(msg, part).await?);
+                        match &part.body {
+                            // in round trip mode, adjacent plain text
+                            // parts would be merged if represented as
+                            // raw text
+                            PartType::Text(plain)
+                                if self.round_trip && prev_is_plain && is_plain(part) =>
+                            {
+                                let props = self.interpret_props(part);
+                                tpl.push_str(&self.interpret_text(TEXT_PLAIN, &props, plain));
+                            }
+                            _ => {
+                                tpl.push_str(&self.interpret_part(report, msg, part).await?);
+                            }
+                        }
+                        prev_is_plain = is_plain(part);
                     } else {
                         debug!("cannot find part {id}, skipping it");
                     }
                 }
 
-                if self.show_multiparts {
+                if show_multipart {
                     tpl.push_str("<#/multipart>\n");
                 }
             }
@@ -533,7 +705,22 @@ async fn interpret_part(&self, msg: &Message<'_>, part: &MessagePart<'_>) -> Res
 
     /// Interpret the given MIME [Message] as a MML message string.
     pub async fn interpret_msg<'a>(&self, msg: &Message<'a>) -> Result<String> {
-        self.interpret_part(msg, msg.root_part()).await
+        let (tpl, _report) = self.interpret_msg_with_report(msg).await?;
+        Ok(tpl)
+    }
+
+    /// Interpret the given MIME [Message] as a MML message string,
+    /// along with the report of the attachments accepted and skipped
+    /// by the attachment policy.
+    pub async fn interpret_msg_with_report<'a>(
+        &self,
+        msg: &Message<'a>,
+    ) -> Result<(String, AttachmentsReport)> {
+        let mut report = AttachmentsReport::default();
+        let tpl = self
+            .interpret_part(&mut report, msg, msg.root_part())
+            .await?;
+        Ok((tpl, report))
     }
 
     /// Interpret the given MIME message bytes as a MML message
@@ -545,6 +732,20 @@ pub async fn interpret_bytes<'a>(&self, bytes: impl AsRef<[u8]> + 'a) -> Result<
         self.interpret_msg(&msg).await
     }
 
+    /// Interpret the given MIME message bytes as a MML message
+    /// string, along with the attachments report.
+    ///
+    /// See [`MimeBodyInterpreter::interpret_msg_with_report`].
+    pub async fn interpret_bytes_with_report<'a>(
+        &self,
+        bytes: impl AsRef<[u8]> + 'a,
+    ) -> Result<(String, AttachmentsReport)> {
+        let msg = MessageParser::new()
+            .parse(bytes.as_ref())
+            .ok_or(Error::ParseMimeMessageError)?;
+        self.interpret_msg_with_report(&msg).await
+    }
+
     /// Interpret the given MIME [MessageBuilder] as a MML message
     /// string.
     pub async fn interpret_msg_builder<'a>(&self, builder: MessageBuilder<'a>) -> Result<String> {
@@ -567,10 +768,30 @@ fn is_plain(part: &MessagePart) -> bool {
     get_ctype(part) == "text/plain"
 }
 
+/// Format the given MML property, starting with a space.
+///
+/// The value is quoted if it contains spaces, greater-than, double
+/// quote or backslash characters. Double quotes and backslashes are
+/// escaped with a backslash, so that the value cannot end the
+/// property nor the tag it belongs to.
+fn format_prop(key: &str, val: &str) -> String {
+    let must_quote =
+        |c: char| c.is_whitespace() || matches!(c, GREATER_THAN | DOUBLE_QUOTE | BACKSLASH);
+
+    if val.is_empty() || val.contains(must_quote) {
+        let val = val.replace(BACKSLASH, "\\\\").replace(DOUBLE_QUOTE, "\\\"");
+        format!(" {key}=\"{val}\"")
+    } else {
+        format!(" {key}={val}")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use concat_with::concat_line;
-    use mail_builder::{mime::MimePart, MessageBuilder};
+    use mail_builder::{headers::text::Text, mime::MimePart, MessageBuilder};
+
+    use crate::message::{AttachmentPolicy, SkipReason};
 
     use super::{FilterParts, MimeBodyInterpreter};
 
@@ -882,6 +1103,37 @@ async fn multipart_alternative_text_html_only() {
         assert_eq!(tpl, expected_tpl);
     }
 
+    #[tokio::test]
+    async fn round_trip() {
+        let builder = MessageBuilder::new().body(MimePart::new(
+            "multipart/alternative",
+            vec![
+                MimePart::new("text/plain", "This is a plain text part.\n"),
+                MimePart::new("text/html", "<h1>This is a HTML text part.</h1>\n")
+                    .header("Content-Description", Text::new("HTML version"))
+                    .cid("html@localhost"),
+            ],
+        ));
+
+        let tpl = MimeBodyInterpreter::new()
+            .with_round_trip(true)
+            .interpret_msg_builder(builder.clone())
+            .await
+            .unwrap();
+
+        let expected_tpl = concat_line!(
+            "<#multipart type=alternative>",
+            "This is a plain text part.",
+            "<#part type=text/html description=\"HTML version\" id=html@localhost>",
+            "<h1>This is a HTML text part.</h1>",
+            "<#/part>",
+            "<#/multipart>",
+            "",
+        );
+
+        assert_eq!(tpl, expected_tpl);
+    }
+
     #[tokio::test]
     async fn attachment() {
         let builder = MessageBuilder::new().attachment(
@@ -903,4 +1155,65 @@ async fn attachment() {
 
         assert_eq!(tpl, expected_tpl);
     }
+
+    #[tokio::test]
+    async fn attachment_hostile_filename() {
+        let builder = MessageBuilder::new().attachment(
+            "application/octet-stream",
+            "../../.bashrc",
+            "Hello, world!".as_bytes(),
+        );
+
+        let tpl = MimeBodyInterpreter::new()
+            .with_save_attachments_dir("~/Downloads")
+            .interpret_msg_builder(builder)
+            .await
+            .unwrap();
+
+        let expected_tpl = concat_line!(
+            "<#part type=application/octet-stream filename=\"~/Downloads/bashrc\"><#/part>",
+            "",
+        );
+
+        assert_eq!(tpl, expected_tpl);
+    }
+
+    #[tokio::test]
+    async fn attachment_skipped() {
+        let builder = MessageBuilder::new()
+            .text_body("Hello, world!\n")
+            .attachment("application/octet-stream", "small.txt", "Hello!".as_bytes())
+            .attachment(
+                "application/octet-stream",
+                "big.txt",
+                "Hello, world!".as_bytes(),
+            );
+
+        let bytes = builder.write_to_vec().unwrap();
+
+        let (tpl, report) = MimeBodyInterpreter::new()
+            .with_save_attachments_dir("~/Downloads")
+            .with_attachment_policy(AttachmentPolicy {
+                max_file_size: Some(10),
+                ..Default::default()
+            })
+            .interpret_bytes_with_report(&bytes)
+            .await
+            .unwrap();
+
+        let expected_tpl = concat_line!(
+            "Hello, world!",
+            "<#part type=application/octet-stream filename=\"~/Downloads/small.txt\"><#/part>",
+            "",
+        );
+
+        assert_eq!(tpl, expected_tpl);
+        assert_eq!(report.accepted.len(), 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].filename.as_deref(), Some("big.txt"));
+        assert_eq!(
+            report.skipped[0].reason,
+            SkipReason::FileTooLarge { size: 13, max: 10 },
+        );
+    }
 }
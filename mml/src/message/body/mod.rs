@@ -6,6 +6,8 @@
 
 #![allow(dead_code)]
 
+#[cfg(feature = "interpreter")]
+pub mod attachment;
 #[cfg(feature = "compiler")]
 pub mod compiler;
 #[cfg(feature = "interpreter")]
@@ -16,7 +18,12 @@
 pub use self::compiler::MmlBodyCompiler;
 #[cfg(feature = "interpreter")]
 #[doc(inline)]
-pub use self::interpreter::{FilterParts, MimeBodyInterpreter};
+pub use self::{
+    attachment::{
+        AttachmentPolicy, AttachmentsReport, MimeTypesFilter, SkipReason, SkippedAttachment,
+    },
+    interpreter::{FilterParts, MimeBodyInterpreter},
+};
 
 pub(crate) const PART_BEGIN: &str = "<#part";
 pub(crate) const PART_BEGIN_ESCAPED: &str = "<#!part";
@@ -43,6 +50,7 @@
 #[cfg(feature = "pgp")]
 pub(crate) const ENCRYPT: &str = "encrypt";
 pub(crate) const FILENAME: &str = "filename";
+pub(crate) const ID: &str = "id";
 pub(crate) const INLINE: &str = "inline";
 pub(crate) const MIXED: &str = "mixed";
 pub(crate) const MODIFICATION_DATE: &str = "modification-date";
@@ -59,6 +67,10 @@
 #[cfg(feature = "pgp")]
 pub(crate) const SIGN: &str = "sign";
 pub(crate) const SIZE: &str = "size";
+pub(crate) const TEXT_HTML: &str = "text/html";
+#[cfg(feature = "markdown")]
+pub(crate) const TEXT_MARKDOWN: &str = "text/markdown";
+pub(crate) const TEXT_PLAIN: &str = "text/plain";
 pub(crate) const TYPE: &str = "type";
 
 pub(crate) const BACKSLASH: char = '\\';
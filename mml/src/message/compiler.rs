@@ -4,6 +4,7 @@
 
 use mail_builder::{headers::text::Text, MessageBuilder};
 use mail_parser::{Message, MessageParser};
+use std::path::PathBuf;
 
 #[cfg(feature = "pgp")]
 use crate::{message::header, pgp::Pgp};
@@ -25,6 +26,53 @@ pub fn new() -> Self {
         Self::default()
     }
 
+    /// Compose HTML parts.
+    ///
+    /// See [`MmlBodyCompiler::with_compose_html`].
+    pub fn set_compose_html(&mut self, b: bool) {
+        self.mml_body_compiler.set_compose_html(b);
+    }
+
+    /// Compose HTML parts.
+    ///
+    /// See [`MmlBodyCompiler::with_compose_html`].
+    pub fn with_compose_html(mut self, b: bool) -> Self {
+        self.mml_body_compiler.set_compose_html(b);
+        self
+    }
+
+    /// Escape raw HTML of markdown parts.
+    ///
+    /// See [`MmlBodyCompiler::with_escape_markdown_html`].
+    #[cfg(feature = "markdown")]
+    pub fn set_escape_markdown_html(&mut self, b: bool) {
+        self.mml_body_compiler.set_escape_markdown_html(b);
+    }
+
+    /// Escape raw HTML of markdown parts.
+    ///
+    /// See [`MmlBodyCompiler::with_escape_markdown_html`].
+    #[cfg(feature = "markdown")]
+    pub fn with_escape_markdown_html(mut self, b: bool) -> Self {
+        self.mml_body_compiler.set_escape_markdown_html(b);
+        self
+    }
+
+    /// Embed local images of composed parts from the given directory.
+    ///
+    /// See [`MmlBodyCompiler::with_inline_images_dir`].
+    pub fn set_inline_images_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.mml_body_compiler.set_inline_images_dir(dir);
+    }
+
+    /// Embed local images of composed parts from the given directory.
+    ///
+    /// See [`MmlBodyCompiler::with_inline_images_dir`].
+    pub fn with_inline_images_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.mml_body_compiler.set_inline_images_dir(dir);
+        self
+    }
+
     /// Customize PGP.
     #[cfg(feature = "pgp")]
     pub fn set_pgp(&mut self, pgp: impl Into<Pgp>) {
@@ -9,7 +9,7 @@
 #[cfg(feature = "pgp")]
 use crate::pgp::Pgp;
 use crate::{
-    message::{FilterParts, MimeBodyInterpreter},
+    message::{AttachmentPolicy, AttachmentsReport, FilterParts, MimeBodyInterpreter},
     Error, Result,
 };
 
@@ -182,6 +182,24 @@ pub fn with_save_some_attachments_dir(self, dir: Option<impl Into<PathBuf>>) ->
         }
     }
 
+    /// Customize the policy applied to attachments.
+    ///
+    /// Filenames are always sanitized and never overwrite existing
+    /// files, see [`AttachmentPolicy`].
+    pub fn with_attachment_policy(mut self, policy: AttachmentPolicy) -> Self {
+        self.mime_body_interpreter = self.mime_body_interpreter.with_attachment_policy(policy);
+        self
+    }
+
+    /// Interpret the MIME body so that compiling back the resulting
+    /// MML gives the same MIME body.
+    ///
+    /// See [`MimeBodyInterpreter::with_round_trip`].
+    pub fn with_round_trip(mut self, b: bool) -> Self {
+        self.mime_body_interpreter = self.mime_body_interpreter.with_round_trip(b);
+        self
+    }
+
     /// Customize PGP.
     #[cfg(feature = "pgp")]
     pub fn set_pgp(&mut self, pgp: impl Into<Pgp>) {
@@ -230,6 +248,17 @@ pub struct MimeInterpreter {
 impl MimeInterpreter {
     /// Interpret the given MIME [Message] as a MML [String].
     pub async fn from_msg(self, msg: &Message<'_>) -> Result<String> {
+        let (mml, _report) = self.from_msg_with_report(msg).await?;
+        Ok(mml)
+    }
+
+    /// Interpret the given MIME [Message] as a MML [String], along
+    /// with the report of the attachments accepted and skipped by the
+    /// attachment policy.
+    pub async fn from_msg_with_report(
+        self,
+        msg: &Message<'_>,
+    ) -> Result<(String, AttachmentsReport)> {
         let mut mml = String::new();
 
         match self.show_headers {
@@ -267,11 +296,11 @@ pub async fn from_msg(self, msg: &Message<'_>) -> Result<String> {
             .with_pgp_sender(header::extract_first_email(msg.from()))
             .with_pgp_recipient(header::extract_first_email(msg.to()));
 
-        let mml_body = mime_body_interpreter.interpret_msg(msg).await?;
+        let (mml_body, report) = mime_body_interpreter.interpret_msg_with_report(msg).await?;
 
         mml.push_str(&mml_body);
 
-        Ok(mml)
+        Ok((mml, report))
     }
 
     /// Interpret the given MIME message bytes as a MML [String].
@@ -282,6 +311,20 @@ pub async fn from_bytes(self, bytes: impl AsRef<[u8]>) -> Result<String> {
         self.from_msg(&msg).await
     }
 
+    /// Interpret the given MIME message bytes as a MML [String],
+    /// along with the attachments report.
+    ///
+    /// See [`MimeInterpreter::from_msg_with_report`].
+    pub async fn from_bytes_with_report(
+        self,
+        bytes: impl AsRef<[u8]>,
+    ) -> Result<(String, AttachmentsReport)> {
+        let msg = MessageParser::new()
+            .parse(bytes.as_ref())
+            .ok_or(Error::ParseRawEmailError)?;
+        self.from_msg_with_report(&msg).await
+    }
+
     /// Interpret the given MIME [MessageBuilder] as a MML [String].
     pub async fn from_msg_builder(self, builder: MessageBuilder<'_>) -> Result<String> {
         let bytes = builder.write_to_vec().map_err(Error::BuildEmailError)?;
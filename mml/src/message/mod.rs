@@ -28,6 +28,9 @@
 #[cfg(feature = "interpreter")]
 #[doc(inline)]
 pub use self::{
-    body::{FilterParts, MimeBodyInterpreter},
+    body::{
+        AttachmentPolicy, AttachmentsReport, FilterParts, MimeBodyInterpreter, MimeTypesFilter,
+        SkipReason, SkippedAttachment,
+    },
     interpreter::{FilterHeaders, MimeInterpreter, MimeInterpreterBuilder},
 };
@@ -2,7 +2,7 @@
 //!
 //! This module contains the PGP backend based on shell commands.
 
-use process::Command;
+use process::{Command, SingleCommand, TemplateVars};
 
 use crate::{Error, Result};
 
@@ -16,18 +16,24 @@
 pub struct CmdsPgp {
     /// The PGP encrypt command.
     ///
-    /// A special placeholder `<recipients>` is available to represent
-    /// the recipients the message needs to be encrypted for. See [CmdsPgp::default_encrypt_cmd].
+    /// A special placeholder `{recipients}` is available to represent
+    /// the recipients the message needs to be encrypted for, as
+    /// formatted by [CmdsPgp::encrypt_recipient_fmt]. See
+    /// [CmdsPgp::default_encrypt_cmd]. The legacy `<recipients>`
+    /// placeholder is still accepted.
     ///
-    /// Defaults to `gpg --encrypt --quiet --armor <recipients>`.
+    /// Defaults to `gpg --encrypt --quiet --armor {recipients}`.
     pub encrypt_cmd: Option<Command>,
 
     /// The PGP encrypt recipient format.
     ///
-    /// A special placeholder `<recipient>` is available to represent
-    /// one recipient of the encrypt command.
+    /// A special placeholder `{recipient}` is available to represent
+    /// one recipient of the encrypt command. The format is rendered
+    /// as a command template (see [`process::TemplateVars`]), which
+    /// quotes the recipient so that it cannot inject shell code. The
+    /// legacy `<recipient>` placeholder is still accepted.
     ///
-    /// Default to `--recipient <recipient>`.
+    /// Default to `--recipient {recipient}`.
     pub encrypt_recipient_fmt: Option<String>,
 
     /// The PGP encrypt recipients separator.
@@ -55,11 +61,11 @@ pub struct CmdsPgp {
 
 impl CmdsPgp {
     pub fn default_encrypt_cmd() -> Command {
-        Command::from("gpg --encrypt --quiet --armor <recipients>")
+        Command::from("gpg --encrypt --quiet --armor {recipients}")
     }
 
     pub fn default_encrypt_recipient_fmt() -> String {
-        String::from("--recipient <recipient>")
+        String::from("--recipient {recipient}")
     }
 
     pub fn default_encrypt_recipients_sep() -> String {
@@ -87,27 +93,34 @@ pub async fn encrypt(
         let recipient_fmt = self
             .encrypt_recipient_fmt
             .clone()
-            .unwrap_or_else(Self::default_encrypt_recipient_fmt);
+            .unwrap_or_else(Self::default_encrypt_recipient_fmt)
+            .replace("<recipient>", "{recipient}");
         let recipients_sep = self
             .encrypt_recipients_sep
             .clone()
             .unwrap_or_else(Self::default_encrypt_recipients_sep);
-        let recipients_str =
-            recipients
-                .into_iter()
-                .fold(String::new(), |mut recipients_str, recipient| {
-                    if !recipients_str.is_empty() {
-                        recipients_str.push_str(&recipients_sep);
-                    }
-                    recipients_str.push_str(&recipient_fmt.replace("<recipient>", &recipient));
-                    recipients_str
-                });
-
+        let mut recipients_str = String::new();
+
+        for recipient in recipients {
+            let vars = TemplateVars::new().with_var("recipient", recipient);
+            let recipient = SingleCommand::from(recipient_fmt.as_str())
+                .render(&vars)
+                .map_err(Error::EncryptCommandError)?;
+
+            if !recipients_str.is_empty() {
+                recipients_str.push_str(&recipients_sep);
+            }
+            recipients_str.push_str(&recipient);
+        }
+
+        // recipients are quoted already, so the formatted recipients
+        // are inserted as they are
         let res = self
             .encrypt_cmd
             .clone()
             .unwrap_or_else(Self::default_encrypt_cmd)
-            .replace("<recipients>", recipients_str)
+            .replace_placeholder("<recipients>", &recipients_str)
+            .replace_placeholder("{recipients}", recipients_str)
             .run_with(plain_bytes)
             .await
             .map_err(Error::EncryptCommandError)?;
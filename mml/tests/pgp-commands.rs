@@ -57,3 +57,26 @@ async fn pgp_cmds() {
 
     assert_eq!(mml, expected_mml);
 }
+
+#[tokio::test]
+async fn pgp_cmds_with_template_placeholders() {
+    let pgp = CmdsPgp {
+        encrypt_cmd: Some(Command::from(
+            "gpg --homedir ./tests/gpg-home -eqa {recipients}",
+        )),
+        encrypt_recipient_fmt: Some(String::from("-r {recipient}")),
+        decrypt_cmd: Some(Command::from("gpg --homedir ./tests/gpg-home -dq")),
+        ..CmdsPgp::default()
+    };
+
+    let encrypted = pgp
+        .encrypt(
+            [String::from("bob@localhost")],
+            b"Encrypted message!".to_vec(),
+        )
+        .await
+        .unwrap();
+    let decrypted = pgp.decrypt(encrypted).await.unwrap();
+
+    assert_eq!(decrypted, b"Encrypted message!");
+}
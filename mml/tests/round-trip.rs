@@ -0,0 +1,209 @@
+#![cfg(all(feature = "compiler", feature = "interpreter"))]
+
+use mml::message::body::{MimeBodyInterpreter, MmlBodyCompiler};
+use proptest::{collection::vec, option, prelude::*};
+use tokio::runtime::Runtime;
+
+fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9][a-zA-Z0-9,.!? ]{0,30}(\n[a-zA-Z0-9,.!? ]{0,30}){0,3}\n?"
+}
+
+/// Generate quoted MML property values, with escaped double quotes
+/// and backslashes.
+fn quoted_val() -> impl Strategy<Value = String> {
+    r##""([a-zA-Z0-9<>#/=']|\\\\|\\"){1,10}( ([a-zA-Z0-9<>#/=']|\\\\|\\"){1,10}){0,2}""##
+}
+
+fn props() -> impl Strategy<Value = String> {
+    (
+        option::of(quoted_val()),
+        option::of("[a-z0-9]{1,8}@[a-z]{1,8}\\.test"),
+    )
+        .prop_map(|(desc, id)| {
+            let mut props = String::new();
+            if let Some(desc) = desc {
+                props.push_str(&format!(" description={desc}"));
+            }
+            if let Some(id) = id {
+                props.push_str(&format!(" id={id}"));
+            }
+            props
+        })
+}
+
+fn plain_part() -> impl Strategy<Value = String> {
+    prop_oneof![
+        text(),
+        (props(), text())
+            .prop_map(|(props, text)| format!("<#part type=text/plain{props}>\n{text}<#/part>\n")),
+    ]
+}
+
+fn html_part() -> impl Strategy<Value = String> {
+    (props(), text()).prop_map(|(props, text)| {
+        format!("<#part type=text/html{props}>\n<p>{text}</p>\n<#/part>\n")
+    })
+}
+
+/// Generate alternatives made of plain text and HTML parts, with at
+/// least one HTML part.
+///
+/// Alternatives without HTML part are not generated: mail-parser
+/// 0.9.4 panics when such an alternative follows alternatives having
+/// more HTML parts than the message has plain text parts (the "found
+/// text part only" branch of `MessageParser::parse` slices the text
+/// parts with the HTML parts index). See the ignored
+/// `alternative_without_html_part` test.
+fn alternative_part() -> impl Strategy<Value = String> {
+    let parts = || vec(prop_oneof![plain_part(), html_part()], 0..=1);
+
+    (props(), parts(), html_part(), parts()).prop_map(|(props, before, html, after)| {
+        let parts = [before.concat(), html, after.concat()].concat();
+        format!("<#multipart type=alternative{props}>\n{parts}<#/multipart>\n")
+    })
+}
+
+/// Generate MML bodies belonging to the round trip subset, see
+/// [`MimeBodyInterpreter::with_round_trip`].
+fn mml_body() -> impl Strategy<Value = String> {
+    let leaf = prop_oneof![plain_part(), html_part(), alternative_part()];
+
+    let node = leaf.prop_recursive(2, 16, 3, |node| {
+        (
+            prop_oneof![Just("mixed"), Just("related")],
+            props(),
+            vec(node, 1..=3),
+        )
+            .prop_map(|(stype, props, parts)| {
+                let parts = parts.concat();
+                format!("<#multipart type={stype}{props}>\n{parts}<#/multipart>\n")
+            })
+    });
+
+    vec(node, 1..=3).prop_map(|nodes| nodes.concat())
+}
+
+/// Replace randomly generated multipart boundaries by predictable
+/// ones.
+fn normalize_boundaries(mime: Vec<u8>) -> String {
+    let mut mime = String::from_utf8(mime).unwrap();
+
+    let boundaries: Vec<String> = mime
+        .match_indices("boundary=\"")
+        .map(|(i, prefix)| {
+            let start = i + prefix.len();
+            let end = start + mime[start..].find('"').unwrap();
+            mime[start..end].to_owned()
+        })
+        .collect();
+
+    for (n, boundary) in boundaries.iter().enumerate() {
+        mime = mime.replace(boundary, &format!("boundary-{n}"));
+    }
+
+    mime
+}
+
+async fn compile(mml: &str) -> Vec<u8> {
+    MmlBodyCompiler::new()
+        .compile(mml)
+        .await
+        .unwrap()
+        .message_id("id@localhost")
+        .date(0_u64)
+        .write_to_vec()
+        .unwrap()
+}
+
+async fn round_trip(mml: &str) -> (Vec<u8>, String, Vec<u8>) {
+    let mime = compile(mml).await;
+
+    let interpreted_mml = MimeBodyInterpreter::new()
+        .with_round_trip(true)
+        .interpret_bytes(&mime)
+        .await
+        .unwrap();
+
+    let compiled_mime = compile(&interpreted_mml).await;
+
+    (mime, interpreted_mml, compiled_mime)
+}
+
+#[tokio::test]
+async fn escaped_markup_in_part() {
+    let mml = concat!(
+        "<#multipart type=mixed>\n",
+        "<#part type=text/plain>\n",
+        "<#!part type=text/html>Hello<#!/part>\n",
+        "<#/part>\n",
+        "<#part type=text/plain>\n",
+        "World\n",
+        "<#/part>\n",
+        "<#/multipart>\n",
+    );
+
+    let (mime, interpreted_mml, compiled_mime) = round_trip(mml).await;
+
+    assert!(String::from_utf8_lossy(&mime).contains("<#part type=text/html>Hello<#/part>"));
+    assert!(interpreted_mml.contains("<#!part type=text/html>Hello<#!/part>"));
+    assert_eq!(
+        normalize_boundaries(compiled_mime),
+        normalize_boundaries(mime),
+        "interpreted MML:\n{interpreted_mml}",
+    );
+}
+
+/// Reproduce the mail-parser 0.9.4 panic which prevents
+/// [`alternative_part`] from generating alternatives without HTML
+/// part. Remove the restriction from the generator once this test
+/// passes.
+#[tokio::test]
+#[ignore = "mail-parser 0.9.4 panics on alternatives without HTML part"]
+async fn alternative_without_html_part() {
+    let mml = concat!(
+        "<#multipart type=mixed>\n",
+        "<#multipart type=alternative>\n",
+        "<#part type=text/plain>\n",
+        "Hello\n",
+        "<#/part>\n",
+        "<#part type=text/html>\n",
+        "<p>Hello</p>\n",
+        "<#/part>\n",
+        "<#part type=text/html>\n",
+        "<p>Hello</p>\n",
+        "<#/part>\n",
+        "<#part type=text/html>\n",
+        "<p>Hello</p>\n",
+        "<#/part>\n",
+        "<#/multipart>\n",
+        "<#multipart type=alternative>\n",
+        "<#part type=text/plain>\n",
+        "World\n",
+        "<#/part>\n",
+        "<#/multipart>\n",
+        "<#/multipart>\n",
+    );
+
+    let (mime, interpreted_mml, compiled_mime) = round_trip(mml).await;
+
+    assert_eq!(
+        normalize_boundaries(compiled_mime),
+        normalize_boundaries(mime),
+        "interpreted MML:\n{interpreted_mml}",
+    );
+}
+
+proptest! {
+    #[test]
+    fn interpret_then_compile(mml in mml_body()) {
+        Runtime::new().unwrap().block_on(async {
+            let (mime, interpreted_mml, compiled_mime) = round_trip(&mml).await;
+
+            assert_eq!(
+                normalize_boundaries(compiled_mime),
+                normalize_boundaries(mime),
+                "interpreted MML:\n{interpreted_mml}",
+            );
+        });
+    }
+}
@@ -0,0 +1,36 @@
+use oauth::v2_0::{Client, DeviceAuthorizationGrant};
+use std::env;
+
+#[tokio::main]
+pub async fn main() {
+    let client_id = env::var("CLIENT_ID").expect("Missing the CLIENT_ID environment variable");
+    let auth_url = "https://accounts.google.com/o/oauth2/v2/auth";
+    let token_url = "https://oauth2.googleapis.com/token";
+
+    // public clients have no client secret
+    let client = match env::var("CLIENT_SECRET") {
+        Ok(client_secret) => Client::new(client_id, client_secret, auth_url, token_url),
+        Err(_) => Client::new_public(client_id, auth_url, token_url),
+    }
+    .unwrap()
+    .with_device_authorization_url("https://oauth2.googleapis.com/device/code")
+    .unwrap()
+    .build()
+    .unwrap();
+
+    let device_grant = DeviceAuthorizationGrant::new().with_scope("https://mail.google.com/");
+
+    let device_code = device_grant.get_device_code(&client).await.unwrap();
+
+    println!("Go to: {}", device_code.verification_uri().as_str());
+    println!("Enter the code: {}", device_code.user_code().secret());
+
+    let tokens = device_grant
+        .wait_for_access_token(&client, &device_code)
+        .await
+        .unwrap();
+
+    println!("access token: {:?}", tokens.access_token);
+    println!("refresh token: {:?}", tokens.refresh_token);
+    println!("expires in: {:?}", tokens.expires_in);
+}
@@ -36,21 +36,23 @@ pub async fn main() {
 
     println!("Go to: {}", redirect_url);
 
-    let (access_token, refresh_token) = auth_code_grant
+    let tokens = auth_code_grant
         .wait_for_redirection(&client, csrf_token)
         .await
         .unwrap();
 
-    println!("access token: {:?}", access_token);
-    println!("refresh token: {:?}", refresh_token);
+    println!("access token: {:?}", tokens.access_token);
+    println!("refresh token: {:?}", tokens.refresh_token);
+    println!("expires in: {:?}", tokens.expires_in);
 
-    if let Some(refresh_token) = refresh_token {
-        let (access_token, refresh_token) = RefreshAccessToken::new()
+    if let Some(refresh_token) = tokens.refresh_token {
+        let tokens = RefreshAccessToken::new()
             .refresh_access_token(&client, refresh_token)
             .await
             .unwrap();
 
-        println!("new access token: {:?}", access_token);
-        println!("new refresh token: {:?}", refresh_token);
+        println!("new access token: {:?}", tokens.access_token);
+        println!("new refresh token: {:?}", tokens.refresh_token);
+        println!("new expires in: {:?}", tokens.expires_in);
     }
 }
@@ -1,16 +1,36 @@
 //! Authorization Grant Code flow helper, as defined in the
 //! [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749#section-1.3.1)
 
+use std::{
+    convert::Infallible, fmt, future::poll_fn, io, net::SocketAddr, str::FromStr, task::Poll,
+    time::Duration,
+};
+
+use log::debug;
 use oauth2::{
     basic::BasicClient, url::Url, AuthorizationCode, CsrfToken, PkceCodeChallenge,
-    PkceCodeVerifier, RequestTokenError, Scope, TokenResponse,
+    PkceCodeVerifier, RequestTokenError, Scope,
 };
 use tokio::{
-    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
-    net::TcpListener,
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{lookup_host, TcpListener, TcpStream},
+    select,
+    task::JoinSet,
+    time::timeout,
 };
 
-use super::{Error, Result};
+use super::{Error, Result, Tokens};
+
+/// Maximum size of the HTTP request head sent to the redirect server.
+const MAX_REQUEST_HEAD_SIZE: u64 = 8 * 1024;
+
+/// Maximum time allowed to a single connection to send its request
+/// head to the redirect server.
+const REQUEST_HEAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum number of connections handled concurrently by the
+/// redirect server.
+const MAX_CONNECTIONS: usize = 16;
 
 /// OAuth 2.0 Authorization Code Grant flow builder.
 ///
@@ -30,6 +50,7 @@ pub struct AuthorizationCodeGrant {
     pub pkce: Option<(PkceCodeChallenge, PkceCodeVerifier)>,
     pub redirect_host: String,
     pub redirect_port: u16,
+    pub timeout: Option<Duration>,
 }
 
 impl AuthorizationCodeGrant {
@@ -50,6 +71,11 @@ pub fn with_pkce(mut self) -> Self {
         self
     }
 
+    /// Customize the host the redirect server listens on.
+    ///
+    /// IPv6 addresses are accepted, with or without brackets. When
+    /// the host resolves to multiple addresses (like `localhost`),
+    /// the redirect server listens on all of them.
     pub fn with_redirect_host<T>(mut self, host: T) -> Self
     where
         T: ToString,
@@ -66,6 +92,20 @@ pub fn with_redirect_port<T>(mut self, port: T) -> Self
         self
     }
 
+    /// Stop waiting for the redirection after the given duration.
+    ///
+    /// Defaults to 5 minutes.
+    pub fn with_timeout(mut self, timeout: impl Into<Duration>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+
+    /// Wait for the redirection without any time limit.
+    pub fn without_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
     /// Generate the redirect URL used to complete the OAuth 2.0
     /// Authorization Code Grant flow.
     pub fn get_redirect_url(&self, client: &BasicClient) -> (Url, CsrfToken) {
@@ -84,68 +124,40 @@ pub fn get_redirect_url(&self, client: &BasicClient) -> (Url, CsrfToken) {
     /// [`AuthorizationCodeGrant::get_redirect_url`], then exchange
     /// the received code with an access token and maybe a refresh
     /// token.
+    ///
+    /// Requests unrelated to the redirection (like `/favicon.ico`)
+    /// are answered with a `404` and ignored. The redirection is
+    /// answered once the code has been exchanged, with an HTML page
+    /// telling the user whether the authorization succeeded.
     pub async fn wait_for_redirection(
         self,
         client: &BasicClient,
         csrf_state: CsrfToken,
-    ) -> Result<(String, Option<String>)> {
-        let host = self.redirect_host;
-        let port = self.redirect_port;
+    ) -> Result<Tokens> {
+        let listeners = self.bind().await?;
 
-        // listen for one single connection
-        let (mut stream, _) = TcpListener::bind((host.clone(), port))
-            .await
-            .map_err(|err| Error::BindRedirectServerError(host, port, err))?
-            .accept()
-            .await
-            .map_err(Error::AcceptRedirectServerError)?;
-
-        // extract the code from the url
-        let code = {
-            let mut reader = BufReader::new(&mut stream);
-
-            let mut request_line = String::new();
-            reader.read_line(&mut request_line).await?;
-
-            let redirect_url = request_line
-                .split_whitespace()
-                .nth(1)
-                .ok_or_else(|| Error::MissingRedirectUrlError(request_line.clone()))?;
-            let redirect_url = format!("http://localhost{redirect_url}");
-            let redirect_url = Url::parse(&redirect_url)
-                .map_err(|err| Error::ParseRedirectUrlError(err, redirect_url.clone()))?;
-
-            let (_, state) = redirect_url
-                .query_pairs()
-                .find(|(key, _)| key == "state")
-                .ok_or_else(|| Error::FindStateInRedirectUrlError(redirect_url.clone()))?;
-            let state = CsrfToken::new(state.into_owned());
-
-            if state.secret() != csrf_state.secret() {
-                return Err(Error::InvalidStateError(
-                    state.secret().to_owned(),
-                    csrf_state.secret().to_owned(),
-                ));
-            }
-
-            let (_, code) = redirect_url
-                .query_pairs()
-                .find(|(key, _)| key == "code")
-                .ok_or_else(|| Error::FindCodeInRedirectUrlError(redirect_url.clone()))?;
+        let (mut stream, code) = match self.timeout {
+            Some(duration) => timeout(duration, accept_redirection(&listeners, csrf_state))
+                .await
+                .map_err(|_| Error::WaitForRedirectionTimeoutError(duration))??,
+            None => accept_redirection(&listeners, csrf_state).await?,
+        };
 
-            AuthorizationCode::new(code.into_owned())
+        let res = match code {
+            Ok(code) => self.exchange_code(client, code).await,
+            Err(err) => Err(err),
         };
 
-        // write a basic http response in plain text
-        let res = "Authentication successful!";
-        let res = format!(
-            "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{}",
-            res.len(),
-            res
-        );
-        stream.write_all(res.as_bytes()).await?;
+        if let Err(err) = write_redirection_response(&mut stream, &res).await {
+            debug!("cannot answer redirection: {err}");
+        }
+
+        res
+    }
 
-        // exchange the code for an access token and a refresh token
+    /// Exchange the given code for an access token and maybe a
+    /// refresh token.
+    async fn exchange_code(self, client: &BasicClient, code: AuthorizationCode) -> Result<Tokens> {
         let mut res = client.exchange_code(code);
 
         if let Some((_, pkce_verifier)) = self.pkce {
@@ -162,10 +174,45 @@ pub async fn wait_for_redirection(
                 RequestTokenError::Other(err) => Error::ExchangeCodeError(err),
             })?;
 
-        let access_token = res.access_token().secret().to_owned();
-        let refresh_token = res.refresh_token().map(|t| t.secret().clone());
+        Ok(Tokens::from(res))
+    }
+
+    /// Bind the redirect server on all the addresses the redirect
+    /// host resolves to.
+    async fn bind(&self) -> Result<Vec<TcpListener>> {
+        let host = self
+            .redirect_host
+            .trim_start_matches('[')
+            .trim_end_matches(']');
+        let port = self.redirect_port;
+
+        let addrs: Vec<SocketAddr> = lookup_host((host, port))
+            .await
+            .map_err(|err| Error::BindRedirectServerError(host.to_owned(), port, err))?
+            .collect();
+
+        let mut listeners = Vec::new();
+        let mut last_err = None;
+
+        for addr in addrs {
+            match TcpListener::bind(addr).await {
+                Ok(listener) => {
+                    debug!("redirect server listening on {addr}");
+                    listeners.push(listener);
+                }
+                Err(err) => {
+                    debug!("cannot bind redirect server on {addr}: {err}");
+                    last_err = Some(err);
+                }
+            }
+        }
 
-        Ok((access_token, refresh_token))
+        if listeners.is_empty() {
+            let err = last_err.unwrap_or_else(|| io::ErrorKind::AddrNotAvailable.into());
+            return Err(Error::BindRedirectServerError(host.to_owned(), port, err));
+        }
+
+        Ok(listeners)
     }
 }
 
@@ -176,6 +223,258 @@ fn default() -> Self {
             pkce: None,
             redirect_host: String::from("localhost"),
             redirect_port: 9999,
+            timeout: Some(Duration::from_secs(5 * 60)),
         }
     }
 }
+
+/// Error code returned by the authorization server in the
+/// redirection, as defined in the
+/// [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749#section-4.1.2.1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum AuthorizationErrorCode {
+    InvalidRequest,
+    UnauthorizedClient,
+    AccessDenied,
+    UnsupportedResponseType,
+    InvalidScope,
+    ServerError,
+    TemporarilyUnavailable,
+    Other(String),
+}
+
+impl FromStr for AuthorizationErrorCode {
+    type Err = std::convert::Infallible;
+
+    fn from_str(code: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(match code {
+            "invalid_request" => Self::InvalidRequest,
+            "unauthorized_client" => Self::UnauthorizedClient,
+            "access_denied" => Self::AccessDenied,
+            "unsupported_response_type" => Self::UnsupportedResponseType,
+            "invalid_scope" => Self::InvalidScope,
+            "server_error" => Self::ServerError,
+            "temporarily_unavailable" => Self::TemporarilyUnavailable,
+            code => Self::Other(code.to_owned()),
+        })
+    }
+}
+
+impl fmt::Display for AuthorizationErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRequest => write!(f, "invalid_request"),
+            Self::UnauthorizedClient => write!(f, "unauthorized_client"),
+            Self::AccessDenied => write!(f, "access_denied"),
+            Self::UnsupportedResponseType => write!(f, "unsupported_response_type"),
+            Self::InvalidScope => write!(f, "invalid_scope"),
+            Self::ServerError => write!(f, "server_error"),
+            Self::TemporarilyUnavailable => write!(f, "temporarily_unavailable"),
+            Self::Other(code) => write!(f, "{code}"),
+        }
+    }
+}
+
+/// Accept connections on the given listeners until the redirection
+/// is received.
+///
+/// Each connection is handled in its own task, so that idle
+/// connections (like browser preconnections) do not block the
+/// redirection. At most [`MAX_CONNECTIONS`] connections are handled
+/// concurrently, and the remaining ones are aborted once the
+/// redirection is received.
+///
+/// Returns the stream of the redirection, which is left unanswered,
+/// together with its outcome.
+async fn accept_redirection(
+    listeners: &[TcpListener],
+    csrf_state: CsrfToken,
+) -> Result<(TcpStream, Result<AuthorizationCode>)> {
+    let mut conns = JoinSet::new();
+
+    let redirection = loop {
+        select! {
+            conn = accept_any(listeners), if conns.len() < MAX_CONNECTIONS => {
+                let (stream, addr) = conn.map_err(Error::AcceptRedirectServerError)?;
+                debug!("redirect server accepted connection from {addr}");
+
+                let csrf_state = csrf_state.clone();
+                conns.spawn(async move { handle_connection(stream, &csrf_state).await });
+            }
+            Some(res) = conns.join_next() => {
+                match res {
+                    Ok(Ok(Some(redirection))) => break redirection,
+                    Ok(Ok(None)) => (),
+                    Ok(Err(err)) => debug!("cannot handle redirect server connection: {err}"),
+                    Err(err) => debug!("cannot join redirect server connection: {err}"),
+                }
+            }
+        }
+    };
+
+    conns.abort_all();
+
+    Ok(redirection)
+}
+
+/// Accept the first incoming connection among the given listeners.
+async fn accept_any(listeners: &[TcpListener]) -> io::Result<(TcpStream, SocketAddr)> {
+    poll_fn(|cx| {
+        for listener in listeners {
+            if let Poll::Ready(res) = listener.poll_accept(cx) {
+                return Poll::Ready(res);
+            }
+        }
+        Poll::Pending
+    })
+    .await
+}
+
+/// Handle a single connection to the redirect server.
+///
+/// Returns [`None`] if the request is unrelated to the redirection
+/// or if its state is missing or invalid, otherwise returns the
+/// unanswered stream together with the outcome of the redirection.
+/// Redirections with a missing or invalid state do not come from the
+/// authorization flow started by the user (they can be forged by any
+/// local process or web page), so they are rejected without stopping
+/// the redirect server.
+async fn handle_connection(
+    mut stream: TcpStream,
+    csrf_state: &CsrfToken,
+) -> io::Result<Option<(TcpStream, Result<AuthorizationCode>)>> {
+    let request_line = match timeout(REQUEST_HEAD_TIMEOUT, read_request_head(&mut stream)).await {
+        Ok(request_line) => request_line?,
+        Err(_) => return Ok(None),
+    };
+
+    let Some(redirect_url) = parse_redirect_url(&request_line) else {
+        write_response(&mut stream, "404 Not Found", &render_page("Not found", "")).await?;
+        return Ok(None);
+    };
+
+    match parse_redirection(&redirect_url, csrf_state) {
+        Err(err @ (Error::FindStateInRedirectUrlError(_) | Error::InvalidStateError)) => {
+            debug!("rejecting redirection with missing or invalid state");
+            let res: Result<AuthorizationCode> = Err(err);
+            write_redirection_response(&mut stream, &res).await?;
+            Ok(None)
+        }
+        res => Ok(Some((stream, res))),
+    }
+}
+
+/// Read the HTTP request head, and return its request line.
+async fn read_request_head(stream: &mut TcpStream) -> io::Result<String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_HEAD_SIZE));
+
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await?;
+
+    // drain the headers
+    let mut line = String::new();
+    while reader.read_line(&mut line).await? > 2 {
+        line.clear();
+    }
+
+    Ok(request_line)
+}
+
+/// Parse the URL of the given request line, if it looks like a
+/// redirection from the authorization server.
+fn parse_redirect_url(request_line: &str) -> Option<Url> {
+    let mut parts = request_line.split_whitespace();
+
+    if parts.next()? != "GET" {
+        return None;
+    }
+
+    let url = Url::parse(&format!("http://localhost{}", parts.next()?)).ok()?;
+    let is_redirection = url
+        .query_pairs()
+        .any(|(key, _)| key == "code" || key == "error");
+
+    is_redirection.then_some(url)
+}
+
+/// Extract the authorization code from the given redirect URL.
+fn parse_redirection(redirect_url: &Url, csrf_state: &CsrfToken) -> Result<AuthorizationCode> {
+    let find_param = |name: &str| {
+        redirect_url
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, val)| val.into_owned())
+    };
+
+    let state = find_param("state")
+        .ok_or_else(|| Error::FindStateInRedirectUrlError(redirect_url.clone()))?;
+
+    if &state != csrf_state.secret() {
+        return Err(Error::InvalidStateError);
+    }
+
+    if let Some(code) = find_param("error") {
+        return Err(Error::AuthorizationError(
+            code.parse()
+                .map_err(|err: Infallible| -> Error { match err {} })?,
+            find_param("error_description"),
+        ));
+    }
+
+    let code = find_param("code")
+        .ok_or_else(|| Error::FindCodeInRedirectUrlError(redirect_url.clone()))?;
+
+    Ok(AuthorizationCode::new(code))
+}
+
+/// Answer the redirection with a page telling the user whether the
+/// authorization succeeded.
+async fn write_redirection_response<T>(stream: &mut TcpStream, res: &Result<T>) -> io::Result<()> {
+    match res {
+        Ok(_) => {
+            let page = render_page(
+                "Authentication successful!",
+                "You can close this page and go back to your terminal.",
+            );
+            write_response(stream, "200 OK", &page).await
+        }
+        Err(err) => {
+            let page = render_page("Authentication failed!", &err.to_string());
+            write_response(stream, "400 Bad Request", &page).await
+        }
+    }
+}
+
+async fn write_response(stream: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    let res = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len(),
+    );
+    stream.write_all(res.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn render_page(title: &str, message: &str) -> String {
+    let title = escape_html(title);
+    let message = escape_html(message);
+    format!(
+        "<!DOCTYPE html><html><head><meta charset=\"utf-8\"><title>{title}</title></head><body><h1>{title}</h1><p>{message}</p></body></html>"
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
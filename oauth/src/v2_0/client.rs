@@ -1,7 +1,10 @@
 //! Client builder, used by other flows to send requests and build
 //! URLs.
 
-use oauth2::{basic::BasicClient, AuthUrl, ClientId, ClientSecret, RedirectUrl, TokenUrl};
+use oauth2::{
+    basic::BasicClient, AuthUrl, ClientId, ClientSecret, DeviceAuthorizationUrl, RedirectUrl,
+    TokenUrl,
+};
 
 use super::{Error, Result};
 
@@ -15,7 +18,11 @@ pub struct Client {
 
     /// Client password issued to the client during the registration process described by
     /// [Section 2.2](https://tools.ietf.org/html/rfc6749#section-2.2).
-    pub client_secret: ClientSecret,
+    ///
+    /// Public clients, as defined in the
+    /// [Section 2.1](https://tools.ietf.org/html/rfc6749#section-2.1),
+    /// have no client secret.
+    pub client_secret: Option<ClientSecret>,
 
     /// URL of the authorization server's authorization endpoint.
     pub auth_url: AuthUrl,
@@ -23,6 +30,11 @@ pub struct Client {
     /// URL of the authorization server's token endpoint.
     pub token_url: TokenUrl,
 
+    /// URL of the authorization server's device authorization
+    /// endpoint, as defined in the
+    /// [RFC8628](https://datatracker.ietf.org/doc/html/rfc8628#section-3.1).
+    pub device_authorization_url: Option<DeviceAuthorizationUrl>,
+
     /// Hostname of the client's redirection endpoint.
     pub redirect_host: String,
 
@@ -31,17 +43,35 @@ pub struct Client {
 }
 
 impl Client {
+    /// Create a confidential client, authenticated with its client
+    /// secret.
     pub fn new(
         client_id: impl ToString,
         client_secret: impl ToString,
         auth_url: impl ToString,
         token_url: impl ToString,
+    ) -> Result<Self> {
+        let mut client = Self::new_public(client_id, auth_url, token_url)?;
+        client.client_secret = Some(ClientSecret::new(client_secret.to_string()));
+        Ok(client)
+    }
+
+    /// Create a public client, without client secret.
+    ///
+    /// Clients running on devices, like the ones using the
+    /// [`crate::v2_0::DeviceAuthorizationGrant`] flow, usually cannot
+    /// keep a secret confidential.
+    pub fn new_public(
+        client_id: impl ToString,
+        auth_url: impl ToString,
+        token_url: impl ToString,
     ) -> Result<Self> {
         Ok(Self {
             client_id: ClientId::new(client_id.to_string()),
-            client_secret: ClientSecret::new(client_secret.to_string()),
+            client_secret: None,
             auth_url: AuthUrl::new(auth_url.to_string()).map_err(Error::BuildAuthUrlError)?,
             token_url: TokenUrl::new(token_url.to_string()).map_err(Error::BuildTokenUrlError)?,
+            device_authorization_url: None,
             redirect_host: String::from("localhost"),
             redirect_port: 9999,
         })
@@ -63,21 +93,43 @@ pub fn with_redirect_port<T>(mut self, port: T) -> Self
         self
     }
 
+    /// Set the device authorization endpoint, required by the
+    /// [`crate::v2_0::DeviceAuthorizationGrant`] flow.
+    pub fn with_device_authorization_url<T>(mut self, url: T) -> Result<Self>
+    where
+        T: ToString,
+    {
+        let url = DeviceAuthorizationUrl::new(url.to_string())
+            .map_err(Error::BuildDeviceAuthorizationUrlError)?;
+        self.device_authorization_url = Some(url);
+        Ok(self)
+    }
+
     /// Build the final client.
     pub fn build(&self) -> Result<BasicClient> {
         let host = &self.redirect_host;
         let port = self.redirect_port;
+        // IPv6 literals need to be enclosed in brackets
+        let host = if host.contains(':') && !host.starts_with('[') {
+            format!("[{host}]")
+        } else {
+            host.to_owned()
+        };
         let redirect_uri = RedirectUrl::new(format!("http://{host}:{port}"))
             .map_err(Error::BuildRedirectUrlError)?;
 
-        let client = BasicClient::new(
+        let mut client = BasicClient::new(
             self.client_id.clone(),
-            Some(self.client_secret.clone()),
+            self.client_secret.clone(),
             self.auth_url.clone(),
             Some(self.token_url.clone()),
         )
         .set_redirect_uri(redirect_uri);
 
+        if let Some(url) = &self.device_authorization_url {
+            client = client.set_device_authorization_url(url.clone());
+        }
+
         Ok(client)
     }
 }
@@ -0,0 +1,56 @@
+//! Client Credentials Grant flow helper, as defined in the
+//! [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749#section-4.4)
+
+use oauth2::{basic::BasicClient, RequestTokenError, Scope};
+
+use super::{Error, Result, Tokens};
+
+/// OAuth 2.0 Client Credentials Grant flow builder.
+///
+/// This flow is meant for confidential clients acting on their own
+/// behalf, like unattended services. The client authenticates with
+/// its own credentials, no user interaction is required.
+///
+/// No refresh token is issued by this flow: a new access token
+/// should be requested once the current one expires.
+#[derive(Debug, Default)]
+pub struct ClientCredentialsGrant {
+    pub scopes: Vec<Scope>,
+}
+
+impl ClientCredentialsGrant {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_scope<T>(mut self, scope: T) -> Self
+    where
+        T: ToString,
+    {
+        self.scopes.push(Scope::new(scope.to_string()));
+        self
+    }
+
+    /// Exchange the client credentials for an access token.
+    pub async fn request_access_token(&self, client: &BasicClient) -> Result<Tokens> {
+        let res = client
+            .exchange_client_credentials()
+            .add_scopes(self.scopes.clone())
+            .request_async(oauth2::reqwest::async_http_client)
+            .await
+            .map_err(|err| match err {
+                RequestTokenError::Request(req) => {
+                    Error::ExchangeClientCredentialsError(req.to_string())
+                }
+                RequestTokenError::ServerResponse(res) => {
+                    Error::ExchangeClientCredentialsError(res.to_string())
+                }
+                RequestTokenError::Parse(err, _) => {
+                    Error::ExchangeClientCredentialsError(err.to_string())
+                }
+                RequestTokenError::Other(err) => Error::ExchangeClientCredentialsError(err),
+            })?;
+
+        Ok(Tokens::from(res))
+    }
+}
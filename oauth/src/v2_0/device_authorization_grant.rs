@@ -0,0 +1,119 @@
+//! Device Authorization Grant flow helper, as defined in the
+//! [RFC8628](https://datatracker.ietf.org/doc/html/rfc8628)
+
+use std::time::Duration;
+
+use oauth2::{
+    basic::BasicClient, devicecode::StandardDeviceAuthorizationResponse, RequestTokenError, Scope,
+};
+
+use super::{Error, Result, Tokens};
+
+/// OAuth 2.0 Device Authorization Grant flow builder.
+///
+/// This flow is meant for devices that cannot spawn a browser nor
+/// receive a redirection, like SSH sessions or headless servers. The
+/// user completes the authorization from another device.
+///
+/// The first step (once the builder is configured) is to build a
+/// [`crate::v2_0::Client`] with a device authorization URL.
+///
+/// The second step is to request a device code by calling
+/// [`DeviceAuthorizationGrant::get_device_code`]. The response
+/// contains the verification URI and the user code that should be
+/// displayed to the user.
+///
+/// The last step is to poll the token endpoint until the user
+/// completes the authorization by calling
+/// [`DeviceAuthorizationGrant::wait_for_access_token`].
+#[derive(Debug, Default)]
+pub struct DeviceAuthorizationGrant {
+    pub scopes: Vec<Scope>,
+    pub timeout: Option<Duration>,
+}
+
+impl DeviceAuthorizationGrant {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_scope<T>(mut self, scope: T) -> Self
+    where
+        T: ToString,
+    {
+        self.scopes.push(Scope::new(scope.to_string()));
+        self
+    }
+
+    /// Stop polling the token endpoint after the given duration.
+    ///
+    /// Defaults to the lifetime of the device code, as returned by
+    /// the device authorization endpoint.
+    pub fn with_timeout(mut self, timeout: impl Into<Duration>) -> Self {
+        self.timeout = Some(timeout.into());
+        self
+    }
+
+    /// Request a device code and a user code to the device
+    /// authorization endpoint.
+    ///
+    /// The verification URI and the user code from the response need
+    /// to be shown to the user.
+    pub async fn get_device_code(
+        &self,
+        client: &BasicClient,
+    ) -> Result<StandardDeviceAuthorizationResponse> {
+        let res = client
+            .exchange_device_code()
+            .map_err(|err| Error::ExchangeDeviceCodeError(err.to_string()))?
+            .add_scopes(self.scopes.clone())
+            .request_async(oauth2::reqwest::async_http_client)
+            .await
+            .map_err(|err| match err {
+                RequestTokenError::Request(req) => Error::ExchangeDeviceCodeError(req.to_string()),
+                RequestTokenError::ServerResponse(res) => {
+                    Error::ExchangeDeviceCodeError(res.to_string())
+                }
+                RequestTokenError::Parse(err, _) => Error::ExchangeDeviceCodeError(err.to_string()),
+                RequestTokenError::Other(err) => Error::ExchangeDeviceCodeError(err),
+            })?;
+
+        Ok(res)
+    }
+
+    /// Poll the token endpoint until the user completes the
+    /// authorization, then return the access token and maybe a
+    /// refresh token.
+    ///
+    /// The polling interval starts at the one returned by the device
+    /// authorization endpoint (or 5 seconds), and is increased by 5
+    /// seconds each time the server responds with `slow_down`.
+    pub async fn wait_for_access_token(
+        &self,
+        client: &BasicClient,
+        device_code: &StandardDeviceAuthorizationResponse,
+    ) -> Result<Tokens> {
+        let res = client
+            .exchange_device_access_token(device_code)
+            .request_async(
+                oauth2::reqwest::async_http_client,
+                tokio::time::sleep,
+                self.timeout,
+            )
+            .await
+            .map_err(|err| match err {
+                RequestTokenError::Request(req) => {
+                    Error::ExchangeDeviceAccessTokenError(req.to_string())
+                }
+                RequestTokenError::ServerResponse(res) => {
+                    Error::ExchangeDeviceAccessTokenError(res.to_string())
+                }
+                RequestTokenError::Parse(err, _) => {
+                    Error::ExchangeDeviceAccessTokenError(err.to_string())
+                }
+                RequestTokenError::Other(err) => Error::ExchangeDeviceAccessTokenError(err),
+            })?;
+
+        Ok(Tokens::from(res))
+    }
+}
@@ -0,0 +1,190 @@
+//! Authorization server metadata discovery, as defined in the
+//! [RFC8414](https://datatracker.ietf.org/doc/html/rfc8414) and in
+//! the [OpenID Connect Discovery](https://openid.net/specs/openid-connect-discovery-1_0.html)
+//! specification.
+
+use log::debug;
+use serde::Deserialize;
+use url::Url;
+
+use super::{Client, Error, Result};
+
+const OAUTH_AUTHORIZATION_SERVER: &str = "oauth-authorization-server";
+const OPENID_CONFIGURATION: &str = "openid-configuration";
+
+/// Placeholder of the tenant-independent issuer advertised by the
+/// multi-tenant endpoints of the Microsoft identity platform (like
+/// `/common/v2.0`), which cannot advertise a single tenant.
+const TENANT_ID_PLACEHOLDER: &str = "{tenantid}";
+
+/// Authorization server metadata, as defined in the
+/// [RFC8414](https://datatracker.ietf.org/doc/html/rfc8414#section-2).
+///
+/// Only the metadata relevant to the flows of this library are kept.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct AuthorizationServerMetadata {
+    /// The authorization server's issuer identifier.
+    pub issuer: String,
+
+    /// URL of the authorization server's authorization endpoint.
+    pub authorization_endpoint: Option<String>,
+
+    /// URL of the authorization server's token endpoint.
+    pub token_endpoint: Option<String>,
+
+    /// URL of the authorization server's device authorization
+    /// endpoint.
+    pub device_authorization_endpoint: Option<String>,
+
+    /// URL of the authorization server's revocation endpoint.
+    pub revocation_endpoint: Option<String>,
+
+    /// Scopes supported by the authorization server.
+    #[serde(default)]
+    pub scopes_supported: Vec<String>,
+
+    /// Grant types supported by the authorization server.
+    #[serde(default)]
+    pub grant_types_supported: Vec<String>,
+
+    /// PKCE code challenge methods supported by the authorization
+    /// server.
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+}
+
+impl AuthorizationServerMetadata {
+    /// Discover the metadata of the authorization server identified
+    /// by the given issuer.
+    ///
+    /// Well-known locations are tried in order, see
+    /// [`AuthorizationServerMetadata::well_known_urls`]. The issuer
+    /// of the discovered metadata must match the given one, a
+    /// `{tenantid}` segment matching any tenant of a multi-tenant
+    /// Microsoft issuer.
+    pub async fn discover(issuer: impl AsRef<str>) -> Result<Self> {
+        let issuer = issuer.as_ref().trim_end_matches('/');
+        let issuer_url =
+            Url::parse(issuer).map_err(|err| Error::ParseIssuerUrlError(err, issuer.to_owned()))?;
+
+        let http = reqwest::Client::new();
+        let mut errs = Vec::new();
+
+        for url in Self::well_known_urls(&issuer_url) {
+            match Self::fetch(&http, url.clone()).await {
+                Ok(metadata) if issuer_matches(&metadata.issuer, issuer) => {
+                    debug!("discovered authorization server metadata at {url}");
+                    return Ok(metadata);
+                }
+                Ok(metadata) => {
+                    return Err(Error::InvalidIssuerError(
+                        metadata.issuer,
+                        issuer.to_owned(),
+                    ));
+                }
+                Err(err) => {
+                    debug!("cannot discover authorization server metadata at {url}: {err}");
+                    errs.push(format!("{url}: {err}"));
+                }
+            }
+        }
+
+        Err(Error::DiscoverMetadataError(
+            issuer.to_owned(),
+            errs.join(", "),
+        ))
+    }
+
+    /// Build the well-known URLs of the authorization server metadata
+    /// for the given issuer.
+    ///
+    /// The RFC8414 location comes first, then the OpenID Connect
+    /// ones. The well-known path is inserted between the host and the
+    /// path of the issuer, except for the OpenID Connect location
+    /// which appends it to the issuer.
+    pub fn well_known_urls(issuer: &Url) -> Vec<Url> {
+        let path = issuer.path().trim_end_matches('/');
+
+        let inserted = |name: &str| {
+            let mut url = issuer.clone();
+            url.set_path(&format!("/.well-known/{name}{path}"));
+            url.set_query(None);
+            url
+        };
+
+        let appended = {
+            let mut url = issuer.clone();
+            url.set_path(&format!("{path}/.well-known/{OPENID_CONFIGURATION}"));
+            url.set_query(None);
+            url
+        };
+
+        let mut urls = vec![
+            inserted(OAUTH_AUTHORIZATION_SERVER),
+            appended,
+            inserted(OPENID_CONFIGURATION),
+        ];
+        urls.dedup();
+        urls
+    }
+
+    async fn fetch(http: &reqwest::Client, url: Url) -> reqwest::Result<Self> {
+        http.get(url).send().await?.error_for_status()?.json().await
+    }
+}
+
+/// Check if the advertised issuer matches the expected one.
+///
+/// Both issuers must be identical, except for the `{tenantid}`
+/// segments of the advertised one which match any segment.
+fn issuer_matches(advertised: &str, expected: &str) -> bool {
+    let advertised = advertised.trim_end_matches('/');
+    let expected = expected.trim_end_matches('/');
+
+    if advertised == expected {
+        return true;
+    }
+
+    let advertised: Vec<_> = advertised.split('/').collect();
+    let expected: Vec<_> = expected.split('/').collect();
+
+    advertised.len() == expected.len()
+        && advertised
+            .iter()
+            .zip(expected)
+            .all(|(advertised, expected)| {
+                *advertised == expected
+                    || (*advertised == TENANT_ID_PLACEHOLDER && !expected.is_empty())
+            })
+}
+
+impl Client {
+    /// Create a client from the given authorization server metadata.
+    ///
+    /// The device authorization endpoint is set when the metadata
+    /// contains one.
+    pub fn from_metadata(
+        client_id: impl ToString,
+        client_secret: impl ToString,
+        metadata: &AuthorizationServerMetadata,
+    ) -> Result<Self> {
+        let auth_url =
+            metadata
+                .authorization_endpoint
+                .as_ref()
+                .ok_or(Error::MissingMetadataEndpointError(
+                    "authorization_endpoint",
+                ))?;
+        let token_url = metadata
+            .token_endpoint
+            .as_ref()
+            .ok_or(Error::MissingMetadataEndpointError("token_endpoint"))?;
+
+        let client = Client::new(client_id, client_secret, auth_url, token_url)?;
+
+        match &metadata.device_authorization_endpoint {
+            Some(url) => client.with_device_authorization_url(url),
+            None => Ok(client),
+        }
+    }
+}
@@ -1,8 +1,10 @@
 use oauth2::{basic::BasicErrorResponseType, RequestTokenError, StandardErrorResponse};
-use std::{io, result};
+use std::{io, result, time::Duration};
 use thiserror::Error;
 use url::Url;
 
+use super::AuthorizationErrorCode;
+
 /// The global `Result` alias of the module.
 pub type Result<T> = result::Result<T, Error>;
 
@@ -17,12 +19,14 @@ pub enum Error {
     BuildRevocationUrlError(#[source] oauth2::url::ParseError),
     #[error("cannot build redirect url")]
     BuildRedirectUrlError(#[source] oauth2::url::ParseError),
+    #[error("cannot build device authorization url")]
+    BuildDeviceAuthorizationUrlError(#[source] oauth2::url::ParseError),
     #[error("cannot bind redirect server")]
     BindRedirectServerError(String, u16, #[source] io::Error),
     #[error("cannot accept redirect server connections")]
     AcceptRedirectServerError(#[source] io::Error),
-    #[error("invalid state {0}: expected {1}")]
-    InvalidStateError(String, String),
+    #[error("invalid state")]
+    InvalidStateError,
     #[error("missing redirect url from {0}")]
     MissingRedirectUrlError(String),
     #[error("cannot parse redirect url {1}")]
@@ -31,8 +35,34 @@ pub enum Error {
     FindCodeInRedirectUrlError(Url),
     #[error("cannot find state from redirect url {0}")]
     FindStateInRedirectUrlError(Url),
+    #[error("authorization server returned error {0}{}", .1.as_ref().map(|d| format!(": {d}")).unwrap_or_default())]
+    AuthorizationError(AuthorizationErrorCode, Option<String>),
+    #[error("cannot receive redirection: timed out after {0:?}")]
+    WaitForRedirectionTimeoutError(Duration),
     #[error("cannot exchange code for access and refresh tokens: {0}")]
     ExchangeCodeError(String),
+    #[error("cannot request device code: {0}")]
+    ExchangeDeviceCodeError(String),
+    #[error("cannot exchange device code for access and refresh tokens: {0}")]
+    ExchangeDeviceAccessTokenError(String),
+    #[error("cannot exchange client credentials for an access token: {0}")]
+    ExchangeClientCredentialsError(String),
+    #[error("cannot parse jwt private key: {0}")]
+    ParseJwtPrivateKeyError(String),
+    #[error("cannot parse service account key: {0}")]
+    ParseServiceAccountKeyError(String),
+    #[error("cannot build jwt assertion: {0}")]
+    BuildJwtAssertionError(String),
+    #[error("cannot exchange jwt assertion for an access token: {0}")]
+    ExchangeJwtAssertionError(String),
+    #[error("cannot parse issuer url {1}")]
+    ParseIssuerUrlError(#[source] url::ParseError, String),
+    #[error("cannot discover authorization server metadata of {0}: {1}")]
+    DiscoverMetadataError(String, String),
+    #[error("invalid issuer {0} in authorization server metadata: expected {1}")]
+    InvalidIssuerError(String, String),
+    #[error("cannot find {0} in authorization server metadata")]
+    MissingMetadataEndpointError(&'static str),
 
     #[error(transparent)]
     IoError(#[from] std::io::Error),
@@ -0,0 +1,266 @@
+//! JWT Bearer Grant flow helper, as defined in the
+//! [RFC7523](https://datatracker.ietf.org/doc/html/rfc7523#section-2.1)
+
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use jsonwebtoken::{Algorithm, EncodingKey, Header};
+use oauth2::{
+    basic::{BasicErrorResponse, BasicTokenResponse},
+    Scope,
+};
+use serde::{Deserialize, Serialize};
+
+use super::{Error, Result, Tokens};
+
+/// Grant type of the JWT bearer assertion.
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:jwt-bearer";
+
+/// Service account key, as issued by Google Cloud in JSON format.
+///
+/// Only the fields relevant to the JWT bearer grant are kept.
+#[derive(Clone, Deserialize, Eq, PartialEq)]
+pub struct ServiceAccountKey {
+    /// Email address of the service account, used as the issuer of
+    /// the assertion.
+    pub client_email: String,
+
+    /// PEM-encoded RSA private key, used to sign the assertion.
+    pub private_key: String,
+
+    /// Identifier of the private key.
+    pub private_key_id: Option<String>,
+
+    /// URL of the token endpoint.
+    pub token_uri: Option<String>,
+}
+
+impl fmt::Debug for ServiceAccountKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceAccountKey")
+            .field("client_email", &self.client_email)
+            .field("private_key_id", &self.private_key_id)
+            .field("token_uri", &self.token_uri)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Claims of the JWT bearer assertion.
+#[derive(Debug, Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    sub: Option<&'a str>,
+    aud: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    iat: u64,
+    exp: u64,
+}
+
+/// OAuth 2.0 JWT Bearer Grant flow builder.
+///
+/// This flow is meant for unattended clients like service accounts:
+/// the client signs an assertion with its private key (RS256), then
+/// exchanges it for an access token. When a subject is given, the
+/// client acts on behalf of this subject (for example with Google
+/// Workspace domain-wide delegation).
+///
+/// No refresh token is issued by this flow: a new access token
+/// should be requested once the current one expires.
+pub struct JwtBearerGrant {
+    /// Issuer of the assertion, usually the client identifier or the
+    /// service account email address.
+    pub issuer: String,
+
+    /// Subject the access token is requested for.
+    pub subject: Option<String>,
+
+    /// Audience of the assertion.
+    ///
+    /// Defaults to the token endpoint URL.
+    pub audience: Option<String>,
+
+    /// Identifier of the private key, sent in the `kid` header of the
+    /// assertion.
+    pub key_id: Option<String>,
+
+    pub scopes: Vec<Scope>,
+
+    /// Lifetime of the assertion.
+    pub lifetime: Duration,
+
+    key: EncodingKey,
+}
+
+impl JwtBearerGrant {
+    /// Default lifetime of the assertion, which is also the maximum
+    /// lifetime accepted by Google.
+    pub const DEFAULT_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+    /// Create a new JWT bearer grant from the given issuer and
+    /// PEM-encoded RSA private key.
+    pub fn new(issuer: impl ToString, private_key: impl AsRef<[u8]>) -> Result<Self> {
+        let key = EncodingKey::from_rsa_pem(private_key.as_ref())
+            .map_err(|err| Error::ParseJwtPrivateKeyError(err.to_string()))?;
+
+        Ok(Self {
+            issuer: issuer.to_string(),
+            subject: None,
+            audience: None,
+            key_id: None,
+            scopes: Vec::new(),
+            lifetime: Self::DEFAULT_LIFETIME,
+            key,
+        })
+    }
+
+    /// Create a new JWT bearer grant from the given service account
+    /// key.
+    ///
+    /// The audience is set to the token endpoint of the key, if any.
+    pub fn from_service_account_key(key: &ServiceAccountKey) -> Result<Self> {
+        let mut grant = Self::new(&key.client_email, &key.private_key)?;
+        grant.key_id = key.private_key_id.clone();
+        grant.audience = key.token_uri.clone();
+        Ok(grant)
+    }
+
+    /// Create a new JWT bearer grant from the given service account
+    /// key, in JSON format.
+    pub fn from_service_account_key_json(json: impl AsRef<str>) -> Result<Self> {
+        let key: ServiceAccountKey = serde_json::from_str(json.as_ref())
+            .map_err(|err| Error::ParseServiceAccountKeyError(err.to_string()))?;
+        Self::from_service_account_key(&key)
+    }
+
+    pub fn with_subject<T>(mut self, subject: T) -> Self
+    where
+        T: ToString,
+    {
+        self.subject = Some(subject.to_string());
+        self
+    }
+
+    pub fn with_audience<T>(mut self, audience: T) -> Self
+    where
+        T: ToString,
+    {
+        self.audience = Some(audience.to_string());
+        self
+    }
+
+    pub fn with_key_id<T>(mut self, key_id: T) -> Self
+    where
+        T: ToString,
+    {
+        self.key_id = Some(key_id.to_string());
+        self
+    }
+
+    pub fn with_scope<T>(mut self, scope: T) -> Self
+    where
+        T: ToString,
+    {
+        self.scopes.push(Scope::new(scope.to_string()));
+        self
+    }
+
+    pub fn with_lifetime(mut self, lifetime: impl Into<Duration>) -> Self {
+        self.lifetime = lifetime.into();
+        self
+    }
+
+    /// Build and sign the assertion for the given token endpoint.
+    ///
+    /// Scopes are added to the claims, as required by some
+    /// authorization servers like Google.
+    pub fn build_assertion(&self, token_url: &str) -> Result<String> {
+        let iat = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+
+        let claims = Claims {
+            iss: &self.issuer,
+            sub: self.subject.as_deref(),
+            aud: self.audience.as_deref().unwrap_or(token_url),
+            scope: self.scope(),
+            iat,
+            exp: iat + self.lifetime.as_secs(),
+        };
+
+        let mut header = Header::new(Algorithm::RS256);
+        header.kid = self.key_id.clone();
+
+        jsonwebtoken::encode(&header, &claims, &self.key)
+            .map_err(|err| Error::BuildJwtAssertionError(err.to_string()))
+    }
+
+    /// Sign an assertion, then exchange it for an access token at the
+    /// given token endpoint.
+    pub async fn request_access_token(&self, token_url: impl AsRef<str>) -> Result<Tokens> {
+        let token_url = token_url.as_ref();
+        let assertion = self.build_assertion(token_url)?;
+
+        let mut params = vec![
+            ("grant_type", GRANT_TYPE.to_owned()),
+            ("assertion", assertion),
+        ];
+
+        if let Some(scope) = self.scope() {
+            params.push(("scope", scope));
+        }
+
+        let res = reqwest::Client::new()
+            .post(token_url)
+            .header(reqwest::header::ACCEPT, "application/json")
+            .form(&params)
+            .send()
+            .await
+            .map_err(|err| Error::ExchangeJwtAssertionError(err.to_string()))?;
+
+        let status = res.status();
+        let body = res
+            .bytes()
+            .await
+            .map_err(|err| Error::ExchangeJwtAssertionError(err.to_string()))?;
+
+        if !status.is_success() {
+            let err = match serde_json::from_slice::<BasicErrorResponse>(&body) {
+                Ok(err) => err.to_string(),
+                Err(_) => format!("{status}: {}", String::from_utf8_lossy(&body)),
+            };
+            return Err(Error::ExchangeJwtAssertionError(err));
+        }
+
+        let res: BasicTokenResponse = serde_json::from_slice(&body)
+            .map_err(|err| Error::ExchangeJwtAssertionError(err.to_string()))?;
+
+        Ok(Tokens::from(res))
+    }
+
+    fn scope(&self) -> Option<String> {
+        if self.scopes.is_empty() {
+            return None;
+        }
+
+        let scopes: Vec<&str> = self.scopes.iter().map(|s| s.as_str()).collect();
+        Some(scopes.join(" "))
+    }
+}
+
+impl fmt::Debug for JwtBearerGrant {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JwtBearerGrant")
+            .field("issuer", &self.issuer)
+            .field("subject", &self.subject)
+            .field("audience", &self.audience)
+            .field("key_id", &self.key_id)
+            .field("scopes", &self.scopes)
+            .field("lifetime", &self.lifetime)
+            .finish_non_exhaustive()
+    }
+}
@@ -1,5 +1,9 @@
 //! This module provides helpers to simplify OAuth 2.0 flows, based on
-//! the [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749).
+//! the [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749),
+//! the [RFC8628](https://datatracker.ietf.org/doc/html/rfc8628) and
+//! the [RFC7523](https://datatracker.ietf.org/doc/html/rfc7523).
+//! Authorization servers endpoints can be discovered, based on the
+//! [RFC8414](https://datatracker.ietf.org/doc/html/rfc8414).
 //!
 //! ```rust,ignore
 #![doc = include_str!("../../examples/gmail.rs")]
@@ -7,13 +11,23 @@
 
 mod authorization_code_grant;
 mod client;
+mod client_credentials_grant;
+mod device_authorization_grant;
+mod discovery;
 mod error;
+mod jwt_bearer_grant;
 mod refresh_access_token;
+mod tokens;
 
 #[doc(inline)]
 pub use self::{
-    authorization_code_grant::AuthorizationCodeGrant,
+    authorization_code_grant::{AuthorizationCodeGrant, AuthorizationErrorCode},
     client::Client,
+    client_credentials_grant::ClientCredentialsGrant,
+    device_authorization_grant::DeviceAuthorizationGrant,
+    discovery::AuthorizationServerMetadata,
     error::{Error, Result},
+    jwt_bearer_grant::{JwtBearerGrant, ServiceAccountKey},
     refresh_access_token::RefreshAccessToken,
+    tokens::Tokens,
 };
@@ -0,0 +1,47 @@
+//! Tokens returned by the authorization server's token endpoint, as
+//! defined in the
+//! [RFC6749](https://datatracker.ietf.org/doc/html/rfc6749#section-5.1)
+
+use std::time::{Duration, SystemTime};
+
+use oauth2::{basic::BasicTokenResponse, TokenResponse};
+
+/// Tokens returned by the authorization server's token endpoint.
+///
+/// The issue time is recorded when the tokens are received, so that
+/// the expiry time of the access token can be computed later on.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Tokens {
+    /// The access token issued by the authorization server.
+    pub access_token: String,
+
+    /// The refresh token, which can be used to obtain new access
+    /// tokens. When refreshing, a new refresh token may be issued,
+    /// in which case the previous one should be discarded.
+    pub refresh_token: Option<String>,
+
+    /// The lifetime of the access token.
+    pub expires_in: Option<Duration>,
+
+    /// The time the tokens were received at.
+    pub issued_at: SystemTime,
+}
+
+impl Tokens {
+    /// Return the time the access token expires at, if known.
+    pub fn expires_at(&self) -> Option<SystemTime> {
+        self.expires_in
+            .map(|expires_in| self.issued_at + expires_in)
+    }
+}
+
+impl From<BasicTokenResponse> for Tokens {
+    fn from(res: BasicTokenResponse) -> Self {
+        Self {
+            access_token: res.access_token().secret().to_owned(),
+            refresh_token: res.refresh_token().map(|t| t.secret().clone()),
+            expires_in: res.expires_in(),
+            issued_at: SystemTime::now(),
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::time::Duration;
+
+use oauth::v2_0::{AuthorizationCodeGrant, AuthorizationErrorCode, Client, Error};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+
+/// Spawn a fake token endpoint answering a single token request.
+async fn spawn_token_endpoint() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut content_length = 0;
+        let mut line = String::new();
+        while reader.read_line(&mut line).await.unwrap() > 2 {
+            if let Some((key, val)) = line.split_once(':') {
+                if key.eq_ignore_ascii_case("content-length") {
+                    content_length = val.trim().parse().unwrap();
+                }
+            }
+            line.clear();
+        }
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).await.unwrap();
+        assert!(String::from_utf8(body).unwrap().contains("code=test-code"));
+
+        let body = r#"{"access_token":"access","token_type":"bearer","expires_in":3600,"refresh_token":"refresh"}"#;
+        let res = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        reader.get_mut().write_all(res.as_bytes()).await.unwrap();
+    });
+
+    format!("http://{addr}/token")
+}
+
+async fn free_port() -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    listener.local_addr().unwrap().port()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redirection_after_unrelated_requests() {
+    let token_url = spawn_token_endpoint().await;
+    let port = free_port().await;
+
+    let client = Client::new("id", "secret", "http://127.0.0.1/auth", token_url)
+        .unwrap()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port)
+        .build()
+        .unwrap();
+
+    let grant = AuthorizationCodeGrant::new()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port);
+    let (_, csrf_token) = grant.get_redirect_url(&client);
+    let state = csrf_token.secret().to_owned();
+
+    let browser = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let http = reqwest::Client::new();
+
+        // an idle connection must not block the redirection
+        let _idle = tokio::net::TcpStream::connect(("127.0.0.1", port))
+            .await
+            .unwrap();
+
+        let res = http
+            .get(format!("http://127.0.0.1:{port}/favicon.ico"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 404);
+
+        let res = http
+            .get(format!(
+                "http://127.0.0.1:{port}/?code=test-code&state={state}"
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), 200);
+        assert!(res
+            .text()
+            .await
+            .unwrap()
+            .contains("Authentication successful!"));
+    });
+
+    let tokens = grant
+        .wait_for_redirection(&client, csrf_token)
+        .await
+        .unwrap();
+    browser.await.unwrap();
+
+    assert_eq!(tokens.access_token, "access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+    assert_eq!(tokens.expires_in, Some(Duration::from_secs(3600)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redirection_with_invalid_state() {
+    let token_url = spawn_token_endpoint().await;
+    let port = free_port().await;
+
+    let client = Client::new("id", "secret", "http://127.0.0.1/auth", token_url)
+        .unwrap()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port)
+        .build()
+        .unwrap();
+
+    let grant = AuthorizationCodeGrant::new()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port);
+    let (_, csrf_token) = grant.get_redirect_url(&client);
+    let state = csrf_token.secret().to_owned();
+
+    let browser = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // forged redirections are rejected without stopping the
+        // redirect server, and never reveal the expected state
+        for query in ["code=forged-code&state=forged-state", "code=forged-code"] {
+            let res = reqwest::get(format!("http://127.0.0.1:{port}/?{query}"))
+                .await
+                .unwrap();
+            assert_eq!(res.status(), 400);
+            let page = res.text().await.unwrap();
+            assert!(page.contains("Authentication failed!"));
+            assert!(!page.contains(&state));
+        }
+
+        let res = reqwest::get(format!(
+            "http://127.0.0.1:{port}/?code=test-code&state={state}"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(res.status(), 200);
+    });
+
+    let tokens = grant
+        .wait_for_redirection(&client, csrf_token)
+        .await
+        .unwrap();
+    browser.await.unwrap();
+
+    assert_eq!(tokens.access_token, "access");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redirection_with_error() {
+    let port = free_port().await;
+
+    let client = Client::new(
+        "id",
+        "secret",
+        "http://127.0.0.1/auth",
+        "http://127.0.0.1/token",
+    )
+    .unwrap()
+    .with_redirect_host("127.0.0.1")
+    .with_redirect_port(port)
+    .build()
+    .unwrap();
+
+    let grant = AuthorizationCodeGrant::new()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port);
+    let (_, csrf_token) = grant.get_redirect_url(&client);
+    let state = csrf_token.secret().to_owned();
+
+    let browser = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let res = reqwest::get(format!(
+            "http://127.0.0.1:{port}/?error=access_denied&error_description=%3Cdenied%3E&state={state}"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(res.status(), 400);
+        let page = res.text().await.unwrap();
+        assert!(page.contains("Authentication failed!"));
+        assert!(page.contains("&lt;denied&gt;"));
+    });
+
+    let err = grant
+        .wait_for_redirection(&client, csrf_token)
+        .await
+        .unwrap_err();
+    browser.await.unwrap();
+
+    match err {
+        Error::AuthorizationError(code, description) => {
+            assert_eq!(code, AuthorizationErrorCode::AccessDenied);
+            assert_eq!(description.as_deref(), Some("<denied>"));
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redirection_with_failed_exchange() {
+    let token_port = free_port().await;
+    let port = free_port().await;
+
+    let client = Client::new(
+        "id",
+        "secret",
+        "http://127.0.0.1/auth",
+        format!("http://127.0.0.1:{token_port}/token"),
+    )
+    .unwrap()
+    .with_redirect_host("127.0.0.1")
+    .with_redirect_port(port)
+    .build()
+    .unwrap();
+
+    let grant = AuthorizationCodeGrant::new()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port);
+    let (_, csrf_token) = grant.get_redirect_url(&client);
+    let state = csrf_token.secret().to_owned();
+
+    let browser = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+
+        // the page is rendered from the outcome of the exchange
+        let res = reqwest::get(format!(
+            "http://127.0.0.1:{port}/?code=test-code&state={state}"
+        ))
+        .await
+        .unwrap();
+        assert_eq!(res.status(), 400);
+        let page = res.text().await.unwrap();
+        assert!(page.contains("Authentication failed!"));
+        assert!(page.contains("cannot exchange code"));
+    });
+
+    let err = grant
+        .wait_for_redirection(&client, csrf_token)
+        .await
+        .unwrap_err();
+    browser.await.unwrap();
+
+    assert!(matches!(err, Error::ExchangeCodeError(_)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn redirection_timeout() {
+    let port = free_port().await;
+
+    let client = Client::new(
+        "id",
+        "secret",
+        "http://127.0.0.1/auth",
+        "http://127.0.0.1/token",
+    )
+    .unwrap()
+    .build()
+    .unwrap();
+
+    let grant = AuthorizationCodeGrant::new()
+        .with_redirect_host("127.0.0.1")
+        .with_redirect_port(port)
+        .with_timeout(Duration::from_millis(100));
+    let (_, csrf_token) = grant.get_redirect_url(&client);
+
+    let err = grant
+        .wait_for_redirection(&client, csrf_token)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::WaitForRedirectionTimeoutError(_)));
+}
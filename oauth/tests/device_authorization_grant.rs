@@ -0,0 +1,213 @@
+use std::{collections::HashMap, time::Duration};
+
+use oauth::v2_0::{Client, DeviceAuthorizationGrant, Error};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    sync::mpsc,
+    time::Instant,
+};
+
+/// Request received by the fake endpoints.
+#[derive(Debug)]
+struct Request {
+    path: String,
+    authorization: Option<String>,
+    params: HashMap<String, String>,
+    received_at: Instant,
+}
+
+/// Spawn fake device authorization and token endpoints. The device
+/// authorization endpoint returns a device code polled without
+/// interval, the token endpoint answers polls with the given status
+/// and body in order. Requests are sent back through the returned
+/// channel.
+async fn spawn_endpoints(
+    token_responses: Vec<(&'static str, &'static str)>,
+) -> (String, mpsc::UnboundedReceiver<Request>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    let verification_uri = format!("{base_url}/device");
+    tokio::spawn(async move {
+        let mut token_responses = token_responses.into_iter();
+
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).await.unwrap();
+            let path = request_line.split(' ').nth(1).unwrap_or_default();
+
+            let mut authorization = None;
+            let mut content_length = 0;
+            let mut line = String::new();
+            while reader.read_line(&mut line).await.unwrap() > 2 {
+                if let Some((key, val)) = line.split_once(':') {
+                    if key.eq_ignore_ascii_case("content-length") {
+                        content_length = val.trim().parse().unwrap();
+                    } else if key.eq_ignore_ascii_case("authorization") {
+                        authorization = Some(val.trim().to_owned());
+                    }
+                }
+                line.clear();
+            }
+
+            let mut params = vec![0; content_length];
+            reader.read_exact(&mut params).await.unwrap();
+            let params = url::form_urlencoded::parse(&params).into_owned().collect();
+
+            let device_code = format!(
+                r#"{{"device_code":"device","user_code":"USER-CODE","verification_uri":"{verification_uri}","expires_in":1800,"interval":0}}"#
+            );
+            let (status, body) = match path {
+                "/devicecode" => ("200 OK", device_code.as_str()),
+                "/token" => token_responses.next().expect("unexpected token request"),
+                _ => ("404 Not Found", ""),
+            };
+
+            let _ = tx.send(Request {
+                path: path.to_owned(),
+                authorization,
+                params,
+                received_at: Instant::now(),
+            });
+
+            let res = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            reader.get_mut().write_all(res.as_bytes()).await.unwrap();
+        }
+    });
+
+    (base_url, rx)
+}
+
+fn public_client(base_url: &str) -> Client {
+    Client::new_public(
+        "id",
+        format!("{base_url}/auth"),
+        format!("{base_url}/token"),
+    )
+    .unwrap()
+    .with_device_authorization_url(format!("{base_url}/devicecode"))
+    .unwrap()
+}
+
+// The clock is paused, so that the polling intervals elapse
+// instantly.
+#[tokio::test(start_paused = true)]
+async fn device_authorization_grant() {
+    let (base_url, mut requests) = spawn_endpoints(vec![
+        ("400 Bad Request", r#"{"error":"authorization_pending"}"#),
+        ("400 Bad Request", r#"{"error":"slow_down"}"#),
+        (
+            "200 OK",
+            r#"{"access_token":"access","token_type":"bearer","expires_in":3600,"refresh_token":"refresh"}"#,
+        ),
+    ])
+    .await;
+
+    let client = public_client(&base_url).build().unwrap();
+    let grant = DeviceAuthorizationGrant::new().with_scope("mail");
+
+    let device_code = grant.get_device_code(&client).await.unwrap();
+    assert_eq!(
+        device_code.verification_uri().as_str(),
+        format!("{base_url}/device")
+    );
+    assert_eq!(device_code.user_code().secret(), "USER-CODE");
+
+    let tokens = grant
+        .wait_for_access_token(&client, &device_code)
+        .await
+        .unwrap();
+
+    assert_eq!(tokens.access_token, "access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+    assert_eq!(tokens.expires_in, Some(Duration::from_secs(3600)));
+
+    let req = requests.recv().await.unwrap();
+    assert_eq!(req.path, "/devicecode");
+    assert_eq!(req.params.get("scope").unwrap(), "mail");
+
+    let mut polls = Vec::new();
+    while let Ok(req) = requests.try_recv() {
+        // public clients authenticate with their identifier only
+        assert_eq!(req.path, "/token");
+        assert_eq!(req.authorization, None);
+        assert_eq!(req.params.get("client_id").unwrap(), "id");
+        assert_eq!(req.params.get("device_code").unwrap(), "device");
+        assert_eq!(
+            req.params.get("grant_type").unwrap(),
+            "urn:ietf:params:oauth:grant-type:device_code"
+        );
+        polls.push(req.received_at);
+    }
+
+    assert_eq!(polls.len(), 3);
+    // authorization_pending keeps the interval
+    assert!(polls[1] - polls[0] < Duration::from_secs(1));
+    // slow_down increases the interval by 5 seconds
+    assert!(polls[2] - polls[1] >= Duration::from_secs(5));
+}
+
+#[tokio::test(start_paused = true)]
+async fn device_authorization_grant_expired_token() {
+    let (base_url, _requests) = spawn_endpoints(vec![
+        ("400 Bad Request", r#"{"error":"authorization_pending"}"#),
+        ("400 Bad Request", r#"{"error":"expired_token"}"#),
+    ])
+    .await;
+
+    let client = public_client(&base_url).build().unwrap();
+    let grant = DeviceAuthorizationGrant::new();
+
+    let device_code = grant.get_device_code(&client).await.unwrap();
+    let err = grant
+        .wait_for_access_token(&client, &device_code)
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::ExchangeDeviceAccessTokenError(err) => assert!(err.contains("expired_token")),
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn device_authorization_grant_confidential_client() {
+    let (base_url, mut requests) = spawn_endpoints(vec![(
+        "200 OK",
+        r#"{"access_token":"access","token_type":"bearer"}"#,
+    )])
+    .await;
+
+    let client = Client::new(
+        "id",
+        "secret",
+        format!("{base_url}/auth"),
+        format!("{base_url}/token"),
+    )
+    .unwrap()
+    .with_device_authorization_url(format!("{base_url}/devicecode"))
+    .unwrap()
+    .build()
+    .unwrap();
+    let grant = DeviceAuthorizationGrant::new();
+
+    let device_code = grant.get_device_code(&client).await.unwrap();
+    let tokens = grant
+        .wait_for_access_token(&client, &device_code)
+        .await
+        .unwrap();
+    assert_eq!(tokens.access_token, "access");
+
+    requests.recv().await.unwrap();
+    let req = requests.recv().await.unwrap();
+    assert_eq!(req.path, "/token");
+    assert!(req.authorization.unwrap().starts_with("Basic "));
+}
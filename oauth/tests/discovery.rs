@@ -0,0 +1,160 @@
+use std::collections::HashMap;
+
+use oauth::v2_0::{AuthorizationServerMetadata, Client, Error};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+};
+use url::Url;
+
+/// Spawn a fake metadata endpoint serving the documents built by the
+/// given function from the base URL of the endpoint, indexed by
+/// path. Other paths are answered with a 404.
+async fn spawn_metadata_endpoint(
+    documents: impl FnOnce(&str) -> HashMap<&'static str, String>,
+) -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let documents = documents(&base_url);
+
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut reader = BufReader::new(stream);
+
+            let mut request_line = String::new();
+            reader.read_line(&mut request_line).await.unwrap();
+            let path = request_line.split(' ').nth(1).unwrap_or_default();
+
+            let mut line = String::new();
+            while reader.read_line(&mut line).await.unwrap() > 2 {
+                line.clear();
+            }
+
+            let (status, body) = match documents.get(path) {
+                Some(body) => ("200 OK", body.as_str()),
+                None => ("404 Not Found", ""),
+            };
+            let res = format!(
+                "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            reader.get_mut().write_all(res.as_bytes()).await.unwrap();
+        }
+    });
+
+    base_url
+}
+
+fn metadata(issuer: &str, base_url: &str) -> String {
+    format!(
+        r#"{{"issuer":"{issuer}","authorization_endpoint":"{base_url}/authorize","token_endpoint":"{base_url}/token","device_authorization_endpoint":"{base_url}/devicecode","code_challenge_methods_supported":["S256"]}}"#
+    )
+}
+
+#[test]
+fn well_known_urls() {
+    let urls = |issuer: &str| {
+        AuthorizationServerMetadata::well_known_urls(&Url::parse(issuer).unwrap())
+            .into_iter()
+            .map(String::from)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        urls("https://accounts.google.com"),
+        vec![
+            "https://accounts.google.com/.well-known/oauth-authorization-server",
+            "https://accounts.google.com/.well-known/openid-configuration",
+        ]
+    );
+
+    assert_eq!(
+        urls("https://login.microsoftonline.com/common/v2.0/"),
+        vec![
+            "https://login.microsoftonline.com/.well-known/oauth-authorization-server/common/v2.0",
+            "https://login.microsoftonline.com/common/v2.0/.well-known/openid-configuration",
+            "https://login.microsoftonline.com/.well-known/openid-configuration/common/v2.0",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn discover_openid_configuration() {
+    let base_url = spawn_metadata_endpoint(|base_url| {
+        let issuer = format!("{base_url}/tenant");
+        HashMap::from_iter([(
+            "/tenant/.well-known/openid-configuration",
+            metadata(&issuer, base_url),
+        )])
+    })
+    .await;
+
+    let metadata = AuthorizationServerMetadata::discover(format!("{base_url}/tenant/"))
+        .await
+        .unwrap();
+
+    assert_eq!(metadata.issuer, format!("{base_url}/tenant"));
+    assert_eq!(
+        metadata.device_authorization_endpoint,
+        Some(format!("{base_url}/devicecode"))
+    );
+    assert_eq!(metadata.code_challenge_methods_supported, vec!["S256"]);
+
+    let client = Client::from_metadata("id", "secret", &metadata).unwrap();
+    assert_eq!(client.token_url.as_str(), format!("{base_url}/token"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn discover_multi_tenant_issuer() {
+    let base_url = spawn_metadata_endpoint(|base_url| {
+        // multi-tenant endpoints advertise a templated issuer
+        let issuer = format!("{base_url}/{{tenantid}}/v2.0");
+        HashMap::from_iter([(
+            "/common/v2.0/.well-known/openid-configuration",
+            metadata(&issuer, base_url),
+        )])
+    })
+    .await;
+
+    let metadata = AuthorizationServerMetadata::discover(format!("{base_url}/common/v2.0"))
+        .await
+        .unwrap();
+
+    assert_eq!(metadata.token_endpoint, Some(format!("{base_url}/token")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn discover_invalid_issuer() {
+    let base_url = spawn_metadata_endpoint(|base_url| {
+        let issuer = format!("{base_url}/other/v2.0");
+        HashMap::from_iter([(
+            "/.well-known/oauth-authorization-server/common/v2.0",
+            metadata(&issuer, base_url),
+        )])
+    })
+    .await;
+
+    let err = AuthorizationServerMetadata::discover(format!("{base_url}/common/v2.0"))
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::InvalidIssuerError(advertised, expected) => {
+            assert_eq!(advertised, format!("{base_url}/other/v2.0"));
+            assert_eq!(expected, format!("{base_url}/common/v2.0"));
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn discover_without_metadata() {
+    let base_url = spawn_metadata_endpoint(|_| HashMap::new()).await;
+
+    let err = AuthorizationServerMetadata::discover(&base_url)
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, Error::DiscoverMetadataError(..)));
+}
@@ -0,0 +1,174 @@
+use std::{collections::HashMap, time::Duration};
+
+use jsonwebtoken::{decode, DecodingKey, Validation};
+use oauth::v2_0::{Client, ClientCredentialsGrant, Error, JwtBearerGrant};
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpListener,
+    sync::oneshot,
+};
+
+const PRIVATE_KEY: &str = include_str!("keys/rsa.pem");
+const PUBLIC_KEY: &str = include_str!("keys/rsa.pub.pem");
+
+/// Spawn a fake token endpoint answering a single token request with
+/// the given status and body. The form parameters of the request are
+/// sent back through the returned channel.
+async fn spawn_token_endpoint(
+    status: &'static str,
+    body: &'static str,
+) -> (String, oneshot::Receiver<HashMap<String, String>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+
+        let mut content_length = 0;
+        let mut line = String::new();
+        while reader.read_line(&mut line).await.unwrap() > 2 {
+            if let Some((key, val)) = line.split_once(':') {
+                if key.eq_ignore_ascii_case("content-length") {
+                    content_length = val.trim().parse().unwrap();
+                }
+            }
+            line.clear();
+        }
+
+        let mut params = vec![0; content_length];
+        reader.read_exact(&mut params).await.unwrap();
+        let params = url::form_urlencoded::parse(&params).into_owned().collect();
+        let _ = tx.send(params);
+
+        let res = format!(
+            "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        reader.get_mut().write_all(res.as_bytes()).await.unwrap();
+    });
+
+    (format!("http://{addr}/token"), rx)
+}
+
+#[derive(Debug, Deserialize)]
+struct Claims {
+    iss: String,
+    sub: Option<String>,
+    aud: String,
+    scope: Option<String>,
+    iat: u64,
+    exp: u64,
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn client_credentials_grant() {
+    let (token_url, params) = spawn_token_endpoint(
+        "200 OK",
+        r#"{"access_token":"access","token_type":"bearer","expires_in":3600}"#,
+    )
+    .await;
+
+    let client = Client::new("id", "secret", "http://127.0.0.1/auth", token_url)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let tokens = ClientCredentialsGrant::new()
+        .with_scope("mail")
+        .request_access_token(&client)
+        .await
+        .unwrap();
+
+    let params = params.await.unwrap();
+    assert_eq!(params["grant_type"], "client_credentials");
+    assert_eq!(params["scope"], "mail");
+
+    assert_eq!(tokens.access_token, "access");
+    assert_eq!(tokens.refresh_token, None);
+    assert_eq!(tokens.expires_in, Some(Duration::from_secs(3600)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn jwt_bearer_grant() {
+    let (token_url, params) = spawn_token_endpoint(
+        "200 OK",
+        r#"{"access_token":"access","token_type":"Bearer","expires_in":3599}"#,
+    )
+    .await;
+
+    let key = serde_json::json!({
+        "type": "service_account",
+        "client_email": "robot@example.iam.gserviceaccount.com",
+        "private_key": PRIVATE_KEY,
+        "private_key_id": "key-id",
+        "token_uri": token_url,
+    });
+
+    let tokens = JwtBearerGrant::from_service_account_key_json(key.to_string())
+        .unwrap()
+        .with_subject("user@example.com")
+        .with_scope("https://mail.google.com/")
+        .request_access_token(&token_url)
+        .await
+        .unwrap();
+
+    assert_eq!(tokens.access_token, "access");
+    assert_eq!(tokens.expires_in, Some(Duration::from_secs(3599)));
+
+    let params = params.await.unwrap();
+    assert_eq!(
+        params["grant_type"],
+        "urn:ietf:params:oauth:grant-type:jwt-bearer"
+    );
+
+    let mut validation = Validation::new(jsonwebtoken::Algorithm::RS256);
+    validation.set_audience(&[&token_url]);
+    let assertion = decode::<Claims>(
+        &params["assertion"],
+        &DecodingKey::from_rsa_pem(PUBLIC_KEY.as_bytes()).unwrap(),
+        &validation,
+    )
+    .unwrap();
+
+    assert_eq!(assertion.header.kid.as_deref(), Some("key-id"));
+    assert_eq!(
+        assertion.claims.iss,
+        "robot@example.iam.gserviceaccount.com"
+    );
+    assert_eq!(assertion.claims.sub.as_deref(), Some("user@example.com"));
+    assert_eq!(assertion.claims.aud, token_url);
+    assert_eq!(
+        assertion.claims.scope.as_deref(),
+        Some("https://mail.google.com/")
+    );
+    assert_eq!(assertion.claims.exp - assertion.claims.iat, 3600);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn jwt_bearer_grant_rejected() {
+    let (token_url, _) = spawn_token_endpoint(
+        "400 Bad Request",
+        r#"{"error":"unauthorized_client","error_description":"Client is unauthorized"}"#,
+    )
+    .await;
+
+    let err = JwtBearerGrant::new("robot", PRIVATE_KEY)
+        .unwrap()
+        .request_access_token(&token_url)
+        .await
+        .unwrap_err();
+
+    match err {
+        Error::ExchangeJwtAssertionError(err) => assert!(err.contains("unauthorized_client")),
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn jwt_bearer_grant_invalid_key() {
+    let err = JwtBearerGrant::new("robot", "not a key").unwrap_err();
+    assert!(matches!(err, Error::ParseJwtPrivateKeyError(_)));
+}
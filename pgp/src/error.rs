@@ -51,6 +51,8 @@ pub enum Error {
     ParsePublicKeyError(#[source] pgp_native::errors::Error, Uri),
     #[error("cannot find pgp public key for email {0}")]
     FindPublicKeyError(String),
+    #[error("cannot get pgp secret key passphrase")]
+    GetSecretKeyPassphraseError(#[source] secret::Error),
     #[error("cannot build pgp secret key params")]
     BuildSecretKeyParamsError(#[source] SecretKeyParamsBuilderError),
     #[error("cannot generate pgp secret key")]
@@ -67,6 +69,15 @@ pub enum Error {
     #[error("cannot verify pgp public subkey")]
     VerifyPublicKeyError(#[source] pgp_native::errors::Error),
 
+    #[error("cannot export pgp secret key as armored string")]
+    ExportSecretKeyToArmoredStringError(#[source] pgp_native::errors::Error),
+    #[error("cannot export pgp public key as armored string")]
+    ExportPublicKeyToArmoredStringError(#[source] pgp_native::errors::Error),
+    #[error("cannot sign pgp revocation certificate")]
+    SignRevocationCertError(#[source] pgp_native::errors::Error),
+    #[error("cannot export pgp revocation certificate as armored string")]
+    ExportRevocationCertToArmoredStringError(#[source] pgp_native::errors::Error),
+
     #[error("cannot read armored public key at {1}")]
     ReadArmoredPublicKeyError(#[source] io::Error, PathBuf),
     #[error("cannot parse armored public key from {1}")]
@@ -102,6 +113,15 @@ pub enum Error {
     #[cfg(feature = "key-discovery")]
     #[error("cannot parse body")]
     ParseBodyError(#[source] hyper::Error),
+    #[cfg(feature = "key-discovery")]
+    #[error("cannot export pgp public key as bytes")]
+    ExportPublicKeyToBytesError(#[source] pgp_native::errors::Error),
+    #[cfg(feature = "key-discovery")]
+    #[error("cannot create web key directory at {1}")]
+    CreateWkdDirError(#[source] io::Error, PathBuf),
+    #[cfg(feature = "key-discovery")]
+    #[error("cannot write web key directory file at {1}")]
+    WriteWkdFileError(#[source] io::Error, PathBuf),
     #[error("cannot parse certificate")]
     ParseCertError(#[source] pgp_native::errors::Error),
 
@@ -10,6 +10,7 @@
 pub mod hkp;
 #[cfg(feature = "key-discovery")]
 pub mod http;
+pub mod key;
 pub mod sign;
 pub mod utils;
 pub mod verify;
@@ -23,6 +24,7 @@
     decrypt::decrypt,
     encrypt::encrypt,
     error::{Error, Result},
+    key::{export_pkey, export_skey, gen_revocation_cert, KeyPairBuilder},
     sign::sign,
     utils::{
         gen_key_pair, read_pkey_from_path, read_sig_from_bytes, read_skey_from_file,
@@ -17,10 +17,13 @@
 use hyper::{client::HttpConnector, http::Response, Body, Client, Uri};
 use hyper_rustls::HttpsConnector;
 use log::debug;
-use pgp_native::{Deserializable, SignedPublicKey};
+use pgp_native::{ser::Serialize, Deserializable, SignedPublicKey};
 use sha1::{Digest, Sha1};
-use std::fmt;
-use tokio::task;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+use tokio::{fs, task};
 
 use crate::{client, Error, Result};
 
@@ -71,7 +74,7 @@ pub fn from(email_address: impl AsRef<str>) -> Result<Self> {
 /// There are two variants of the URL scheme. `Advanced` should be
 /// preferred.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
-enum Variant {
+pub enum Variant {
     /// Advanced variant.
     ///
     /// This method uses a separate subdomain and is more flexible.
@@ -275,3 +278,91 @@ pub async fn get_all(emails: Vec<String>) -> Vec<(String, Result<SignedPublicKey
         .collect()
         .await
 }
+
+/// Publishes the given public key into a Web Key Directory layout.
+///
+/// The layout mirrors the URLs used by [`get`], relatively to the
+/// given root directory (usually the root of the web server):
+///
+/// ```text
+/// Advanced: <dir>/.well-known/openpgpkey/<domain>/hu/<hash>
+/// Direct:   <dir>/.well-known/openpgpkey/hu/<hash>
+/// ```
+///
+/// An empty `policy` file is also created next to the `hu`
+/// directory if it does not exist yet, as required by [draft-koch].
+/// Returns the path of the published key.
+///
+/// [draft-koch]: https://datatracker.ietf.org/doc/html/draft-koch-openpgp-webkey-service/#section-4.5
+pub async fn publish(
+    dir: impl AsRef<Path>,
+    email: impl AsRef<str>,
+    pkey: &SignedPublicKey,
+    variant: Variant,
+) -> Result<PathBuf> {
+    let wkd_url = Url::from(email)?;
+
+    let mut wkd_dir = dir.as_ref().join(".well-known").join("openpgpkey");
+    if variant == Variant::Advanced {
+        wkd_dir.push(&wkd_url.domain);
+    }
+
+    let hu_dir = wkd_dir.join("hu");
+    fs::create_dir_all(&hu_dir)
+        .await
+        .map_err(|err| Error::CreateWkdDirError(err, hu_dir.clone()))?;
+
+    let policy_path = wkd_dir.join("policy");
+    if !policy_path.exists() {
+        fs::write(&policy_path, [])
+            .await
+            .map_err(|err| Error::WriteWkdFileError(err, policy_path.clone()))?;
+    }
+
+    let pkey_bytes = pkey
+        .to_bytes()
+        .map_err(Error::ExportPublicKeyToBytesError)?;
+    let pkey_path = hu_dir.join(&wkd_url.local_encoded);
+    fs::write(&pkey_path, pkey_bytes)
+        .await
+        .map_err(|err| Error::WriteWkdFileError(err, pkey_path.clone()))?;
+
+    debug!("published public key of {wkd_url} at {pkey_path:?}");
+
+    Ok(pkey_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use pgp_native::{Deserializable, SignedPublicKey};
+    use std::fs;
+
+    use crate::{
+        gen_key_pair,
+        wkd::{encode_local_part, publish, Variant},
+    };
+
+    #[tokio::test]
+    async fn publish_advanced_and_direct() {
+        let dir = tempfile::tempdir().unwrap();
+        let (_, pkey) = gen_key_pair("Alice@Localhost", "").await.unwrap();
+        let hash = encode_local_part("alice");
+
+        let path = publish(dir.path(), "Alice@Localhost", &pkey, Variant::Advanced)
+            .await
+            .unwrap();
+        let expected_path = dir.path().join(".well-known/openpgpkey/localhost");
+        assert_eq!(path, expected_path.join("hu").join(&hash));
+        assert!(expected_path.join("policy").is_file());
+
+        let path = publish(dir.path(), "Alice@Localhost", &pkey, Variant::Direct)
+            .await
+            .unwrap();
+        let expected_path = dir.path().join(".well-known/openpgpkey");
+        assert_eq!(path, expected_path.join("hu").join(&hash));
+        assert!(expected_path.join("policy").is_file());
+
+        let published_pkey = SignedPublicKey::from_bytes(fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(published_pkey, pkey);
+    }
+}
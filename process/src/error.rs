@@ -1,4 +1,4 @@
-use std::{io, result, string::FromUtf8Error};
+use std::{io, result, string::FromUtf8Error, time::Duration};
 use thiserror::Error;
 
 /// The global `Result` alias of the library.
@@ -9,12 +9,22 @@
 pub enum Error {
     #[error("cannot get standard input")]
     GetStdinError,
+    #[error("cannot get standard output")]
+    GetStdoutError,
+    #[error("command {0} timed out after {1:?}")]
+    RunCommandTimeoutError(String, Duration),
+    #[error("cannot stream output of empty pipeline")]
+    StreamEmptyPipelineError,
     #[error("cannot get exit status code of command: {0}")]
     GetExitStatusCodeNotAvailableError(String),
     #[error("command {0} returned non-zero exit status code {1}: {2}")]
     GetExitStatusCodeNonZeroError(String, i32, String),
     #[error("cannot parse command output as string")]
     ParseOutputAsUtf8StringError(#[source] FromUtf8Error),
+    #[error("cannot parse command template {0}: {1}")]
+    ParseTemplateError(String, String),
+    #[error("cannot find variable {1} of command template {0}")]
+    MissingTemplateVariableError(String, String),
 
     #[error(transparent)]
     IoError(#[from] io::Error),
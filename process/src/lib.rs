@@ -13,27 +13,56 @@
 //!
 //! 3. Commands can be executed in a pipeline, which means the output
 //! of the previous command is send as input of the next one.
+//!
+//! 4. Commands can be given a timeout, environment variables and a
+//! working directory, and their output can be either collected (see
+//! [`CommandOutput`]) or streamed (see [`CommandStream`]).
+//!
+//! 5. Commands can contain named placeholders, rendered with quoted
+//! values so that they cannot inject shell code (see
+//! [`Command::render`]), or be executed without shell (see
+//! [`SingleCommand::new_argv`]).
 
 mod error;
+mod stream;
+mod template;
 
 use log::debug;
 use std::{
-    env,
+    collections::BTreeMap,
+    env, io,
     ops::{Deref, DerefMut},
-    process::Stdio,
+    path::PathBuf,
+    process::{ExitStatus, Stdio},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    process::Command as TokioCommand,
 };
-use tokio::{io::AsyncWriteExt, process::Command as TokioCommand};
 
 #[doc(inline)]
-pub use crate::error::{Error, Result};
+pub use crate::{
+    error::{Error, Result},
+    stream::CommandStream,
+    template::TemplateVars,
+};
 
-fn new_tokio_cmd() -> TokioCommand {
-    let windows = cfg!(target_os = "windows")
+/// Return `true` if commands are executed by the Windows `cmd`
+/// shell, `false` if they are executed by `sh`.
+fn is_windows_shell() -> bool {
+    cfg!(target_os = "windows")
         && !(env::var("MSYSTEM")
             .map(|env| env.starts_with("MINGW"))
-            .unwrap_or_default());
+            .unwrap_or_default())
+}
 
-    let (shell, arg) = if windows { ("cmd", "/C") } else { ("sh", "-c") };
+fn new_tokio_cmd() -> TokioCommand {
+    let (shell, arg) = if is_windows_shell() {
+        ("cmd", "/C")
+    } else {
+        ("sh", "-c")
+    };
 
     let mut cmd = TokioCommand::new(shell);
     cmd.arg(arg);
@@ -63,20 +92,92 @@ impl Command {
     ///
     /// This function is particularly useful when you need to replace
     /// placeholders on all inner commands.
-    pub fn replace(mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
-        match &mut self {
-            Self::SingleCommand(SingleCommand(cmd, ..)) => {
-                *cmd = cmd.replace(from.as_ref(), to.as_ref())
-            }
-            Self::Pipeline(Pipeline(cmds)) => {
-                for SingleCommand(cmd, ..) in cmds {
-                    *cmd = cmd.replace(from.as_ref(), to.as_ref());
-                }
-            }
+    ///
+    /// Values are replaced as they are, which makes shell commands
+    /// vulnerable to injection: prefer [`Command::render`] when
+    /// values come from untrusted sources.
+    pub fn replace_placeholder(mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
+        for cmd in self.commands_mut() {
+            *cmd = cmd.clone().replace_placeholder(from.as_ref(), to.as_ref());
         }
         self
     }
 
+    #[deprecated(note = "use `Command::replace_placeholder` instead")]
+    pub fn replace(self, from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
+        self.replace_placeholder(from, to)
+    }
+
+    /// Render the placeholders of all inner commands using the given
+    /// variables.
+    ///
+    /// See [`TemplateVars`] for the rendering rules.
+    pub fn render(mut self, vars: &TemplateVars) -> Result<Self> {
+        for cmd in self.commands_mut() {
+            *cmd = cmd.render(vars)?;
+        }
+        Ok(self)
+    }
+
+    /// Render the placeholders of all inner commands using the given
+    /// variables, leniently.
+    ///
+    /// See [`SingleCommand::render_lenient`].
+    pub fn render_lenient(mut self, vars: &TemplateVars) -> Result<Self> {
+        for cmd in self.commands_mut() {
+            *cmd = cmd.render_lenient(vars)?;
+        }
+        Ok(self)
+    }
+
+    /// Set the timeout of all inner commands.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        for cmd in self.commands_mut() {
+            cmd.timeout = Some(timeout);
+        }
+        self
+    }
+
+    /// Add an environment variable to all inner commands.
+    pub fn with_env(mut self, key: impl ToString, val: impl ToString) -> Self {
+        for cmd in self.commands_mut() {
+            cmd.env.insert(key.to_string(), val.to_string());
+        }
+        self
+    }
+
+    /// Clear or not the inherited environment of all inner commands.
+    pub fn with_env_cleared(mut self, cleared: bool) -> Self {
+        for cmd in self.commands_mut() {
+            cmd.clear_env = cleared;
+        }
+        self
+    }
+
+    /// Set the working directory of all inner commands.
+    pub fn with_current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        for cmd in self.commands_mut() {
+            cmd.current_dir = Some(dir.clone());
+        }
+        self
+    }
+
+    /// Ignore or not non-zero exit status of all inner commands.
+    pub fn with_exit_status_ignored(mut self, ignored: bool) -> Self {
+        for cmd in self.commands_mut() {
+            cmd.ignore_exit_status = ignored;
+        }
+        self
+    }
+
+    fn commands_mut(&mut self) -> &mut [SingleCommand] {
+        match self {
+            Self::SingleCommand(cmd) => std::slice::from_mut(cmd),
+            Self::Pipeline(Pipeline(cmds)) => cmds,
+        }
+    }
+
     /// Run the command without piped input.
     pub async fn run(&self) -> Result<CommandOutput> {
         self.run_with([]).await
@@ -89,6 +190,23 @@ pub async fn run_with(&self, input: impl AsRef<[u8]>) -> Result<CommandOutput> {
             Self::Pipeline(cmds) => cmds.run_with(input).await,
         }
     }
+
+    /// Run the command without piped input, and stream its output.
+    pub async fn stream(&self) -> Result<CommandStream> {
+        self.stream_with([]).await
+    }
+
+    /// Run the command with the given piped input, and stream its
+    /// output.
+    ///
+    /// For pipelines, only the output of the last command is
+    /// streamed.
+    pub async fn stream_with(&self, input: impl AsRef<[u8]>) -> Result<CommandStream> {
+        match self {
+            Self::SingleCommand(cmd) => cmd.stream_with(input),
+            Self::Pipeline(cmds) => cmds.stream_with(input).await,
+        }
+    }
 }
 
 impl Default for Command {
@@ -97,6 +215,12 @@ fn default() -> Self {
     }
 }
 
+impl From<SingleCommand> for Command {
+    fn from(cmd: SingleCommand) -> Self {
+        Self::SingleCommand(cmd)
+    }
+}
+
 impl From<String> for Command {
     fn from(cmd: String) -> Self {
         Self::SingleCommand(cmd.into())
@@ -163,20 +287,173 @@ fn to_string(&self) -> String {
 /// The single command structure.
 ///
 /// Represents commands that are composed of one single command.
+///
+/// Apart from the shell command itself, a single command holds
+/// execution options: a timeout, environment variables, a working
+/// directory and whether non-zero exit status should be
+/// ignored. When deserialized, a single command is either a string,
+/// or a map containing the command and its options.
+///
+/// By default, the command is executed by a shell (`sh -c` or `cmd
+/// /C`). A command created with [`SingleCommand::new_argv`] is
+/// executed directly instead, without shell: its arguments are never
+/// interpreted.
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(
     feature = "derive",
     derive(serde::Serialize, serde::Deserialize),
-    serde(from = "String", into = "String")
+    serde(from = "SingleCommandConfig", into = "SingleCommandConfig")
 )]
-pub struct SingleCommand(String, bool);
+pub struct SingleCommand {
+    cmd: String,
+    args: Option<Vec<String>>,
+    piped: bool,
+    timeout: Option<Duration>,
+    env: BTreeMap<String, String>,
+    clear_env: bool,
+    current_dir: Option<PathBuf>,
+    ignore_exit_status: bool,
+}
 
 impl SingleCommand {
+    /// Create a command executing the given program with the given
+    /// arguments, without shell.
+    ///
+    /// The command then dereferences to its program.
+    pub fn new_argv(program: impl ToString, args: impl IntoIterator<Item = impl ToString>) -> Self {
+        let mut cmd = Self::from(program.to_string());
+        cmd.args = Some(args.into_iter().map(|arg| arg.to_string()).collect());
+        cmd
+    }
+
+    /// Return the arguments of the command, if it is executed without
+    /// shell.
+    pub fn args(&self) -> Option<&[String]> {
+        self.args.as_deref()
+    }
+
+    /// Wrapper around [`alloc::str::replace`], applied to the command
+    /// and to its arguments.
+    ///
+    /// Not named `replace`, so that [`str::replace`] remains
+    /// reachable through [`Deref`].
+    pub fn replace_placeholder(mut self, from: impl AsRef<str>, to: impl AsRef<str>) -> Self {
+        let (from, to) = (from.as_ref(), to.as_ref());
+
+        self.cmd = self.cmd.replace(from, to);
+
+        for arg in self.args.iter_mut().flatten() {
+            *arg = arg.replace(from, to);
+        }
+
+        self
+    }
+
+    /// Render the placeholders of the command using the given
+    /// variables.
+    ///
+    /// Values substituted into shell commands are quoted, while
+    /// values substituted into the program and the arguments of
+    /// commands executed without shell are kept as they are. See
+    /// [`TemplateVars`] for the rendering rules.
+    pub fn render(&self, vars: &TemplateVars) -> Result<Self> {
+        self.render_with(vars, false)
+    }
+
+    /// Render the placeholders of the command using the given
+    /// variables, leniently.
+    ///
+    /// Like [`SingleCommand::render`], except that braces which are
+    /// not part of a placeholder of a known variable are kept as they
+    /// are, and that placeholders can be placed inside quotes: the
+    /// quotes are closed then reopened around the quoted value. This
+    /// suits commands written for plain replacement, like
+    /// `notify-send "New message: {subject}" | awk '{print $1}'`.
+    pub fn render_lenient(&self, vars: &TemplateVars) -> Result<Self> {
+        self.render_with(vars, true)
+    }
+
+    fn render_with(&self, vars: &TemplateVars, lenient: bool) -> Result<Self> {
+        let mut cmd = self.clone();
+
+        match &mut cmd.args {
+            None => {
+                cmd.cmd = template::render(&self.cmd, vars, true, lenient)?;
+            }
+            Some(args) => {
+                cmd.cmd = template::render(&self.cmd, vars, false, lenient)?;
+                for arg in args {
+                    *arg = template::render(arg, vars, false, lenient)?;
+                }
+            }
+        }
+
+        Ok(cmd)
+    }
+
     pub fn with_output_piped(mut self, piped: bool) -> Self {
-        self.1 = piped;
+        self.piped = piped;
+        self
+    }
+
+    /// Kill the command if it does not exit within the given
+    /// duration.
+    ///
+    /// On Unix, a command with a timeout runs in its own process
+    /// group, which is killed as a whole: processes spawned in
+    /// background by the command are killed as well. As a
+    /// consequence, such command cannot read from the terminal.
+    ///
+    /// A command with a timeout is also killed when its future or its
+    /// stream is dropped.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Add an environment variable to the command.
+    pub fn with_env(mut self, key: impl ToString, val: impl ToString) -> Self {
+        self.env.insert(key.to_string(), val.to_string());
         self
     }
 
+    /// Clear or not the environment inherited from the current
+    /// process.
+    ///
+    /// Variables added with [`SingleCommand::with_env`] are still
+    /// passed to the command.
+    pub fn with_env_cleared(mut self, cleared: bool) -> Self {
+        self.clear_env = cleared;
+        self
+    }
+
+    /// Run the command from the given working directory.
+    pub fn with_current_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.current_dir = Some(dir.into());
+        self
+    }
+
+    /// Ignore or not non-zero exit status.
+    ///
+    /// When ignored, the exit status can be read from the
+    /// [`CommandOutput`] instead.
+    pub fn with_exit_status_ignored(mut self, ignored: bool) -> Self {
+        self.ignore_exit_status = ignored;
+        self
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn env(&self) -> &BTreeMap<String, String> {
+        &self.env
+    }
+
+    pub fn current_dir(&self) -> Option<&PathBuf> {
+        self.current_dir.as_ref()
+    }
+
     pub async fn run(&self) -> Result<CommandOutput> {
         self.run_with([]).await
     }
@@ -187,6 +464,9 @@ pub async fn run(&self) -> Result<CommandOutput> {
     /// output. Otherwise the commands pipes this input to the
     /// standard input channel then waits for the output on the
     /// standard output channel.
+    ///
+    /// If the command does not exit before its timeout, it is killed
+    /// and an error is returned.
     pub async fn run_with(&self, input: impl AsRef<[u8]>) -> Result<CommandOutput> {
         debug!("running single command: {}", self.to_string());
 
@@ -199,44 +479,142 @@ pub async fn run_with(&self, input: impl AsRef<[u8]>) -> Result<CommandOutput> {
         };
 
         let stdout = || {
-            if self.1 {
+            if self.piped {
                 Stdio::piped()
             } else {
                 Stdio::inherit()
             }
         };
 
-        let mut cmd = new_tokio_cmd()
-            .arg(&self.0)
+        let mut child = self
+            .to_tokio_cmd()
             .stdin(stdin)
             .stdout(stdout())
             .stderr(stdout())
             .spawn()?;
 
-        if !input.is_empty() {
-            cmd.stdin
-                .as_mut()
-                .ok_or(Error::GetStdinError)?
-                .write_all(input)
-                .await?
+        // the process identifier needs to be kept, since the process
+        // group can outlive the command itself
+        let pid = child.id();
+
+        let stdin = match child.stdin.take() {
+            None if !input.is_empty() => return Err(Error::GetStdinError),
+            stdin => stdin,
+        };
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        // input is written while output is read, so that commands
+        // filling their output before consuming their input do not
+        // block
+        let run = async {
+            let write = async {
+                if let Some(mut stdin) = stdin {
+                    stdin.write_all(input).await?;
+                }
+                io::Result::Ok(())
+            };
+
+            let (_, stdout, stderr, status) = tokio::try_join!(
+                write,
+                read_to_end(stdout),
+                read_to_end(stderr),
+                child.wait()
+            )?;
+
+            io::Result::Ok((stdout, stderr, status))
+        };
+
+        let (stdout, stderr, status) = match self.timeout {
+            None => run.await?,
+            Some(timeout) => match tokio::time::timeout(timeout, run).await {
+                Ok(output) => output?,
+                Err(_) => {
+                    debug!("single command timed out after {timeout:?}, killing it");
+                    kill_process_group(pid);
+                    if let Err(_err) = child.kill().await {
+                        debug!("cannot kill single command: {_err}");
+                    }
+                    return Err(Error::RunCommandTimeoutError(self.to_string(), timeout));
+                }
+            },
+        };
+
+        if !self.ignore_exit_status {
+            check_exit_status(self, status, &stderr)?;
         }
 
-        let output = cmd.wait_with_output().await?;
+        Ok(CommandOutput {
+            stdout,
+            stderr,
+            status: Some(status),
+        })
+    }
 
-        let code = output
-            .status
-            .code()
-            .ok_or_else(|| Error::GetExitStatusCodeNotAvailableError(self.to_string()))?;
+    /// Run the single command without piped input, and stream its
+    /// output.
+    pub fn stream(&self) -> Result<CommandStream> {
+        self.stream_with([])
+    }
 
-        debug!("single command exit code: {code}");
+    /// Run the single command with the given input, and stream its
+    /// output.
+    ///
+    /// The standard output is always piped, while the standard error
+    /// is inherited from the current process. The input is written
+    /// as the output is read.
+    pub fn stream_with(&self, input: impl AsRef<[u8]>) -> Result<CommandStream> {
+        debug!("streaming single command: {}", self.to_string());
 
-        if code != 0 {
-            let cmd = self.to_string();
-            let err = String::from_utf8_lossy(&output.stderr).to_string();
-            return Err(Error::GetExitStatusCodeNonZeroError(cmd, code, err));
+        let input = input.as_ref();
+
+        let stdin = if input.is_empty() {
+            Stdio::inherit()
+        } else {
+            Stdio::piped()
+        };
+
+        let child = self
+            .to_tokio_cmd()
+            .stdin(stdin)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()?;
+
+        CommandStream::new(self, child, input.to_owned())
+    }
+
+    fn to_tokio_cmd(&self) -> TokioCommand {
+        let mut cmd = match &self.args {
+            None => {
+                let mut cmd = new_tokio_cmd();
+                cmd.arg(&self.cmd);
+                cmd
+            }
+            Some(args) => {
+                let mut cmd = TokioCommand::new(&self.cmd);
+                cmd.args(args);
+                cmd
+            }
+        };
+
+        if self.timeout.is_some() {
+            cmd.kill_on_drop(true);
+            #[cfg(unix)]
+            cmd.process_group(0);
+        }
+
+        if self.clear_env {
+            cmd.env_clear();
+        }
+
+        cmd.envs(&self.env);
+
+        if let Some(dir) = &self.current_dir {
+            cmd.current_dir(dir);
         }
 
-        Ok(output.stdout.into())
+        cmd
     }
 }
 
@@ -244,19 +622,28 @@ impl Deref for SingleCommand {
     type Target = String;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.cmd
     }
 }
 
 impl DerefMut for SingleCommand {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.cmd
     }
 }
 
 impl From<String> for SingleCommand {
     fn from(cmd: String) -> Self {
-        Self(cmd, true)
+        Self {
+            cmd,
+            args: None,
+            piped: true,
+            timeout: None,
+            env: BTreeMap::new(),
+            clear_env: false,
+            current_dir: None,
+            ignore_exit_status: false,
+        }
     }
 }
 
@@ -274,13 +661,149 @@ fn from(cmd: &str) -> Self {
 
 impl From<SingleCommand> for String {
     fn from(cmd: SingleCommand) -> Self {
-        cmd.0
+        cmd.to_string()
     }
 }
 
+/// Commands executed without shell are displayed as POSIX shell
+/// commands, with their arguments quoted when needed.
 impl ToString for SingleCommand {
     fn to_string(&self) -> String {
-        self.0.to_owned()
+        let Some(args) = &self.args else {
+            return self.cmd.to_owned();
+        };
+
+        let quote = |arg: &String| {
+            let safe = !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || "_-.,:/=@%+".contains(c));
+
+            if safe {
+                arg.to_owned()
+            } else {
+                template::posix_quote(arg)
+            }
+        };
+
+        std::iter::once(&self.cmd)
+            .chain(args)
+            .map(quote)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// The serde representation of a single command.
+///
+/// A single command without option is represented as a string.
+#[cfg(feature = "derive")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(untagged)]
+enum SingleCommandConfig {
+    Command(String),
+    WithOptions(SingleCommandOptions),
+}
+
+#[cfg(feature = "derive")]
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SingleCommandOptions {
+    cmd: String,
+    /// The arguments, when executed without shell.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    args: Option<Vec<String>>,
+    /// The timeout, in seconds.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "timeout_secs"
+    )]
+    timeout: Option<Duration>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    env: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    clear_env: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    current_dir: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    ignore_exit_status: bool,
+}
+
+#[cfg(feature = "derive")]
+impl From<SingleCommandConfig> for SingleCommand {
+    fn from(config: SingleCommandConfig) -> Self {
+        match config {
+            SingleCommandConfig::Command(cmd) => cmd.into(),
+            SingleCommandConfig::WithOptions(opts) => Self {
+                cmd: opts.cmd,
+                args: opts.args,
+                piped: true,
+                timeout: opts.timeout,
+                env: opts.env,
+                clear_env: opts.clear_env,
+                current_dir: opts.current_dir,
+                ignore_exit_status: opts.ignore_exit_status,
+            },
+        }
+    }
+}
+
+#[cfg(feature = "derive")]
+impl From<SingleCommand> for SingleCommandConfig {
+    fn from(cmd: SingleCommand) -> Self {
+        let SingleCommand {
+            cmd,
+            args,
+            piped: _,
+            timeout,
+            env,
+            clear_env,
+            current_dir,
+            ignore_exit_status,
+        } = cmd;
+
+        if args.is_none()
+            && timeout.is_none()
+            && env.is_empty()
+            && !clear_env
+            && current_dir.is_none()
+            && !ignore_exit_status
+        {
+            return Self::Command(cmd);
+        }
+
+        Self::WithOptions(SingleCommandOptions {
+            cmd,
+            args,
+            timeout,
+            env,
+            clear_env,
+            current_dir,
+            ignore_exit_status,
+        })
+    }
+}
+
+/// (De)serialize an optional timeout as a number of seconds.
+#[cfg(feature = "derive")]
+mod timeout_secs {
+    use std::time::Duration;
+
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(timeout: &Option<Duration>, s: S) -> Result<S::Ok, S::Error> {
+        match timeout {
+            Some(timeout) => s.serialize_f64(timeout.as_secs_f64()),
+            None => s.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Duration>, D::Error> {
+        let secs = f64::deserialize(d)?;
+        let timeout = Duration::try_from_secs_f64(secs)
+            .map_err(|err| D::Error::custom(format!("invalid timeout {secs}: {err}")))?;
+        Ok(Some(timeout))
     }
 }
 
@@ -294,22 +817,37 @@ fn to_string(&self) -> String {
 #[cfg_attr(
     feature = "derive",
     derive(serde::Serialize, serde::Deserialize),
-    serde(from = "Vec<String>", into = "Vec<String>")
+    serde(transparent)
 )]
 pub struct Pipeline(Vec<SingleCommand>);
 
 impl Pipeline {
     /// Run the command pipeline with the given input.
+    ///
+    /// The returned output contains the standard output and the exit
+    /// status of the last command, and the standard error of all
+    /// commands.
     pub async fn run_with(&self, input: impl AsRef<[u8]>) -> Result<CommandOutput> {
         debug!("running pipeline: {}", self.to_string());
 
-        let mut output = input.as_ref().to_owned();
+        let mut output = CommandOutput::from(input.as_ref().to_owned());
+        let mut stderr = Vec::new();
 
         for cmd in &self.0 {
-            output = cmd.run_with(&output).await?.0;
+            output = cmd.run_with(&output.stdout).await?;
+            stderr.append(&mut output.stderr);
         }
 
-        Ok(output.into())
+        output.stderr = stderr;
+        Ok(output)
+    }
+
+    /// Run the command pipeline with the given input, and stream the
+    /// output of the last command.
+    pub async fn stream_with(&self, input: impl AsRef<[u8]>) -> Result<CommandStream> {
+        let (last, cmds) = self.0.split_last().ok_or(Error::StreamEmptyPipelineError)?;
+        let output = Pipeline(cmds.to_vec()).run_with(input).await?;
+        last.stream_with(&output.stdout)
     }
 }
 
@@ -383,41 +921,64 @@ fn to_string(&self) -> String {
 
 /// Wrapper around command output.
 ///
-/// The only role of this struct is to provide convenient functions to
-/// export command output as string.
+/// Provides convenient functions to export the standard output as
+/// string. The standard error is only captured when the output is
+/// piped.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct CommandOutput(Vec<u8>);
+pub struct CommandOutput {
+    /// The standard output of the command.
+    pub stdout: Vec<u8>,
+
+    /// The standard error of the command.
+    pub stderr: Vec<u8>,
+
+    /// The exit status of the command, if the command has been run.
+    pub status: Option<ExitStatus>,
+}
 
 impl CommandOutput {
     /// Reads the command output as string lossy.
     pub fn to_string_lossy(&self) -> String {
         String::from_utf8_lossy(self).to_string()
     }
+
+    /// Reads the command error output as string lossy.
+    pub fn stderr_to_string_lossy(&self) -> String {
+        String::from_utf8_lossy(&self.stderr).to_string()
+    }
+
+    /// Return the exit status code of the command, if any.
+    pub fn code(&self) -> Option<i32> {
+        self.status.and_then(|status| status.code())
+    }
 }
 
 impl Deref for CommandOutput {
     type Target = Vec<u8>;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &self.stdout
     }
 }
 
 impl DerefMut for CommandOutput {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.stdout
     }
 }
 
 impl From<Vec<u8>> for CommandOutput {
-    fn from(output: Vec<u8>) -> Self {
-        Self(output)
+    fn from(stdout: Vec<u8>) -> Self {
+        Self {
+            stdout,
+            ..Default::default()
+        }
     }
 }
 
 impl From<CommandOutput> for Vec<u8> {
     fn from(val: CommandOutput) -> Self {
-        val.0
+        val.stdout
     }
 }
 
@@ -428,3 +989,52 @@ fn try_from(cmd: CommandOutput) -> Result<Self> {
         String::from_utf8(cmd.into()).map_err(Error::ParseOutputAsUtf8StringError)
     }
 }
+
+/// Read the given optional output until the end.
+async fn read_to_end(reader: Option<impl AsyncRead + Unpin>) -> io::Result<Vec<u8>> {
+    let mut output = Vec::new();
+
+    if let Some(mut reader) = reader {
+        reader.read_to_end(&mut output).await?;
+    }
+
+    Ok(output)
+}
+
+/// Kill the process group led by the given timed out command, so
+/// that the processes it spawned in background are killed as well.
+///
+/// Commands with a timeout lead their own process group, whose
+/// identifier is the process identifier of the command.
+#[cfg(unix)]
+pub(crate) fn kill_process_group(pid: Option<u32>) {
+    let Some(pid) = pid.and_then(|pid| libc::pid_t::try_from(pid).ok()) else {
+        return;
+    };
+
+    // SAFETY: kill(2) does not access the memory of the process
+    if unsafe { libc::kill(-pid, libc::SIGKILL) } != 0 {
+        let _err = io::Error::last_os_error();
+        debug!("cannot kill process group {pid}: {_err}");
+    }
+}
+
+#[cfg(not(unix))]
+pub(crate) fn kill_process_group(_pid: Option<u32>) {}
+
+/// Return an error if the given exit status is not successful.
+fn check_exit_status(cmd: &SingleCommand, status: ExitStatus, stderr: &[u8]) -> Result<()> {
+    let code = status
+        .code()
+        .ok_or_else(|| Error::GetExitStatusCodeNotAvailableError(cmd.to_string()))?;
+
+    debug!("single command exit code: {code}");
+
+    if code != 0 {
+        let cmd = cmd.to_string();
+        let err = String::from_utf8_lossy(stderr).to_string();
+        return Err(Error::GetExitStatusCodeNonZeroError(cmd, code, err));
+    }
+
+    Ok(())
+}
@@ -0,0 +1,162 @@
+//! # Stream
+//!
+//! Module dedicated to command output streaming. Streaming is useful
+//! for commands producing large outputs, which do not need to be
+//! entirely held in memory.
+
+use std::{
+    future::Future,
+    io,
+    pin::Pin,
+    process::ExitStatus,
+    task::{Context, Poll},
+};
+
+use log::debug;
+use tokio::{
+    io::{AsyncRead, AsyncWrite, ReadBuf},
+    process::{Child, ChildStdin, ChildStdout},
+    time::{self, Sleep},
+};
+
+use crate::{check_exit_status, kill_process_group, Error, Result, SingleCommand};
+
+/// The standard output of a running command.
+///
+/// The stream implements [`AsyncRead`]. Reading from it also writes
+/// the input of the command, if any. Once the output has been read,
+/// [`CommandStream::wait`] should be called in order to get the exit
+/// status of the command.
+///
+/// A command with a timeout is killed when its timeout is reached,
+/// or when the stream is dropped. On timeout, its whole process group
+/// is killed, see [`SingleCommand::with_timeout`].
+#[derive(Debug)]
+pub struct CommandStream {
+    cmd: SingleCommand,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    stdout: ChildStdout,
+    input: Vec<u8>,
+    written: usize,
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl CommandStream {
+    pub(crate) fn new(cmd: &SingleCommand, mut child: Child, input: Vec<u8>) -> Result<Self> {
+        let stdin = match child.stdin.take() {
+            None if !input.is_empty() => return Err(Error::GetStdinError),
+            stdin => stdin,
+        };
+
+        let stdout = child.stdout.take().ok_or(Error::GetStdoutError)?;
+        let deadline = cmd.timeout.map(|timeout| Box::pin(time::sleep(timeout)));
+
+        Ok(Self {
+            cmd: cmd.clone(),
+            child,
+            stdin,
+            stdout,
+            input,
+            written: 0,
+            deadline,
+        })
+    }
+
+    /// Wait for the command to exit, then return its exit status.
+    ///
+    /// The part of the output that has not been read yet is
+    /// discarded.
+    pub async fn wait(self) -> Result<ExitStatus> {
+        let Self {
+            cmd,
+            mut child,
+            stdin,
+            stdout,
+            deadline,
+            ..
+        } = self;
+
+        // closing pipes prevents the command from blocking on them
+        drop(stdin);
+        drop(stdout);
+
+        let status = match deadline {
+            None => child.wait().await?,
+            Some(deadline) => match time::timeout_at(deadline.deadline(), child.wait()).await {
+                Ok(status) => status?,
+                Err(_) => return Err(kill(&cmd, &mut child).await),
+            },
+        };
+
+        if !cmd.ignore_exit_status {
+            check_exit_status(&cmd, status, &[])?;
+        }
+
+        Ok(status)
+    }
+
+    /// Write as much input as possible without blocking, then close
+    /// the standard input once the whole input has been written.
+    fn poll_write_input(&mut self, cx: &mut Context<'_>) -> io::Result<()> {
+        while let Some(stdin) = &mut self.stdin {
+            if self.written == self.input.len() {
+                self.stdin = None;
+                break;
+            }
+
+            match Pin::new(stdin).poll_write(cx, &self.input[self.written..]) {
+                Poll::Ready(Ok(0)) => return Err(io::ErrorKind::WriteZero.into()),
+                Poll::Ready(Ok(n)) => self.written += n,
+                // the command exited without reading the whole input
+                Poll::Ready(Err(err)) if err.kind() == io::ErrorKind::BrokenPipe => {
+                    self.stdin = None;
+                }
+                Poll::Ready(Err(err)) => return Err(err),
+                Poll::Pending => break,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl AsyncRead for CommandStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        if let Some(deadline) = &mut this.deadline {
+            if deadline.as_mut().poll(cx).is_ready() {
+                let timeout = this.cmd.timeout.unwrap_or_default();
+                debug!("single command timed out after {timeout:?}, killing it");
+                kill_process_group(this.child.id());
+                if let Err(_err) = this.child.start_kill() {
+                    debug!("cannot kill single command: {_err}");
+                }
+                let err = Error::RunCommandTimeoutError(this.cmd.to_string(), timeout);
+                return Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, err)));
+            }
+        }
+
+        this.poll_write_input(cx)?;
+
+        Pin::new(&mut this.stdout).poll_read(cx, buf)
+    }
+}
+
+/// Kill the given timed out command, then return the timeout error.
+async fn kill(cmd: &SingleCommand, child: &mut Child) -> Error {
+    let timeout = cmd.timeout.unwrap_or_default();
+    debug!("single command timed out after {timeout:?}, killing it");
+    kill_process_group(child.id());
+
+    if let Err(_err) = child.kill().await {
+        debug!("cannot kill single command: {_err}");
+    }
+
+    Error::RunCommandTimeoutError(cmd.to_string(), timeout)
+}
@@ -0,0 +1,242 @@
+//! # Template
+//!
+//! Module dedicated to command templating. Commands can contain
+//! named placeholders, which are rendered using [`TemplateVars`].
+
+use std::collections::BTreeMap;
+
+use crate::{Error, Result};
+
+/// The template variables structure.
+///
+/// Holds the values substituted to the placeholders of a command, by
+/// placeholder name (see [`Command::render`]). Rendering follows
+/// these rules:
+///
+/// 1. Placeholders are variable names wrapped in braces, like
+/// `{subject}`. Names are made of alphanumeric characters, `_`, `-`
+/// and `.`, for example `{sender.address}`.
+///
+/// 2. Every placeholder must have a matching variable, otherwise an
+/// error is returned.
+///
+/// 3. Literal braces are written `{{` and `}}`, for example
+/// `awk '{{print $1}}'`.
+///
+/// 4. Values substituted into shell commands are quoted, so that
+/// they cannot inject shell code. For the same reason, placeholders
+/// of shell commands cannot be placed inside quotes or right after
+/// an escape character, like in `"{subject}"` or `\{subject}`: the
+/// value is quoted already. Values substituted into commands
+/// executed without shell (see [`SingleCommand::new_argv`]) are kept
+/// as they are, since each argument is passed as is to the program.
+/// Note that the Windows `cmd` shell expands variables like `%PATH%`
+/// even in quoted values: prefer commands executed without shell
+/// there.
+///
+/// [`Command::render`]: crate::Command::render
+/// [`SingleCommand::new_argv`]: crate::SingleCommand::new_argv
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TemplateVars(BTreeMap<String, String>);
+
+impl TemplateVars {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a variable, replacing the previous one with the same name.
+    pub fn with_var(mut self, name: impl ToString, val: impl ToString) -> Self {
+        self.insert(name, val);
+        self
+    }
+
+    /// Add a variable, replacing the previous one with the same name.
+    pub fn insert(&mut self, name: impl ToString, val: impl ToString) {
+        self.0.insert(name.to_string(), val.to_string());
+    }
+
+    /// Get the value of the given variable.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(name).map(String::as_str)
+    }
+}
+
+impl<K: ToString, V: ToString> FromIterator<(K, V)> for TemplateVars {
+    fn from_iter<T: IntoIterator<Item = (K, V)>>(vars: T) -> Self {
+        Self(
+            vars.into_iter()
+                .map(|(name, val)| (name.to_string(), val.to_string()))
+                .collect(),
+        )
+    }
+}
+
+/// Render the given template.
+///
+/// When the template is a shell command, substituted values are
+/// quoted and placeholders are rejected if the shell would not
+/// interpret the quoted value as a single word, that is inside
+/// quotes or right after an escape character.
+///
+/// In lenient mode, braces which are not part of a placeholder of a
+/// known variable are kept as they are, and quotes around
+/// placeholders are closed then reopened around the quoted value.
+pub(crate) fn render(
+    template: &str,
+    vars: &TemplateVars,
+    shell: bool,
+    lenient: bool,
+) -> Result<String> {
+    let err = |reason: &str| Error::ParseTemplateError(template.to_owned(), reason.to_owned());
+
+    let mut output = String::with_capacity(template.len());
+    let mut quoting = Quoting::Unquoted;
+    let mut rest = template;
+
+    while let Some(pos) = rest.find(['{', '}']) {
+        quoting = quoting.scan(&rest[..pos]);
+        output.push_str(&rest[..pos]);
+        let brace = &rest[pos..pos + 1];
+        rest = &rest[pos + 1..];
+
+        if lenient && !(brace == "{" && starts_with_known_placeholder(rest, vars)) {
+            quoting = quoting.scan(brace);
+            output.push_str(brace);
+            continue;
+        }
+
+        // escaped braces
+        if rest.starts_with(brace) {
+            quoting = quoting.scan(brace);
+            output.push_str(brace);
+            rest = &rest[1..];
+            continue;
+        }
+
+        if brace == "}" {
+            return Err(err("unmatched closing brace"));
+        }
+
+        let end = rest.find('}').ok_or_else(|| err("unclosed placeholder"))?;
+        let name = &rest[..end];
+        rest = &rest[end + 1..];
+
+        if !is_valid_name(name) {
+            return Err(err(&format!("invalid placeholder name {name:?}")));
+        }
+
+        if !shell {
+            let val = get_var(template, vars, name)?;
+            output.push_str(val);
+            continue;
+        }
+
+        match quoting {
+            Quoting::Unquoted => {
+                let val = get_var(template, vars, name)?;
+                output.push_str(&shell_quote(val));
+            }
+            Quoting::SingleQuoted | Quoting::DoubleQuoted if lenient => {
+                let val = get_var(template, vars, name)?;
+                let quote = if quoting == Quoting::SingleQuoted {
+                    '\''
+                } else {
+                    '"'
+                };
+                output.push(quote);
+                output.push_str(&shell_quote(val));
+                output.push(quote);
+            }
+            Quoting::Escaped => {
+                return Err(err(&format!(
+                    "placeholder {name:?} cannot follow an escape character"
+                )));
+            }
+            _ => {
+                return Err(err(&format!(
+                    "placeholder {name:?} cannot be quoted, values are quoted already"
+                )));
+            }
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Return `true` if the given text starts with the name of a known
+/// variable followed by a closing brace.
+fn starts_with_known_placeholder(text: &str, vars: &TemplateVars) -> bool {
+    text.split_once('}')
+        .is_some_and(|(name, _)| is_valid_name(name) && vars.get(name).is_some())
+}
+
+fn get_var<'a>(template: &str, vars: &'a TemplateVars, name: &str) -> Result<&'a str> {
+    vars.get(name)
+        .ok_or_else(|| Error::MissingTemplateVariableError(template.to_owned(), name.to_owned()))
+}
+
+/// The quoting context of a shell command, at a given position.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Quoting {
+    Unquoted,
+    /// Right after an escape character, outside quotes.
+    Escaped,
+    SingleQuoted,
+    DoubleQuoted,
+    /// Right after an escape character, inside double quotes.
+    DoubleQuotedEscaped,
+}
+
+impl Quoting {
+    /// Compute the quoting context after the given shell text.
+    ///
+    /// POSIX shells support single quotes, double quotes and the
+    /// backslash escape character, while the Windows `cmd` shell
+    /// only supports double quotes and the caret escape character.
+    fn scan(self, text: &str) -> Self {
+        let windows = crate::is_windows_shell();
+
+        text.chars().fold(self, |quoting, c| match (quoting, c) {
+            (Self::Unquoted, '^') if windows => Self::Escaped,
+            (Self::Unquoted, '\\') if !windows => Self::Escaped,
+            (Self::Unquoted, '\'') if !windows => Self::SingleQuoted,
+            (Self::Unquoted, '"') => Self::DoubleQuoted,
+            (Self::Unquoted, _) | (Self::Escaped, _) => Self::Unquoted,
+            (Self::SingleQuoted, '\'') => Self::Unquoted,
+            (Self::SingleQuoted, _) => Self::SingleQuoted,
+            (Self::DoubleQuoted, '"') => Self::Unquoted,
+            (Self::DoubleQuoted, '\\') if !windows => Self::DoubleQuotedEscaped,
+            (Self::DoubleQuoted, _) | (Self::DoubleQuotedEscaped, _) => Self::DoubleQuoted,
+        })
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+}
+
+/// Quote the given value for the shell used to execute commands.
+///
+/// On Windows, the value is wrapped in double quotes. Since `cmd`
+/// still expands variables like `%PATH%` inside double quotes,
+/// commands executed without shell are safer there.
+pub(crate) fn shell_quote(val: &str) -> String {
+    if crate::is_windows_shell() {
+        format!("\"{}\"", val.replace('"', "\"\""))
+    } else {
+        posix_quote(val)
+    }
+}
+
+/// Quote the given value for POSIX shells.
+///
+/// The value is wrapped in single quotes, inside which no character
+/// has a special meaning. Single quotes are ended, escaped then
+/// started again.
+pub(crate) fn posix_quote(val: &str) -> String {
+    format!("'{}'", val.replace('\'', r"'\''"))
+}
@@ -0,0 +1,145 @@
+use std::{env, time::Duration};
+
+use process::{Command, Error, SingleCommand};
+
+#[tokio::test]
+async fn test_timeout() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let cmd = SingleCommand::from("sleep 10").with_timeout(Duration::from_millis(100));
+    match cmd.run().await.unwrap_err() {
+        Error::RunCommandTimeoutError(cmd, timeout) => {
+            assert_eq!(cmd, "sleep 10");
+            assert_eq!(timeout, Duration::from_millis(100));
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+
+    let cmd = Command::from(vec!["echo hello", "cat"]).with_timeout(Duration::from_secs(10));
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "hello\n");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn test_timeout_kills_background_processes() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let marker = env::temp_dir().join(format!("process-lib-timeout-{}", std::process::id()));
+    let spawn = format!("(sleep 1; touch {}) &", marker.display());
+
+    // the background process outlives the shell in the second case,
+    // holding its output open
+    for cmd in [format!("{spawn} wait"), format!("{spawn} echo spawned")] {
+        let cmd = SingleCommand::from(cmd).with_timeout(Duration::from_millis(200));
+        let err = cmd.run().await.unwrap_err();
+        assert!(matches!(err, Error::RunCommandTimeoutError(..)));
+    }
+
+    tokio::time::sleep(Duration::from_millis(1500)).await;
+    assert!(!marker.exists());
+}
+
+#[tokio::test]
+async fn test_env() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let cmd = SingleCommand::from("echo $PROCESS_LIB_TEST").with_env("PROCESS_LIB_TEST", "hello");
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "hello\n");
+
+    env::set_var("PROCESS_LIB_TEST_INHERITED", "inherited");
+
+    let cmd = SingleCommand::from("echo \"$PROCESS_LIB_TEST_INHERITED,$PROCESS_LIB_TEST\"")
+        .with_env_cleared(true)
+        .with_env("PROCESS_LIB_TEST", "hello");
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, ",hello\n");
+}
+
+#[tokio::test]
+async fn test_current_dir() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let dir = env::temp_dir().canonicalize().unwrap();
+    let cmd = SingleCommand::from("pwd -P").with_current_dir(&dir);
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out.trim_end(), dir.to_string_lossy());
+}
+
+#[tokio::test]
+async fn test_stderr_and_exit_status() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let cmd = SingleCommand::from("echo out; echo err >&2");
+    let out = cmd.run().await.unwrap();
+    assert_eq!(out.to_string_lossy(), "out\n");
+    assert_eq!(out.stderr_to_string_lossy(), "err\n");
+    assert_eq!(out.code(), Some(0));
+
+    let cmd = SingleCommand::from("echo err >&2; exit 3");
+    match cmd.run().await.unwrap_err() {
+        Error::GetExitStatusCodeNonZeroError(_, status, err) => {
+            assert_eq!(status, 3);
+            assert_eq!(err, "err\n");
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+
+    let out = cmd.with_exit_status_ignored(true).run().await.unwrap();
+    assert_eq!(out.code(), Some(3));
+    assert_eq!(out.stderr_to_string_lossy(), "err\n");
+
+    let cmd = Command::from(vec!["echo a >&2; echo hello", "echo b >&2; cat"]);
+    let out = cmd.run().await.unwrap();
+    assert_eq!(out.to_string_lossy(), "hello\n");
+    assert_eq!(out.stderr_to_string_lossy(), "a\nb\n");
+}
+
+#[tokio::test]
+async fn test_large_input() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    // bigger than pipe buffers, so that input and output need to be
+    // handled concurrently
+    let input = "hello\n".repeat(100_000);
+    let out = SingleCommand::from("cat").run_with(&input).await.unwrap();
+    assert_eq!(out.len(), input.len());
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn test_serde() {
+    let cmd: Command = serde_json::from_str(r#""echo hello""#).unwrap();
+    assert_eq!(cmd, Command::from("echo hello"));
+    assert_eq!(serde_json::to_string(&cmd).unwrap(), r#""echo hello""#);
+
+    let cmd: Command = serde_json::from_str(
+        r#"{"cmd":"echo hello","timeout":1.5,"env":{"KEY":"val"},"clear-env":true,"current-dir":"/tmp","ignore-exit-status":true}"#,
+    )
+    .unwrap();
+    let expected = Command::from("echo hello")
+        .with_timeout(Duration::from_millis(1500))
+        .with_env("KEY", "val")
+        .with_env_cleared(true)
+        .with_current_dir("/tmp")
+        .with_exit_status_ignored(true);
+    assert_eq!(cmd, expected);
+
+    let cmd: Command =
+        serde_json::from_str(r#"["echo hello", {"cmd":"cat","timeout":10}]"#).unwrap();
+    assert_eq!(
+        serde_json::to_string(&cmd).unwrap(),
+        r#"["echo hello",{"cmd":"cat","timeout":10.0}]"#
+    );
+
+    let cmd: Command = serde_json::from_str(r#"{"cmd":"echo","args":["hello world"]}"#).unwrap();
+    assert_eq!(
+        cmd,
+        Command::from(SingleCommand::new_argv("echo", ["hello world"]))
+    );
+    assert_eq!(
+        serde_json::to_string(&cmd).unwrap(),
+        r#"{"cmd":"echo","args":["hello world"]}"#
+    );
+}
@@ -0,0 +1,51 @@
+use std::time::Duration;
+
+use process::{Command, SingleCommand};
+use tokio::io::AsyncReadExt;
+
+#[tokio::test]
+async fn test_stream() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let mut stream = SingleCommand::from("seq 1 100000").stream().unwrap();
+    let mut lines = 0;
+    let mut buf = [0; 4096];
+
+    loop {
+        let n = stream.read(&mut buf).await.unwrap();
+        if n == 0 {
+            break;
+        }
+        lines += buf[..n].iter().filter(|b| **b == b'\n').count();
+    }
+
+    assert_eq!(lines, 100_000);
+    assert!(stream.wait().await.unwrap().success());
+}
+
+#[tokio::test]
+async fn test_stream_pipeline() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let input = "hello\n".repeat(100_000);
+    let cmd = Command::from(vec!["cat", "tr a-z A-Z"]);
+    let mut stream = cmd.stream_with(&input).await.unwrap();
+
+    let mut out = String::new();
+    stream.read_to_string(&mut out).await.unwrap();
+    assert_eq!(out, input.to_uppercase());
+    assert!(stream.wait().await.unwrap().success());
+}
+
+#[tokio::test]
+async fn test_stream_timeout() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let cmd = SingleCommand::from("echo hello; sleep 10").with_timeout(Duration::from_millis(200));
+    let mut stream = cmd.stream().unwrap();
+
+    let mut out = Vec::new();
+    let err = stream.read_to_end(&mut out).await.unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::TimedOut);
+    assert_eq!(out, b"hello\n");
+}
@@ -0,0 +1,176 @@
+use std::env;
+
+use process::{Command, Error, SingleCommand, TemplateVars};
+
+#[tokio::test]
+async fn test_render() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let vars = TemplateVars::new()
+        .with_var("subject", "Hello, world!")
+        .with_var("sender.address", "alice@localhost");
+
+    let cmd = Command::from("echo {subject} {sender.address}")
+        .render(&vars)
+        .unwrap();
+    assert_eq!(cmd.to_string(), "echo 'Hello, world!' 'alice@localhost'");
+
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "Hello, world! alice@localhost\n");
+
+    // placeholders are rendered in all pipeline commands
+    let cmd = Command::from(vec!["echo {subject}", "sed s/world/{sender.address}/"])
+        .render(&vars)
+        .unwrap();
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "Hello, alice@localhost!\n");
+
+    // literal braces are escaped
+    let cmd = Command::from("echo {subject} | awk '{{print $1}}'")
+        .render(&vars)
+        .unwrap();
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "Hello,\n");
+}
+
+#[tokio::test]
+async fn test_render_prevents_injection() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let subjects = [
+        "$(echo injected)",
+        "`echo injected`",
+        "'; echo injected; '",
+        "\"; echo injected; \"",
+        "a\nb",
+    ];
+
+    for subject in subjects {
+        let vars = TemplateVars::new().with_var("subject", subject);
+        let cmd = Command::from("printf %s {subject}").render(&vars).unwrap();
+        let out = cmd.run().await.unwrap().to_string_lossy();
+        assert_eq!(out, subject);
+
+        // quoted or escaped placeholders would unquote the value
+        for template in [
+            "printf %s \"{subject}\"",
+            "printf %s '{subject}'",
+            "printf %s \"a {subject} b\"",
+            "printf %s \\{subject}",
+        ] {
+            match Command::from(template).render(&vars).unwrap_err() {
+                Error::ParseTemplateError(t, _) => assert_eq!(t, template),
+                err => panic!("unexpected error: {err:?}"),
+            }
+        }
+    }
+
+    // placeholders can follow closed quotes and escaped characters
+    let vars = TemplateVars::new().with_var("subject", "$(echo injected)");
+    let cmd = Command::from("printf '%s|' \"a\\\"b\" \\' {subject}")
+        .render(&vars)
+        .unwrap();
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "a\"b|'|$(echo injected)|");
+}
+
+#[tokio::test]
+async fn test_render_lenient() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let vars = TemplateVars::new().with_var("subject", "'\"; echo injected; \"'");
+
+    // quoted placeholders are rendered without unquoting the value
+    for template in [
+        "printf %s {subject}",
+        "printf %s \"{subject}\"",
+        "printf %s '{subject}'",
+    ] {
+        let cmd = Command::from(template).render_lenient(&vars).unwrap();
+        let out = cmd.run().await.unwrap().to_string_lossy();
+        assert_eq!(out, "'\"; echo injected; \"'");
+    }
+
+    let cmd = Command::from("printf %s \"a {subject} b\"")
+        .render_lenient(&vars)
+        .unwrap();
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "a '\"; echo injected; \"' b");
+
+    // other braces are kept as they are
+    let vars = TemplateVars::new().with_var("subject", "Hello, world!");
+    let cmd = Command::from("echo {subject} {unknown} {} { | awk '{print $1}'")
+        .render_lenient(&vars)
+        .unwrap();
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "Hello,\n");
+
+    // escaped placeholders are still rejected
+    match Command::from("printf %s \\{subject}")
+        .render_lenient(&vars)
+        .unwrap_err()
+    {
+        Error::ParseTemplateError(..) => (),
+        err => panic!("unexpected error: {err:?}"),
+    }
+}
+
+#[test]
+fn test_render_errors() {
+    let vars: TemplateVars = [("subject", "hello")].into_iter().collect();
+
+    match SingleCommand::from("echo {sender}")
+        .render(&vars)
+        .unwrap_err()
+    {
+        Error::MissingTemplateVariableError(template, name) => {
+            assert_eq!(template, "echo {sender}");
+            assert_eq!(name, "sender");
+        }
+        err => panic!("unexpected error: {err:?}"),
+    }
+
+    for template in [
+        "echo {subject",
+        "echo subject}",
+        "echo {}",
+        "echo {sub ject}",
+    ] {
+        match SingleCommand::from(template).render(&vars).unwrap_err() {
+            Error::ParseTemplateError(t, _) => assert_eq!(t, template),
+            err => panic!("unexpected error: {err:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_argv() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    env::set_var("PROCESS_LIB_TEST_ARGV", "expanded");
+
+    let cmd = SingleCommand::new_argv("printf", ["%s|%s", "$PROCESS_LIB_TEST_ARGV", "{subject}"]);
+    assert_eq!(
+        cmd.to_string(),
+        "printf '%s|%s' '$PROCESS_LIB_TEST_ARGV' '{subject}'"
+    );
+
+    // values are neither quoted nor interpreted by a shell
+    let vars = TemplateVars::new().with_var("subject", "'; echo injected");
+    let cmd = cmd.render(&vars).unwrap();
+    assert_eq!(
+        cmd.args().unwrap(),
+        ["%s|%s", "$PROCESS_LIB_TEST_ARGV", "'; echo injected"]
+    );
+
+    let out = cmd.run().await.unwrap().to_string_lossy();
+    assert_eq!(out, "$PROCESS_LIB_TEST_ARGV|'; echo injected");
+
+    let out = Command::from(SingleCommand::new_argv("echo", ["hello"]))
+        .replace_placeholder("hello", "world")
+        .run()
+        .await
+        .unwrap()
+        .to_string_lossy();
+    assert_eq!(out, "world\n");
+}
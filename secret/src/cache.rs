@@ -0,0 +1,157 @@
+//! # Cache
+//!
+//! Module dedicated to the secret cache. The cache is disabled by
+//! default, and can be enabled globally with a time-to-live using
+//! [`enable`].
+//!
+//! Cached values are stored as [`SecretString`], which are zeroized
+//! when dropped. Expired values are evicted from the cache, and so
+//! zeroized, as soon as the cache is accessed again. Concurrent
+//! lookups of the same secret are coalesced, so that a command like
+//! `pass show` is run (and prompts for a passphrase) only once.
+
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use secrecy::SecretString;
+use tokio::sync::{Mutex as AsyncMutex, MutexGuard as AsyncMutexGuard};
+
+use crate::Secret;
+
+/// The time-to-live of cached values, [`None`] when the cache is
+/// disabled.
+static TTL: Mutex<Option<Duration>> = Mutex::new(None);
+
+/// Cache slots, indexed by secret.
+static SLOTS: Mutex<Vec<(Secret, Slot)>> = Mutex::new(Vec::new());
+
+/// Enable the cache, with the given time-to-live.
+///
+/// Values cached before calling this function keep their previous
+/// time-to-live.
+pub fn enable(ttl: Duration) {
+    debug!("enabling secret cache with ttl {ttl:?}");
+    *TTL.lock().unwrap_or_else(PoisonError::into_inner) = Some(ttl);
+}
+
+/// Disable the cache, and clear all cached values.
+pub fn disable() {
+    debug!("disabling secret cache");
+    *TTL.lock().unwrap_or_else(PoisonError::into_inner) = None;
+    clear();
+}
+
+/// Return the time-to-live of cached values, or [`None`] if the cache
+/// is disabled.
+pub fn ttl() -> Option<Duration> {
+    *TTL.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Clear all cached values.
+///
+/// The decrypted entries of encrypted files are cleared as well, see
+/// [`EncryptedFile::clear_cache`](crate::EncryptedFile::clear_cache).
+pub fn clear() {
+    SLOTS.lock().unwrap_or_else(PoisonError::into_inner).clear();
+
+    #[cfg(feature = "encrypted-file")]
+    crate::EncryptedFile::clear_cache();
+}
+
+/// Return the number of values currently cached.
+///
+/// Expired values are evicted before being counted.
+pub fn len() -> usize {
+    let mut slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+    evict_expired(&mut slots);
+    slots.len()
+}
+
+/// Remove the cached value of the given secret.
+pub(crate) fn invalidate(secret: &Secret) {
+    SLOTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .retain(|(s, _)| s != secret);
+}
+
+/// Find or create the cache slot of the given secret.
+///
+/// Returns [`None`] if the cache is disabled or if the secret does
+/// not need to be cached.
+pub(crate) fn slot(secret: &Secret) -> Option<Slot> {
+    ttl()?;
+
+    if matches!(secret, Secret::Raw(_) | Secret::Undefined) {
+        return None;
+    }
+
+    let mut slots = SLOTS.lock().unwrap_or_else(PoisonError::into_inner);
+    evict_expired(&mut slots);
+
+    if let Some((_, slot)) = slots.iter().find(|(s, _)| s == secret) {
+        return Some(slot.clone());
+    }
+
+    let slot = Slot(Arc::new(AsyncMutex::new(CachedValue::default())));
+    slots.push((secret.clone(), slot.clone()));
+    Some(slot)
+}
+
+/// Drop the expired values, then remove the slots left empty and
+/// unused.
+///
+/// Slots are only handed out while the slots lock is held, so a slot
+/// referenced only by the cache cannot be reused meanwhile.
+fn evict_expired(slots: &mut Vec<(Secret, Slot)>) {
+    slots.retain(|(_, slot)| {
+        let Ok(mut value) = slot.0.try_lock() else {
+            return true;
+        };
+
+        value.drop_expired();
+        value.value.is_some() || Arc::strong_count(&slot.0) > 1
+    });
+}
+
+/// The cache slot of a secret.
+#[derive(Clone, Debug)]
+pub(crate) struct Slot(Arc<AsyncMutex<CachedValue>>);
+
+impl Slot {
+    /// Lock the slot, so that concurrent lookups wait for the value
+    /// to be cached.
+    pub(crate) async fn lock(&self) -> AsyncMutexGuard<'_, CachedValue> {
+        self.0.lock().await
+    }
+}
+
+/// The cached value of a secret.
+#[derive(Debug, Default)]
+pub(crate) struct CachedValue {
+    value: Option<(SecretString, Instant)>,
+}
+
+impl CachedValue {
+    /// Get the cached value, if not expired.
+    pub(crate) fn get(&mut self) -> Option<SecretString> {
+        self.drop_expired();
+        self.value.as_ref().map(|(value, _)| value.clone())
+    }
+
+    /// Cache the given value, using the current time-to-live.
+    pub(crate) fn set(&mut self, value: SecretString) {
+        let ttl = ttl().unwrap_or_default();
+        self.value = Some((value, Instant::now() + ttl));
+    }
+
+    fn drop_expired(&mut self) {
+        if matches!(&self.value, Some((_, expires_at)) if *expires_at <= Instant::now()) {
+            debug!("dropping expired cached secret");
+            self.value = None;
+        }
+    }
+}
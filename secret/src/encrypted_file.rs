@@ -0,0 +1,261 @@
+//! # Encrypted file
+//!
+//! Module dedicated to secrets stored in an
+//! [age](https://age-encryption.org)-encrypted file. The decrypted
+//! file is a JSON object mapping entry names to secret values.
+//!
+//! Only age encryption is supported: PGP-encrypted files need to be
+//! converted first, or can be read with a command-based secret (for
+//! example `gpg --decrypt`). Native PGP decryption cannot rely on
+//! `pgp-lib`, which itself depends on this crate.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::BufReader,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use age::{armor::ArmoredReader, scrypt, Decryptor, Identity, IdentityFile};
+use log::debug;
+use secrecy::{ExposeSecret, SecretString};
+use shellexpand_utils::shellexpand_path;
+use tokio::{sync::Mutex as AsyncMutex, task};
+
+use crate::{Error, Result, Secret};
+
+/// Decrypted entries of an encrypted file, zeroized when dropped.
+type Entries = Arc<HashMap<String, SecretString>>;
+
+/// Decrypted encrypted files, indexed by their path and the way they
+/// were unlocked.
+static CACHE: Mutex<Vec<(CacheKey, Entries)>> = Mutex::new(Vec::new());
+
+/// Lock held while decrypting files, so that concurrent lookups
+/// decrypt a file only once.
+static DECRYPT_LOCK: AsyncMutex<()> = AsyncMutex::const_new(());
+
+/// The key of decrypted files in the cache.
+///
+/// The unlock method is part of the key, so that an encrypted file
+/// secret with a wrong passphrase or identity file cannot read the
+/// entries decrypted by another one.
+#[derive(Debug, Eq, PartialEq)]
+struct CacheKey {
+    path: PathBuf,
+    passphrase: Option<Secret>,
+    identity_file: Option<PathBuf>,
+}
+
+/// The encrypted file secret.
+///
+/// The file is decrypted either with a passphrase or with an age
+/// identity file (ASCII-armored files are supported). Once
+/// decrypted, its entries are cached in memory until
+/// [`EncryptedFile::clear_cache`] or [`cache::clear`] is called, so
+/// that the file is decrypted only once. Cached entries are zeroized
+/// when cleared.
+///
+/// [`cache::clear`]: crate::cache::clear
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub struct EncryptedFile {
+    /// The path of the encrypted file.
+    ///
+    /// The path is shell-expanded.
+    pub path: PathBuf,
+
+    /// The name of the entry holding the secret value.
+    pub key: String,
+
+    /// The passphrase the file is encrypted with.
+    ///
+    /// The passphrase is a secret itself, so it can be retrieved
+    /// from a command or from a keyring entry.
+    #[cfg_attr(
+        feature = "derive",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub passphrase: Option<Box<Secret>>,
+
+    /// The path of the age identity file the file is encrypted for.
+    #[cfg_attr(
+        feature = "derive",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub identity_file: Option<PathBuf>,
+}
+
+impl EncryptedFile {
+    /// Create a new encrypted file secret from the given file path
+    /// and entry name.
+    ///
+    /// The passphrase or the identity file still need to be set.
+    pub fn new(path: impl Into<PathBuf>, key: impl ToString) -> Self {
+        Self {
+            path: path.into(),
+            key: key.to_string(),
+            passphrase: None,
+            identity_file: None,
+        }
+    }
+
+    pub fn with_passphrase(mut self, passphrase: impl Into<Secret>) -> Self {
+        self.passphrase = Some(Box::new(passphrase.into()));
+        self
+    }
+
+    pub fn with_identity_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.identity_file = Some(path.into());
+        self
+    }
+
+    /// Clear the decrypted entries of all encrypted files.
+    pub fn clear_cache() {
+        debug!("clearing decrypted secrets files");
+        CACHE.lock().unwrap_or_else(PoisonError::into_inner).clear();
+    }
+
+    /// Get the secret value of the entry.
+    pub async fn get_secret(&self) -> Result<String> {
+        self.find_secret()
+            .await?
+            .ok_or_else(|| Error::FindEncryptedFileEntryError(self.key.clone(), self.path.clone()))
+    }
+
+    /// Find the secret value of the entry.
+    ///
+    /// Like [`EncryptedFile::get_secret`], but returns [`None`] if
+    /// the entry does not exist.
+    pub async fn find_secret(&self) -> Result<Option<String>> {
+        let entries = self.entries().await?;
+        let entry = entries.get(&self.key);
+        Ok(entry.map(|entry| entry.expose_secret().to_owned()))
+    }
+
+    /// Get the decrypted entries of the file, from the cache if
+    /// already decrypted.
+    ///
+    /// The file is decrypted on the blocking thread pool, since the
+    /// scrypt key derivation is deliberately slow.
+    async fn entries(&self) -> Result<Entries> {
+        let key = self.cache_key();
+
+        if let Some(entries) = cached_entries(&key) {
+            return Ok(entries);
+        }
+
+        // the passphrase is retrieved before locking, since it can be
+        // an entry of another encrypted file
+        let passphrase = match (&self.identity_file, &self.passphrase) {
+            (None, Some(passphrase)) => Some(Box::pin(passphrase.get_secret()).await?),
+            _ => None,
+        };
+
+        let _lock = DECRYPT_LOCK.lock().await;
+
+        // the file may have been decrypted by someone else while
+        // getting the passphrase
+        if let Some(entries) = cached_entries(&key) {
+            return Ok(entries);
+        }
+
+        debug!("decrypting secrets file {}", self.path.display());
+
+        let path = self.path.clone();
+        let identity_file = self.identity_file.clone();
+        let entries = task::spawn_blocking(move || {
+            let identities = identities(&path, identity_file.as_deref(), passphrase)?;
+            decrypt(&path, &identities)
+        })
+        .await
+        .map_err(|err| Error::JoinDecryptTaskError(err, self.path.clone()))??;
+
+        let entries = Arc::new(entries);
+        CACHE
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((key, entries.clone()));
+
+        Ok(entries)
+    }
+
+    /// Build the cache key of the file, from its path and the way it
+    /// is unlocked.
+    fn cache_key(&self) -> CacheKey {
+        CacheKey {
+            path: self.path.clone(),
+            passphrase: self.passphrase.as_deref().cloned(),
+            identity_file: self.identity_file.clone(),
+        }
+    }
+}
+
+/// Get the cached entries matching the given key, if any.
+fn cached_entries(key: &CacheKey) -> Option<Entries> {
+    CACHE
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, entries)| entries.clone())
+}
+
+impl From<EncryptedFile> for Secret {
+    fn from(file: EncryptedFile) -> Self {
+        Secret::EncryptedFile(file)
+    }
+}
+
+/// Build the identities able to decrypt the file at the given path,
+/// from the identity file if any, otherwise from the passphrase.
+fn identities(
+    path: &Path,
+    identity_file: Option<&Path>,
+    passphrase: Option<SecretString>,
+) -> Result<Vec<Box<dyn Identity>>> {
+    if let Some(identity_file) = identity_file {
+        let identity_file = shellexpand_path(identity_file)
+            .to_string_lossy()
+            .to_string();
+        return IdentityFile::from_file(identity_file.clone())
+            .map_err(|err| Error::ReadIdentityFileError(err, identity_file.clone()))?
+            .into_identities()
+            .map_err(|err| Error::ParseIdentityFileError(err, identity_file));
+    }
+
+    if let Some(passphrase) = passphrase {
+        let identity = scrypt::Identity::new(passphrase);
+        return Ok(vec![Box::new(identity)]);
+    }
+
+    Err(Error::MissingEncryptedFileUnlockError(path.to_owned()))
+}
+
+/// Decrypt the file at the given path, then parse its entries.
+fn decrypt(path: &Path, identities: &[Box<dyn Identity>]) -> Result<HashMap<String, SecretString>> {
+    let path = shellexpand_path(path);
+    let path = path.as_path();
+    let file =
+        fs::File::open(path).map_err(|err| Error::OpenEncryptedFileError(err, path.into()))?;
+    let reader = ArmoredReader::new(BufReader::new(file));
+
+    let decryptor =
+        Decryptor::new(reader).map_err(|err| Error::DecryptFileError(err, path.into()))?;
+    let decrypted = decryptor
+        .decrypt(identities.iter().map(|i| i.as_ref() as &dyn Identity))
+        .map_err(|err| Error::DecryptFileError(err, path.into()))?;
+
+    let entries: HashMap<String, String> = serde_json::from_reader(decrypted)
+        .map_err(|err| Error::ParseEncryptedFileError(err, path.into()))?;
+
+    Ok(entries
+        .into_iter()
+        .map(|(key, val)| (key, SecretString::from(val)))
+        .collect())
+}
@@ -1,4 +1,6 @@
-use std::result;
+#[cfg(any(feature = "command", feature = "encrypted-file"))]
+use std::path::PathBuf;
+use std::{env::VarError, result};
 use thiserror::Error;
 
 /// The global `Result` alias of the library.
@@ -18,4 +20,54 @@ pub enum Error {
     #[cfg(feature = "keyring")]
     #[error("error while using secret from keyring")]
     KeyringError(#[source] keyring::Error),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot open encrypted file {1}")]
+    OpenEncryptedFileError(#[source] std::io::Error, PathBuf),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot decrypt encrypted file {1}")]
+    DecryptFileError(#[source] age::DecryptError, PathBuf),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot decrypt encrypted file {1}")]
+    JoinDecryptTaskError(#[source] tokio::task::JoinError, PathBuf),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot parse entries of encrypted file {1}")]
+    ParseEncryptedFileError(#[source] serde_json::Error, PathBuf),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot find entry {0} in encrypted file {1}")]
+    FindEncryptedFileEntryError(String, PathBuf),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot unlock encrypted file {0}: missing passphrase or identity file")]
+    MissingEncryptedFileUnlockError(PathBuf),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot read identity file {1}")]
+    ReadIdentityFileError(#[source] std::io::Error, String),
+    #[cfg(feature = "encrypted-file")]
+    #[error("cannot parse identity file {1}")]
+    ParseIdentityFileError(#[source] age::DecryptError, String),
+    #[error("cannot find secret provider {0}")]
+    FindProviderError(String),
+    #[error("cannot find secret {1} from provider {0}")]
+    FindSecretFromProviderError(String, String),
+    #[error("cannot change secret: provider {0} is read-only")]
+    ReadOnlyProviderError(&'static str),
+    #[error("cannot get secret from environment variable {1}")]
+    GetSecretFromEnvError(#[source] VarError, String),
+    #[cfg(feature = "command")]
+    #[error("cannot set secret using command")]
+    SetSecretIntoCommand(#[source] process::Error),
+    #[cfg(feature = "command")]
+    #[error("invalid pass key {0}")]
+    InvalidPassKeyError(String),
+    #[cfg(feature = "command")]
+    #[error("cannot find pass recipients (.gpg-id) in {0}")]
+    FindPassRecipientsError(PathBuf),
+    #[cfg(feature = "command")]
+    #[error("cannot read pass recipients from {1}")]
+    ReadPassRecipientsError(#[source] std::io::Error, PathBuf),
+    #[cfg(feature = "command")]
+    #[error("cannot create pass directory {1}")]
+    CreatePassDirError(#[source] std::io::Error, PathBuf),
+    #[cfg(feature = "command")]
+    #[error("cannot delete pass entry {1}")]
+    DeletePassEntryError(#[source] std::io::Error, PathBuf),
 }
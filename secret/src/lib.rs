@@ -2,10 +2,19 @@
 //!
 //! The core concept of this library is to abstract the concept of
 //! secret. A secret can be retrieved either from a raw string, from a
-//! command or from a keyring entry. The associated structure is
-//! [`Secret`].
+//! command, from a keyring entry, from an encrypted file or from a
+//! registered provider. The associated structure is [`Secret`].
+//!
+//! Custom providers can be registered using [`register_provider`].
+//!
+//! Secret values can be cached in memory for a limited time, see
+//! [`cache::enable`].
 
+pub mod cache;
+#[cfg(feature = "encrypted-file")]
+mod encrypted_file;
 mod error;
+pub mod provider;
 
 #[cfg(feature = "keyring")]
 pub use keyring;
@@ -16,14 +25,22 @@
 pub use process;
 #[cfg(feature = "command")]
 use process::Command;
+pub use secrecy::{self, ExposeSecret, SecretString};
 
+#[cfg(feature = "encrypted-file")]
 #[doc(inline)]
-pub use crate::error::{Error, Result};
+pub use crate::encrypted_file::EncryptedFile;
+#[doc(inline)]
+pub use crate::{
+    error::{Error, Result},
+    provider::{register_provider, ProviderEntry, SecretProvider},
+};
 
 /// The secret.
 ///
 /// A secret can be retrieved either from a raw string, from a shell
-/// command or from a keyring entry.
+/// command, from a keyring entry, from an encrypted file or from a
+/// registered provider.
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
 #[cfg_attr(
     feature = "derive",
@@ -46,6 +63,16 @@ pub enum Secret {
     #[cfg_attr(feature = "derive", serde(rename = "keyring"))]
     KeyringEntry(KeyringEntry),
 
+    /// The secret is contained in the given entry of an encrypted
+    /// file. The file is decrypted once, then cached in memory for
+    /// the process lifetime.
+    #[cfg(feature = "encrypted-file")]
+    EncryptedFile(EncryptedFile),
+
+    /// The secret is contained in the given provider at the given
+    /// key, see [`provider`].
+    Provider(ProviderEntry),
+
     /// The secret is not defined.
     #[default]
     #[cfg_attr(feature = "derive", serde(skip_serializing))]
@@ -75,6 +102,17 @@ pub fn new_keyring_entry(entry: KeyringEntry) -> Self {
         Self::KeyringEntry(entry)
     }
 
+    /// Create a new secret from the given encrypted file entry.
+    #[cfg(feature = "encrypted-file")]
+    pub fn new_encrypted_file(file: EncryptedFile) -> Self {
+        Self::EncryptedFile(file)
+    }
+
+    /// Create a new secret from the given provider name and key.
+    pub fn new_provider(provider: impl ToString, key: impl ToString) -> Self {
+        Self::Provider(ProviderEntry::new(provider, key))
+    }
+
     /// Try to create a new secret from the given entry.
     #[cfg(feature = "keyring")]
     pub fn try_new_keyring_entry(
@@ -94,7 +132,38 @@ pub fn is_undefined(&self) -> bool {
     /// The command-based secret execute its shell command and returns
     /// the output, and the keyring-based secret retrieves the value
     /// from the global keyring using its inner key.
+    ///
+    /// The value is taken from the cache when enabled, see
+    /// [`cache::enable`].
+    ///
+    /// The returned value is not zeroized when dropped, prefer
+    /// [`Secret::get_secret`].
     pub async fn get(&self) -> Result<String> {
+        Ok(self.get_secret().await?.expose_secret().to_owned())
+    }
+
+    /// Get the secret value, as a [`SecretString`] zeroized when
+    /// dropped.
+    ///
+    /// Like [`Secret::get`], but the value is not exposed.
+    pub async fn get_secret(&self) -> Result<SecretString> {
+        let Some(slot) = cache::slot(self) else {
+            return Ok(self.get_uncached().await?.into());
+        };
+
+        let mut cached = slot.lock().await;
+
+        if let Some(secret) = cached.get() {
+            debug!("found secret from cache");
+            return Ok(secret);
+        }
+
+        let secret = SecretString::from(self.get_uncached().await?);
+        cached.set(secret.clone());
+        Ok(secret)
+    }
+
+    async fn get_uncached(&self) -> Result<String> {
         match self {
             Self::Raw(raw) => Ok(raw.clone()),
             #[cfg(feature = "command")]
@@ -112,15 +181,52 @@ pub async fn get(&self) -> Result<String> {
             Self::KeyringEntry(entry) => {
                 Ok(entry.get_secret().await.map_err(Error::KeyringError)?)
             }
+            #[cfg(feature = "encrypted-file")]
+            Self::EncryptedFile(file) => file.get_secret().await,
+            Self::Provider(entry) => entry.get_secret().await,
             Self::Undefined => Err(Error::GetUndefinedSecretError),
         }
     }
 
     /// Find the secret value.
     ///
-    /// Like [`get`], but returns [`None`] if the secret value is not
-    /// found or undefined.
+    /// Like [`Secret::get`], but returns [`None`] if the secret value
+    /// is not found or undefined.
+    ///
+    /// The returned value is not zeroized when dropped, prefer
+    /// [`Secret::find_secret`].
     pub async fn find(&self) -> Result<Option<String>> {
+        let secret = self.find_secret().await?;
+        Ok(secret.map(|secret| secret.expose_secret().to_owned()))
+    }
+
+    /// Find the secret value, as a [`SecretString`] zeroized when
+    /// dropped.
+    ///
+    /// Like [`Secret::find`], but the value is not exposed. Values
+    /// not found are not cached.
+    pub async fn find_secret(&self) -> Result<Option<SecretString>> {
+        let Some(slot) = cache::slot(self) else {
+            return Ok(self.find_uncached().await?.map(Into::into));
+        };
+
+        let mut cached = slot.lock().await;
+
+        if let Some(secret) = cached.get() {
+            debug!("found secret from cache");
+            return Ok(Some(secret));
+        }
+
+        let secret = self.find_uncached().await?.map(SecretString::from);
+
+        if let Some(secret) = &secret {
+            cached.set(secret.clone());
+        }
+
+        Ok(secret)
+    }
+
+    async fn find_uncached(&self) -> Result<Option<String>> {
         match self {
             Self::Raw(secret) => Ok(Some(secret.clone())),
             #[cfg(feature = "command")]
@@ -137,17 +243,23 @@ pub async fn find(&self) -> Result<Option<String>> {
             Self::KeyringEntry(entry) => {
                 Ok(entry.find_secret().await.map_err(Error::KeyringError)?)
             }
+            #[cfg(feature = "encrypted-file")]
+            Self::EncryptedFile(file) => file.find_secret().await,
+            Self::Provider(entry) => entry.find_secret().await,
             Self::Undefined => Ok(None),
         }
     }
 
     /// Change the secret value.
     ///
-    /// This is only applicable for raw secrets and keyring-based
-    /// secrets. A secret value cannot be changed for command-base
-    /// secrets, since the value is the output of the command.
+    /// This is only applicable for raw secrets, keyring-based secrets
+    /// and provider-based secrets. A secret value cannot be changed
+    /// for command-based secrets, since the value is the output of
+    /// the command, nor for encrypted file secrets, which are
+    /// read-only.
     pub async fn set(&mut self, secret: impl AsRef<str>) -> Result<String> {
         let secret = secret.as_ref();
+        cache::invalidate(self);
 
         match self {
             Self::Raw(prev) => {
@@ -164,6 +276,13 @@ pub async fn set(&mut self, secret: impl AsRef<str>) -> Result<String> {
                     .await
                     .map_err(Error::KeyringError)?;
             }
+            #[cfg(feature = "encrypted-file")]
+            Self::EncryptedFile(_) => {
+                debug!("cannot change value of encrypted file secret");
+            }
+            Self::Provider(entry) => {
+                entry.set_secret(secret).await?;
+            }
             Self::Undefined => {
                 debug!("cannot change value of undefined secret");
             }
@@ -180,6 +299,7 @@ pub async fn set_only_keyring(&self, secret: impl AsRef<str>) -> Result<String>
         let secret = secret.as_ref();
 
         if let Self::KeyringEntry(entry) = self {
+            cache::invalidate(self);
             entry
                 .set_secret(secret)
                 .await
@@ -204,13 +324,42 @@ pub fn replace_undefined_to_keyring(
         Ok(())
     }
 
+    /// Replace undefined secret by a provider-based one.
+    ///
+    /// This function has no effect on other variants.
+    pub fn replace_undefined_to_provider(&mut self, provider: impl ToString, key: impl ToString) {
+        if self.is_undefined() {
+            *self = Self::new_provider(provider, key)
+        }
+    }
+
+    /// Change the secret value of the provider-based secret only.
+    ///
+    /// This function has no effect on other secret variants.
+    pub async fn set_only_provider(&self, secret: impl AsRef<str>) -> Result<String> {
+        let secret = secret.as_ref();
+
+        if let Self::Provider(entry) = self {
+            cache::invalidate(self);
+            entry.set_secret(secret).await?;
+        }
+
+        Ok(secret.to_owned())
+    }
+
     /// Delete the secret value and make the current secret undefined.
     pub async fn delete(&mut self) -> Result<()> {
+        cache::invalidate(self);
+
         #[cfg(feature = "keyring")]
         if let Self::KeyringEntry(entry) = self {
             entry.delete_secret().await.map_err(Error::KeyringError)?;
         }
 
+        if let Self::Provider(entry) = self {
+            entry.delete_secret().await?;
+        }
+
         *self = Self::Undefined;
 
         Ok(())
@@ -222,9 +371,22 @@ pub async fn delete(&mut self) -> Result<()> {
     #[cfg(feature = "keyring")]
     pub async fn delete_only_keyring(&self) -> Result<()> {
         if let Self::KeyringEntry(entry) = self {
+            cache::invalidate(self);
             entry.delete_secret().await.map_err(Error::KeyringError)?;
         }
 
         Ok(())
     }
+
+    /// Delete the secret value of provider-based secrets only.
+    ///
+    /// This function has no effect on other variants.
+    pub async fn delete_only_provider(&self) -> Result<()> {
+        if let Self::Provider(entry) = self {
+            cache::invalidate(self);
+            entry.delete_secret().await?;
+        }
+
+        Ok(())
+    }
 }
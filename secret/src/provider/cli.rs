@@ -0,0 +1,92 @@
+//! # Password managers CLI providers
+//!
+//! Module dedicated to secret providers backed by the CLI of
+//! password managers.
+
+use async_trait::async_trait;
+use process::SingleCommand;
+
+use super::SecretProvider;
+use crate::{Error, Result};
+
+/// The 1Password secret provider, based on the `op` CLI.
+///
+/// Keys are secret references, like `op://vault/item/field`. The CLI
+/// needs to be signed in beforehand. This provider is read-only.
+///
+/// Unknown vaults, items and fields are reported as not found.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OnePasswordProvider;
+
+#[async_trait]
+impl SecretProvider for OnePasswordProvider {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let cmd = SingleCommand::new_argv("op", ["read", "--no-newline", key]);
+        run(cmd, |err| {
+            err.contains("isn't a vault")
+                || err.contains("isn't an item")
+                || err.contains("cannot be found")
+                || err.contains("could not find")
+        })
+        .await
+    }
+
+    async fn set(&self, _key: &str, _secret: &str) -> Result<()> {
+        Err(Error::ReadOnlyProviderError("1password"))
+    }
+
+    async fn delete(&self, _key: &str) -> Result<()> {
+        Err(Error::ReadOnlyProviderError("1password"))
+    }
+}
+
+/// The Bitwarden secret provider, based on the `bw` CLI.
+///
+/// Keys are item identifiers or names, the password of the item is
+/// used as secret. The vault needs to be unlocked beforehand (see
+/// `BW_SESSION`). This provider is read-only.
+///
+/// Unknown items are reported as not found.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BitwardenProvider;
+
+#[async_trait]
+impl SecretProvider for BitwardenProvider {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let cmd = SingleCommand::new_argv("bw", ["get", "password", key]);
+        run(cmd, |err| err.trim() == "not found.").await
+    }
+
+    async fn set(&self, _key: &str, _secret: &str) -> Result<()> {
+        Err(Error::ReadOnlyProviderError("bitwarden"))
+    }
+
+    async fn delete(&self, _key: &str) -> Result<()> {
+        Err(Error::ReadOnlyProviderError("bitwarden"))
+    }
+}
+
+/// Run the given command, then return the first line of its output.
+///
+/// The command is executed without shell, so that keys are never
+/// interpreted.
+///
+/// Returns [`None`] if the command fails with an error output
+/// matching `is_not_found`. The error output is given in lowercase.
+async fn run(cmd: SingleCommand, is_not_found: impl Fn(&str) -> bool) -> Result<Option<String>> {
+    let output = match cmd.run().await {
+        Ok(output) => output,
+        Err(process::Error::GetExitStatusCodeNonZeroError(_, _, err))
+            if is_not_found(&err.to_lowercase()) =>
+        {
+            return Ok(None);
+        }
+        Err(err) => return Err(Error::GetSecretFromCommand(err)),
+    };
+
+    Ok(output
+        .to_string_lossy()
+        .lines()
+        .next()
+        .map(ToOwned::to_owned))
+}
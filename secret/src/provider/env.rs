@@ -0,0 +1,37 @@
+//! # Environment variables provider
+//!
+//! Module dedicated to the environment variables secret provider.
+
+use std::env::{self, VarError};
+
+use async_trait::async_trait;
+
+use super::SecretProvider;
+use crate::{Error, Result};
+
+/// The environment variables secret provider.
+///
+/// Keys are environment variable names. This provider is read-only,
+/// since changing environment variables of a multi-threaded process
+/// is not safe.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct EnvProvider;
+
+#[async_trait]
+impl SecretProvider for EnvProvider {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        match env::var(key) {
+            Ok(secret) => Ok(Some(secret)),
+            Err(VarError::NotPresent) => Ok(None),
+            Err(err) => Err(Error::GetSecretFromEnvError(err, key.to_owned())),
+        }
+    }
+
+    async fn set(&self, _key: &str, _secret: &str) -> Result<()> {
+        Err(Error::ReadOnlyProviderError("env"))
+    }
+
+    async fn delete(&self, _key: &str) -> Result<()> {
+        Err(Error::ReadOnlyProviderError("env"))
+    }
+}
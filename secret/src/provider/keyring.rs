@@ -0,0 +1,33 @@
+//! # Keyring provider
+//!
+//! Module dedicated to the global keyring secret provider.
+
+use async_trait::async_trait;
+use keyring::KeyringEntry;
+
+use super::SecretProvider;
+use crate::{Error, Result};
+
+/// The global keyring secret provider.
+///
+/// Keys are keyring entry keys, see [`KeyringEntry`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct KeyringProvider;
+
+#[async_trait]
+impl SecretProvider for KeyringProvider {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let entry = KeyringEntry::try_new(key).map_err(Error::KeyringError)?;
+        entry.find_secret().await.map_err(Error::KeyringError)
+    }
+
+    async fn set(&self, key: &str, secret: &str) -> Result<()> {
+        let entry = KeyringEntry::try_new(key).map_err(Error::KeyringError)?;
+        entry.set_secret(secret).await.map_err(Error::KeyringError)
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let entry = KeyringEntry::try_new(key).map_err(Error::KeyringError)?;
+        entry.delete_secret().await.map_err(Error::KeyringError)
+    }
+}
@@ -0,0 +1,166 @@
+//! # Provider
+//!
+//! Module dedicated to secret providers. A provider is a store able
+//! to get, set and delete secrets by key, like a password manager.
+//! Providers are registered globally under a name, then referenced
+//! by secrets using [`ProviderEntry`].
+//!
+//! The following providers are registered by default:
+//!
+//! - `env`: environment variables, see [`EnvProvider`]
+//! - `keyring`: the user's global keyring, see [`KeyringProvider`]
+//! - `pass`: the [pass](https://www.passwordstore.org/) password
+//!   store, see [`PassProvider`]
+//! - `1password`: the 1Password CLI, see [`OnePasswordProvider`]
+//! - `bitwarden`: the Bitwarden CLI, see [`BitwardenProvider`]
+
+#[cfg(feature = "command")]
+mod cli;
+mod env;
+#[cfg(feature = "keyring")]
+mod keyring;
+#[cfg(feature = "command")]
+mod pass;
+
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, OnceLock, PoisonError, RwLock},
+};
+
+use async_trait::async_trait;
+
+#[doc(inline)]
+pub use self::env::EnvProvider;
+#[cfg(feature = "keyring")]
+#[doc(inline)]
+pub use self::keyring::KeyringProvider;
+#[cfg(feature = "command")]
+#[doc(inline)]
+pub use self::{
+    cli::{BitwardenProvider, OnePasswordProvider},
+    pass::PassProvider,
+};
+use crate::{Error, Result, Secret};
+
+/// Registered providers, indexed by their name.
+static PROVIDERS: OnceLock<RwLock<HashMap<String, Arc<dyn SecretProvider>>>> = OnceLock::new();
+
+/// The secret provider.
+///
+/// A provider stores secrets by key. Read-only providers should
+/// return [`Error::ReadOnlyProviderError`] when setting or deleting
+/// secrets.
+#[async_trait]
+pub trait SecretProvider: fmt::Debug + Send + Sync {
+    /// Get the secret value at the given key.
+    ///
+    /// Returns [`None`] if the key does not exist.
+    async fn get(&self, key: &str) -> Result<Option<String>>;
+
+    /// Change the secret value at the given key.
+    async fn set(&self, key: &str, secret: &str) -> Result<()>;
+
+    /// Delete the secret value at the given key.
+    async fn delete(&self, key: &str) -> Result<()>;
+}
+
+/// Register the given provider under the given name.
+///
+/// A provider previously registered under the same name is
+/// replaced, which allows to customize the default providers.
+pub fn register_provider(name: impl ToString, provider: impl SecretProvider + 'static) {
+    providers()
+        .write()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(name.to_string(), Arc::new(provider));
+}
+
+/// Find the provider registered under the given name.
+pub fn find_provider(name: impl AsRef<str>) -> Option<Arc<dyn SecretProvider>> {
+    providers()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(name.as_ref())
+        .cloned()
+}
+
+fn providers() -> &'static RwLock<HashMap<String, Arc<dyn SecretProvider>>> {
+    PROVIDERS.get_or_init(|| {
+        let mut providers: HashMap<String, Arc<dyn SecretProvider>> = HashMap::new();
+
+        providers.insert("env".into(), Arc::new(EnvProvider));
+        #[cfg(feature = "keyring")]
+        providers.insert("keyring".into(), Arc::new(KeyringProvider));
+        #[cfg(feature = "command")]
+        {
+            providers.insert("pass".into(), Arc::new(PassProvider::default()));
+            providers.insert("1password".into(), Arc::new(OnePasswordProvider));
+            providers.insert("bitwarden".into(), Arc::new(BitwardenProvider));
+        }
+
+        RwLock::new(providers)
+    })
+}
+
+/// The provider entry.
+///
+/// References a secret by the name of a registered provider and the
+/// key of the secret within this provider.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "derive",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub struct ProviderEntry {
+    /// The name of the registered provider.
+    pub provider: String,
+
+    /// The key of the secret within the provider.
+    pub key: String,
+}
+
+impl ProviderEntry {
+    pub fn new(provider: impl ToString, key: impl ToString) -> Self {
+        Self {
+            provider: provider.to_string(),
+            key: key.to_string(),
+        }
+    }
+
+    /// Get the secret value from the provider.
+    pub async fn get_secret(&self) -> Result<String> {
+        self.find_secret().await?.ok_or_else(|| {
+            Error::FindSecretFromProviderError(self.provider.clone(), self.key.clone())
+        })
+    }
+
+    /// Find the secret value from the provider.
+    ///
+    /// Like [`ProviderEntry::get_secret`], but returns [`None`] if
+    /// the key does not exist.
+    pub async fn find_secret(&self) -> Result<Option<String>> {
+        self.provider()?.get(&self.key).await
+    }
+
+    /// Change the secret value of the provider.
+    pub async fn set_secret(&self, secret: impl AsRef<str>) -> Result<()> {
+        self.provider()?.set(&self.key, secret.as_ref()).await
+    }
+
+    /// Delete the secret value from the provider.
+    pub async fn delete_secret(&self) -> Result<()> {
+        self.provider()?.delete(&self.key).await
+    }
+
+    fn provider(&self) -> Result<Arc<dyn SecretProvider>> {
+        find_provider(&self.provider).ok_or_else(|| Error::FindProviderError(self.provider.clone()))
+    }
+}
+
+impl From<ProviderEntry> for Secret {
+    fn from(entry: ProviderEntry) -> Self {
+        Secret::Provider(entry)
+    }
+}
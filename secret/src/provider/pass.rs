@@ -0,0 +1,187 @@
+//! # Pass provider
+//!
+//! Module dedicated to the [pass](https://www.passwordstore.org/)
+//! secret provider.
+
+use std::{
+    env, fs, io,
+    path::{Component, Path, PathBuf},
+};
+
+use async_trait::async_trait;
+use process::SingleCommand;
+use shellexpand_utils::shellexpand_path;
+
+use super::SecretProvider;
+use crate::{Error, Result};
+
+/// The pass secret provider.
+///
+/// Secrets are stored in GPG-encrypted files following the layout of
+/// the pass password store: the key `email/imap` is stored in the
+/// file `email/imap.gpg` of the store directory, and encrypted for
+/// the recipients listed in the nearest `.gpg-id` file. Only the
+/// first line of the decrypted file is used as secret.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PassProvider {
+    /// The password store directory.
+    ///
+    /// Defaults to `$PASSWORD_STORE_DIR`, or `~/.password-store`.
+    pub store_dir: PathBuf,
+
+    /// The GnuPG home directory, passed to `gpg --homedir`.
+    ///
+    /// Defaults to the one of GnuPG.
+    pub gpg_home: Option<PathBuf>,
+}
+
+impl PassProvider {
+    pub fn new(store_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            store_dir: store_dir.into(),
+            gpg_home: None,
+        }
+    }
+
+    pub fn with_gpg_home(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.gpg_home = Some(dir.into());
+        self
+    }
+
+    /// Build the path of the encrypted file of the given key.
+    ///
+    /// Keys escaping the store directory are rejected.
+    fn entry_path(&self, key: &str) -> Result<PathBuf> {
+        let is_valid = !key.is_empty()
+            && Path::new(key)
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)));
+
+        if !is_valid {
+            return Err(Error::InvalidPassKeyError(key.to_owned()));
+        }
+
+        Ok(self.store_dir.join(format!("{key}.gpg")))
+    }
+
+    /// Find the recipients of the given entry, from the nearest
+    /// `.gpg-id` file.
+    fn recipients(&self, path: &Path) -> Result<Vec<String>> {
+        let mut dir = path.parent();
+
+        while let Some(path) = dir {
+            let gpg_id = path.join(".gpg-id");
+
+            if gpg_id.is_file() {
+                let recipients = fs::read_to_string(&gpg_id)
+                    .map_err(|err| Error::ReadPassRecipientsError(err, gpg_id.clone()))?
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(ToOwned::to_owned)
+                    .collect();
+                return Ok(recipients);
+            }
+
+            if path == self.store_dir {
+                break;
+            }
+
+            dir = path.parent();
+        }
+
+        Err(Error::FindPassRecipientsError(self.store_dir.clone()))
+    }
+
+    /// Build the `gpg` command with the given arguments.
+    ///
+    /// The command is executed without shell, so that paths and
+    /// recipients are never interpreted.
+    fn gpg(&self, args: impl IntoIterator<Item = String>) -> SingleCommand {
+        let home = self.gpg_home.iter().flat_map(|dir| {
+            [
+                String::from("--homedir"),
+                dir.to_string_lossy().into_owned(),
+            ]
+        });
+
+        SingleCommand::new_argv("gpg", home.chain(args))
+    }
+}
+
+impl Default for PassProvider {
+    fn default() -> Self {
+        let store_dir = match env::var_os("PASSWORD_STORE_DIR") {
+            Some(dir) => PathBuf::from(dir),
+            None => shellexpand_path("~/.password-store"),
+        };
+
+        Self::new(store_dir)
+    }
+}
+
+#[async_trait]
+impl SecretProvider for PassProvider {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        let path = self.entry_path(key)?;
+
+        if !path.is_file() {
+            return Ok(None);
+        }
+
+        let args = ["--quiet", "--batch", "--decrypt"].map(String::from);
+        let path = path.to_string_lossy().into_owned();
+
+        let output = self
+            .gpg(args.into_iter().chain([path]))
+            .run()
+            .await
+            .map_err(Error::GetSecretFromCommand)?;
+
+        Ok(output
+            .to_string_lossy()
+            .lines()
+            .next()
+            .map(ToOwned::to_owned))
+    }
+
+    async fn set(&self, key: &str, secret: &str) -> Result<()> {
+        let path = self.entry_path(key)?;
+        let recipients = self.recipients(&path)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| Error::CreatePassDirError(err, dir.into()))?;
+        }
+
+        let mut args = ["--quiet", "--batch", "--yes", "--encrypt"]
+            .map(String::from)
+            .to_vec();
+
+        for recipient in recipients {
+            args.extend([String::from("--recipient"), recipient]);
+        }
+
+        args.extend([
+            String::from("--output"),
+            path.to_string_lossy().into_owned(),
+        ]);
+
+        // like pass, the secret is stored with a trailing new line
+        self.gpg(args)
+            .run_with(format!("{secret}\n"))
+            .await
+            .map_err(Error::SetSecretIntoCommand)?;
+
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let path = self.entry_path(key)?;
+
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Error::DeletePassEntryError(err, path)),
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use async_trait::async_trait;
+use secret::{cache, register_provider, ExposeSecret, Result, Secret, SecretProvider};
+use tokio::sync::Mutex;
+
+/// The cache is global, so tests using it must not run in parallel.
+static LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Provider returning a new value each time it is read.
+#[derive(Debug, Default)]
+struct CountingProvider(AtomicUsize);
+
+#[async_trait]
+impl SecretProvider for CountingProvider {
+    async fn get(&self, _key: &str) -> Result<Option<String>> {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let count = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(Some(count.to_string()))
+    }
+
+    async fn set(&self, _key: &str, _secret: &str) -> Result<()> {
+        Ok(())
+    }
+
+    async fn delete(&self, _key: &str) -> Result<()> {
+        Ok(())
+    }
+}
+
+fn counting_secret(name: &str) -> Secret {
+    register_provider(name, CountingProvider::default());
+    Secret::new_provider(name, "key")
+}
+
+#[tokio::test]
+async fn cache_disabled_by_default() {
+    let _lock = LOCK.lock().await;
+    cache::disable();
+
+    let secret = counting_secret("counting-disabled");
+
+    assert_eq!(secret.get().await.unwrap(), "1");
+    assert_eq!(secret.get().await.unwrap(), "2");
+}
+
+#[tokio::test]
+async fn cache_ttl() {
+    let _lock = LOCK.lock().await;
+    cache::enable(Duration::from_millis(200));
+
+    let secret = counting_secret("counting-ttl");
+
+    let value = secret.get_secret().await.unwrap();
+    assert_eq!(value.expose_secret(), "1");
+    assert_eq!(secret.get().await.unwrap(), "1");
+    assert_eq!(secret.find().await.unwrap().as_deref(), Some("1"));
+
+    tokio::time::sleep(Duration::from_millis(250)).await;
+    assert_eq!(secret.get().await.unwrap(), "2");
+
+    cache::clear();
+    assert_eq!(secret.get().await.unwrap(), "3");
+
+    cache::disable();
+}
+
+#[tokio::test]
+async fn cache_invalidation() {
+    let _lock = LOCK.lock().await;
+    cache::enable(Duration::from_secs(60));
+
+    let secret = counting_secret("counting-invalidation");
+
+    assert_eq!(secret.get().await.unwrap(), "1");
+    assert_eq!(secret.get().await.unwrap(), "1");
+
+    secret.set_only_provider("secret").await.unwrap();
+    assert_eq!(secret.get().await.unwrap(), "2");
+
+    secret.delete_only_provider().await.unwrap();
+    assert_eq!(secret.get().await.unwrap(), "3");
+
+    cache::disable();
+}
+
+#[tokio::test]
+async fn cache_concurrent_lookups() {
+    let _lock = LOCK.lock().await;
+    cache::enable(Duration::from_secs(60));
+
+    let secret = Arc::new(counting_secret("counting-concurrent"));
+
+    let lookups = (0..8).map(|_| {
+        let secret = secret.clone();
+        tokio::spawn(async move { secret.get().await.unwrap() })
+    });
+
+    for lookup in lookups.collect::<Vec<_>>() {
+        assert_eq!(lookup.await.unwrap(), "1");
+    }
+
+    cache::disable();
+}
+
+#[tokio::test]
+async fn cache_eviction() {
+    let _lock = LOCK.lock().await;
+    cache::enable(Duration::from_millis(100));
+    cache::clear();
+
+    let first = counting_secret("counting-eviction-first");
+    let second = counting_secret("counting-eviction-second");
+
+    assert_eq!(first.get().await.unwrap(), "1");
+    assert_eq!(second.get().await.unwrap(), "1");
+    assert_eq!(cache::len(), 2);
+
+    // expired values are evicted on the next access
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(second.get().await.unwrap(), "2");
+    assert_eq!(cache::len(), 1);
+
+    tokio::time::sleep(Duration::from_millis(150)).await;
+    assert_eq!(cache::len(), 0);
+
+    cache::disable();
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "encrypted-file")]
+
+use std::{fs, io::Write, iter, path::PathBuf};
+
+use age::{
+    armor::{ArmoredWriter, Format},
+    scrypt,
+    secrecy::{ExposeSecret, SecretString},
+    x25519, Encryptor, Recipient,
+};
+use secret::{EncryptedFile, Error, Secret};
+use tempfile::tempdir;
+
+const ENTRIES: &str = r#"{"imap-password":"imap-secret","smtp-password":"smtp-secret"}"#;
+
+fn encrypt(path: &PathBuf, recipient: &dyn Recipient, armor: bool) {
+    let encryptor = Encryptor::with_recipients(iter::once(recipient)).unwrap();
+    let format = if armor {
+        Format::AsciiArmor
+    } else {
+        Format::Binary
+    };
+
+    let output = ArmoredWriter::wrap_output(fs::File::create(path).unwrap(), format).unwrap();
+    let mut writer = encryptor.wrap_output(output).unwrap();
+    writer.write_all(ENTRIES.as_bytes()).unwrap();
+    writer.finish().unwrap().finish().unwrap();
+}
+
+#[tokio::test]
+async fn encrypted_file_with_passphrase() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("passphrase.age");
+    let mut recipient = scrypt::Recipient::new(SecretString::from("passphrase".to_owned()));
+    recipient.set_work_factor(2);
+    encrypt(&path, &recipient, false);
+
+    let passphrase = Secret::new_command("echo passphrase");
+
+    let secret = Secret::new_encrypted_file(
+        EncryptedFile::new(&path, "imap-password").with_passphrase(passphrase.clone()),
+    );
+    assert_eq!(secret.get().await.unwrap(), "imap-secret");
+
+    // decrypted entries are not shared with other unlock methods
+    let secret = Secret::new_encrypted_file(
+        EncryptedFile::new(&path, "imap-password").with_passphrase(Secret::new_raw("wrong")),
+    );
+    assert!(matches!(
+        secret.get().await.unwrap_err(),
+        Error::DecryptFileError(..)
+    ));
+
+    // decrypted entries are cached till the cache is cleared
+    fs::remove_file(&path).unwrap();
+
+    let secret = Secret::new_encrypted_file(
+        EncryptedFile::new(&path, "smtp-password").with_passphrase(passphrase.clone()),
+    );
+    assert_eq!(secret.get().await.unwrap(), "smtp-secret");
+
+    let mut secret = Secret::new_encrypted_file(
+        EncryptedFile::new(&path, "unknown").with_passphrase(passphrase.clone()),
+    );
+    assert_eq!(secret.find().await.unwrap(), None);
+    assert!(matches!(
+        secret.get().await.unwrap_err(),
+        Error::FindEncryptedFileEntryError(..)
+    ));
+
+    // encrypted file secrets are read-only
+    secret.set("secret").await.unwrap();
+    assert_eq!(secret.find().await.unwrap(), None);
+
+    EncryptedFile::clear_cache();
+
+    let secret = Secret::new_encrypted_file(
+        EncryptedFile::new(&path, "smtp-password").with_passphrase(passphrase),
+    );
+    assert!(matches!(
+        secret.get().await.unwrap_err(),
+        Error::OpenEncryptedFileError(..)
+    ));
+}
+
+#[tokio::test]
+async fn encrypted_file_with_identity_file() {
+    let identity = x25519::Identity::generate();
+    let dir = tempdir().unwrap();
+    let identity_path = dir.path().join("identity.txt");
+    fs::write(&identity_path, identity.to_string().expose_secret()).unwrap();
+
+    let path = dir.path().join("identity.age");
+    encrypt(&path, &identity.to_public(), true);
+
+    let secret = Secret::new_encrypted_file(
+        EncryptedFile::new(&path, "imap-password").with_identity_file(&identity_path),
+    );
+    assert_eq!(secret.get().await.unwrap(), "imap-secret");
+}
+
+#[tokio::test]
+async fn encrypted_file_without_unlock() {
+    let dir = tempdir().unwrap();
+    let path = dir.path().join("locked.age");
+    let secret = Secret::new_encrypted_file(EncryptedFile::new(&path, "imap-password"));
+
+    assert!(matches!(
+        secret.get().await.unwrap_err(),
+        Error::MissingEncryptedFileUnlockError(_)
+    ));
+}
@@ -0,0 +1,209 @@
+#[cfg(feature = "command")]
+use std::fs;
+use std::{collections::HashMap, env, sync::Mutex};
+
+use async_trait::async_trait;
+#[cfg(feature = "command")]
+use secret::provider::PassProvider;
+#[cfg(all(unix, feature = "command"))]
+use secret::provider::{BitwardenProvider, OnePasswordProvider};
+use secret::{provider::EnvProvider, register_provider, Error, Result, Secret, SecretProvider};
+#[cfg(feature = "command")]
+use tempfile::tempdir;
+
+#[derive(Debug, Default)]
+struct MemoryProvider(Mutex<HashMap<String, String>>);
+
+#[async_trait]
+impl SecretProvider for MemoryProvider {
+    async fn get(&self, key: &str) -> Result<Option<String>> {
+        Ok(self.0.lock().unwrap().get(key).cloned())
+    }
+
+    async fn set(&self, key: &str, secret: &str) -> Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .insert(key.to_owned(), secret.to_owned());
+        Ok(())
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        self.0.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn custom_provider() {
+    register_provider("memory", MemoryProvider::default());
+
+    let mut secret = Secret::new();
+    secret.replace_undefined_to_provider("memory", "key");
+    assert_eq!(secret, Secret::new_provider("memory", "key"));
+    assert_eq!(secret.find().await.unwrap(), None);
+
+    secret.set("secret").await.unwrap();
+    assert_eq!(secret.get().await.unwrap(), "secret");
+
+    secret.set_only_provider("secret2").await.unwrap();
+    assert_eq!(secret.get().await.unwrap(), "secret2");
+
+    secret.delete_only_provider().await.unwrap();
+    assert_eq!(secret.find().await.unwrap(), None);
+    assert!(matches!(
+        secret.get().await.unwrap_err(),
+        Error::FindSecretFromProviderError(..)
+    ));
+
+    secret.delete().await.unwrap();
+    assert!(secret.is_undefined());
+
+    let secret = Secret::new_provider("unknown", "key");
+    assert!(matches!(
+        secret.get().await.unwrap_err(),
+        Error::FindProviderError(_)
+    ));
+}
+
+#[tokio::test]
+async fn env_provider() {
+    let home = env::var("HOME").unwrap();
+    assert_eq!(EnvProvider.get("HOME").await.unwrap(), Some(home.clone()));
+    assert_eq!(
+        Secret::new_provider("env", "HOME").get().await.unwrap(),
+        home
+    );
+
+    let secret = Secret::new_provider("env", "SECRET_LIB_UNDEFINED_VAR");
+    assert_eq!(secret.find().await.unwrap(), None);
+    assert!(matches!(
+        secret.set_only_provider("secret").await.unwrap_err(),
+        Error::ReadOnlyProviderError("env")
+    ));
+}
+
+#[cfg(feature = "command")]
+#[tokio::test]
+#[ignore = "requires gpg"]
+async fn pass_provider() {
+    let dir = tempdir().unwrap();
+    let gpg_home = dir.path().join("gnupg");
+    let store_dir = dir.path().join("store");
+    fs::create_dir_all(&gpg_home).unwrap();
+    fs::create_dir_all(&store_dir).unwrap();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&gpg_home, fs::Permissions::from_mode(0o700)).unwrap();
+    }
+
+    let status = std::process::Command::new("gpg")
+        .arg("--homedir")
+        .arg(&gpg_home)
+        .args(["--batch", "--quiet", "--passphrase", ""])
+        .args(["--quick-gen-key", "pass@localhost", "default", "default"])
+        .status()
+        .unwrap();
+    assert!(status.success(), "cannot generate gpg key");
+
+    fs::write(store_dir.join(".gpg-id"), "pass@localhost\n").unwrap();
+    register_provider(
+        "test-pass",
+        PassProvider::new(&store_dir).with_gpg_home(&gpg_home),
+    );
+
+    let secret = Secret::new_provider("test-pass", "email/it's imap");
+    assert_eq!(secret.find().await.unwrap(), None);
+
+    secret.set_only_provider("secret").await.unwrap();
+    assert!(store_dir.join("email/it's imap.gpg").is_file());
+    assert_eq!(secret.get().await.unwrap(), "secret");
+
+    secret.delete_only_provider().await.unwrap();
+    assert_eq!(secret.find().await.unwrap(), None);
+
+    let secret = Secret::new_provider("test-pass", "../escape");
+    assert!(matches!(
+        secret.find().await.unwrap_err(),
+        Error::InvalidPassKeyError(_)
+    ));
+}
+
+/// Fake `op` and `bw` CLIs, answering the `vault/item/password`
+/// reference and the `item` item, or failing with the given error
+/// output.
+#[cfg(all(unix, feature = "command"))]
+fn fake_cli(dir: &std::path::Path, op_err: &str, bw_err: &str) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let scripts = [
+        ("op", "op://vault/item/password", op_err),
+        ("bw", "item", bw_err),
+    ];
+
+    for (name, key, err) in scripts {
+        let path = dir.join(name);
+        let script = format!(
+            "#!/bin/sh\nfor arg; do key=\"$arg\"; done\nif [ \"$key\" = '{key}' ]; then printf secret; exit 0; fi\ncat >&2 <<'EOF'\n{err}\nEOF\nexit 1\n"
+        );
+        fs::write(&path, script).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+#[cfg(all(unix, feature = "command"))]
+#[tokio::test]
+async fn cli_providers() {
+    let dir = tempdir().unwrap();
+    let not_found_dir = dir.path().join("not-found");
+    let locked_dir = dir.path().join("locked");
+    fs::create_dir_all(&not_found_dir).unwrap();
+    fs::create_dir_all(&locked_dir).unwrap();
+
+    fake_cli(
+        &not_found_dir,
+        r#"[ERROR] 2024/01/01 00:00:00 "missing" isn't an item in the "vault" vault. Specify the item with its UUID, name, or domain."#,
+        "Not found.",
+    );
+    fake_cli(
+        &locked_dir,
+        "[ERROR] 2024/01/01 00:00:00 You are not currently signed in.",
+        "You are not logged in.",
+    );
+
+    let path = env::var_os("PATH").unwrap_or_default();
+    let path_with = |dir: &std::path::Path| {
+        let mut paths = vec![dir.to_owned()];
+        paths.extend(env::split_paths(&path));
+        env::join_paths(paths).unwrap()
+    };
+
+    env::set_var("PATH", path_with(&not_found_dir));
+
+    let op = OnePasswordProvider;
+    assert_eq!(
+        op.get("op://vault/item/password").await.unwrap().as_deref(),
+        Some("secret")
+    );
+    assert_eq!(op.get("op://vault/missing/password").await.unwrap(), None);
+
+    let bw = BitwardenProvider;
+    assert_eq!(bw.get("item").await.unwrap().as_deref(), Some("secret"));
+    assert_eq!(bw.get("missing").await.unwrap(), None);
+
+    // other errors are not mistaken for missing secrets
+    env::set_var("PATH", path_with(&locked_dir));
+
+    assert!(matches!(
+        op.get("op://vault/missing/password").await.unwrap_err(),
+        Error::GetSecretFromCommand(_)
+    ));
+    assert!(matches!(
+        bw.get("missing").await.unwrap_err(),
+        Error::GetSecretFromCommand(_)
+    ));
+
+    env::set_var("PATH", path);
+}
@@ -4,6 +4,9 @@
 async fn test_secret_variants() {
     env_logger::builder().is_test(true).init();
 
+    // use an in-memory keyring, so that no keyring daemon is needed
+    keyring::set_global_backend(keyring::KeyringBackend::Memory);
+
     // test raw secret
 
     let mut secret = Secret::new_raw("secret");
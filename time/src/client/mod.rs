@@ -3,16 +3,26 @@
 //! The client connects to the server, sends requests in order to
 //! control the timer and receive responses.
 //!
-//! The client must implement the [`Client`] trait.
+//! The client must implement the [`Client`] trait. Built-in clients
+//! send requests to the default timer, or to the named timer given
+//! to their `with_timer` function.
 
 #[cfg(feature = "tcp-client")]
 pub mod tcp;
+#[cfg(all(unix, feature = "unix-client"))]
+pub mod unix;
 
 use async_trait::async_trait;
 use log::{info, trace};
-use std::io::{Error, ErrorKind, Result};
+use std::{
+    collections::BTreeMap,
+    io::{Error, ErrorKind, Result},
+};
 
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+use crate::event::Event;
 use crate::{
+    history::Totals,
     request::{Request, RequestWriter},
     response::{Response, ResponseReader},
     timer::Timer,
@@ -34,10 +44,7 @@ async fn start(&self) -> Result<()> {
 
         match self.send(Request::Start).await {
             Ok(Response::Ok) => Ok(()),
-            Ok(res) => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid response: {res:?}"),
-            )),
+            Ok(res) => Err(invalid_response(res)),
             Err(err) => Err(Error::new(ErrorKind::Other, err)),
         }
     }
@@ -51,10 +58,7 @@ async fn get(&self) -> Result<Timer> {
                 trace!("timer: {timer:#?}");
                 Ok(timer)
             }
-            Ok(res) => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid response: {res:?}"),
-            )),
+            Ok(res) => Err(invalid_response(res)),
             Err(err) => Err(Error::new(ErrorKind::Other, err)),
         }
     }
@@ -65,10 +69,40 @@ async fn set(&self, duration: usize) -> Result<()> {
 
         match self.send(Request::Set(duration)).await {
             Ok(Response::Ok) => Ok(()),
-            Ok(res) => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid response: {res:?}"),
-            )),
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+
+    /// Send the add timer request.
+    async fn add(&self, duration: usize) -> Result<()> {
+        info!("sending request to add time to timer");
+
+        match self.send(Request::Add(duration)).await {
+            Ok(Response::Ok) => Ok(()),
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+
+    /// Send the subtract timer request.
+    async fn subtract(&self, duration: usize) -> Result<()> {
+        info!("sending request to subtract time from timer");
+
+        match self.send(Request::Subtract(duration)).await {
+            Ok(Response::Ok) => Ok(()),
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+
+    /// Send the next timer cycle request.
+    async fn next(&self) -> Result<()> {
+        info!("sending request to skip timer cycle");
+
+        match self.send(Request::Next).await {
+            Ok(Response::Ok) => Ok(()),
+            Ok(res) => Err(invalid_response(res)),
             Err(err) => Err(Error::new(ErrorKind::Other, err)),
         }
     }
@@ -79,10 +113,7 @@ async fn pause(&self) -> Result<()> {
 
         match self.send(Request::Pause).await {
             Ok(Response::Ok) => Ok(()),
-            Ok(res) => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid response: {res:?}"),
-            )),
+            Ok(res) => Err(invalid_response(res)),
             Err(err) => Err(Error::new(ErrorKind::Other, err)),
         }
     }
@@ -93,27 +124,216 @@ async fn resume(&self) -> Result<()> {
 
         match self.send(Request::Resume).await {
             Ok(Response::Ok) => Ok(()),
-            Ok(res) => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid response: {res:?}"),
-            )),
+            Ok(res) => Err(invalid_response(res)),
             Err(err) => Err(Error::new(ErrorKind::Other, err)),
         }
     }
 
+    /// Send the subscribe request, then return the stream of events.
+    ///
+    /// The default implementation returns an error, as subscriptions
+    /// need the connection to stay open.
+    #[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+    async fn subscribe(&self) -> Result<EventStream> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            "subscription not supported by this client",
+        ))
+    }
+
     /// Send the stop timer request.
     async fn stop(&self) -> Result<()> {
         info!("sending request to stop timer");
 
         match self.send(Request::Stop).await {
             Ok(Response::Ok) => Ok(()),
-            Ok(res) => Err(Error::new(
-                ErrorKind::InvalidData,
-                format!("invalid response: {res:?}"),
-            )),
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+
+    /// Send the list timers request.
+    async fn list(&self) -> Result<BTreeMap<String, Timer>> {
+        info!("sending request to list timers");
+
+        match self.send(Request::List).await {
+            Ok(Response::Timers(timers)) => {
+                trace!("timers: {timers:#?}");
+                Ok(timers)
+            }
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+
+    /// Send the history totals per day request.
+    async fn totals_per_day(&self) -> Result<BTreeMap<String, Totals>> {
+        info!("sending request to get history totals per day");
+
+        match self.send(Request::TotalsPerDay).await {
+            Ok(Response::Totals(totals)) => Ok(totals),
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+
+    /// Send the history totals per cycle request.
+    async fn totals_per_cycle(&self) -> Result<BTreeMap<String, Totals>> {
+        info!("sending request to get history totals per cycle");
+
+        match self.send(Request::TotalsPerCycle).await {
+            Ok(Response::Totals(totals)) => Ok(totals),
+            Ok(res) => Err(invalid_response(res)),
             Err(err) => Err(Error::new(ErrorKind::Other, err)),
         }
     }
+
+    /// Send the history totals per tag request.
+    async fn totals_per_tag(&self) -> Result<BTreeMap<String, Totals>> {
+        info!("sending request to get history totals per tag");
+
+        match self.send(Request::TotalsPerTag).await {
+            Ok(Response::Totals(totals)) => Ok(totals),
+            Ok(res) => Err(invalid_response(res)),
+            Err(err) => Err(Error::new(ErrorKind::Other, err)),
+        }
+    }
+}
+
+/// Build the error matching the given unexpected response.
+fn invalid_response(res: Response) -> Error {
+    match res {
+        Response::Error(err) => Error::new(ErrorKind::Other, err),
+        res => Error::new(ErrorKind::InvalidData, format!("invalid response: {res:?}")),
+    }
+}
+
+/// Read the next frame sent by the server.
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+async fn read_frame(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+) -> Result<crate::protocol::ServerFrame> {
+    match crate::protocol::read_line(reader).await? {
+        Some(line) => crate::protocol::parse_frame(&line),
+        None => Err(Error::new(
+            ErrorKind::UnexpectedEof,
+            "connection closed by server",
+        )),
+    }
+}
+
+/// Open a JSON protocol session authenticated by the given token,
+/// send the given request to the given timer then return its
+/// response.
+///
+/// See the [`protocol`](crate::protocol) module.
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+pub(crate) async fn send(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    token: Option<&str>,
+    timer: Option<&str>,
+    req: Request,
+) -> Result<Response> {
+    use log::debug;
+
+    use crate::protocol::{self, ClientFrame, Hello, HelloFrame, RequestFrame, ServerFrame};
+
+    let hello = HelloFrame {
+        hello: Hello {
+            token: token.map(ToOwned::to_owned),
+            ..Default::default()
+        },
+    };
+    protocol::write_frame(writer, &ClientFrame::Hello(hello)).await?;
+
+    match read_frame(reader).await? {
+        ServerFrame::Hello(HelloFrame { hello }) => {
+            debug!("using protocol version {}", hello.version)
+        }
+        ServerFrame::Response(frame) => return Err(invalid_response(frame.response)),
+    }
+
+    let frame = ClientFrame::Request(RequestFrame {
+        id: REQUEST_ID,
+        timer: timer.map(ToOwned::to_owned),
+        request: req,
+    });
+    protocol::write_frame(writer, &frame).await?;
+
+    match read_frame(reader).await? {
+        ServerFrame::Response(frame) if frame.id == Some(REQUEST_ID) => Ok(frame.response),
+        ServerFrame::Response(frame) if frame.id.is_none() => Err(invalid_response(frame.response)),
+        frame => Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("unexpected frame: {frame:?}"),
+        )),
+    }
+}
+
+/// The ID of requests sent by built-in clients, which send one
+/// request per session.
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+const REQUEST_ID: u64 = 1;
+
+/// Open a JSON protocol session authenticated by the given token,
+/// then subscribe to the events of the given timer.
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+pub(crate) async fn subscribe(
+    mut reader: impl tokio::io::AsyncBufRead + Send + Unpin + 'static,
+    mut writer: impl tokio::io::AsyncWrite + Send + Unpin + 'static,
+    token: Option<&str>,
+    timer: Option<&str>,
+) -> Result<EventStream> {
+    match send(&mut reader, &mut writer, token, timer, Request::Subscribe).await? {
+        Response::Ok => Ok(EventStream {
+            reader: Box::new(reader),
+            _writer: Box::new(writer),
+        }),
+        res => Err(invalid_response(res)),
+    }
+}
+
+/// The stream of events received by a subscribed client.
+///
+/// See [`Client::subscribe`].
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+pub struct EventStream {
+    reader: Box<dyn tokio::io::AsyncBufRead + Send + Unpin>,
+
+    /// The writer is kept so that the connection stays open.
+    _writer: Box<dyn tokio::io::AsyncWrite + Send + Unpin>,
+}
+
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+impl EventStream {
+    /// Wait for the next event.
+    ///
+    /// Returns [`None`] once the server closed the connection.
+    pub async fn next(&mut self) -> Result<Option<Event>> {
+        use crate::protocol::ServerFrame;
+
+        loop {
+            let frame = match read_frame(&mut self.reader).await {
+                Ok(ServerFrame::Response(frame)) => frame,
+                Ok(frame) => {
+                    let err = format!("unexpected frame: {frame:?}");
+                    return Err(Error::new(ErrorKind::InvalidData, err));
+                }
+                Err(err) if err.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+                Err(err) => return Err(err),
+            };
+
+            match frame.response {
+                Response::Event(event) if frame.id == Some(REQUEST_ID) => {
+                    trace!("event: {event:?}");
+                    return Ok(Some(event));
+                }
+                Response::Event(_) => continue,
+                res => return Err(invalid_response(res)),
+            }
+        }
+    }
 }
 
 /// The client stream trait.
@@ -4,7 +4,7 @@
 //! on [`tokio::net::TcpStream`].
 
 use async_trait::async_trait;
-use std::io::{Error, ErrorKind, Result};
+use std::io::Result;
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt},
     net::TcpStream,
@@ -13,31 +13,86 @@
 use crate::{
     request::{Request, RequestWriter},
     response::{Response, ResponseReader},
-    tcp::TcpHandler,
-    timer::Timer,
+    tcp::{TcpConfig, TcpHandler, TcpTlsConfig},
 };
 
-use super::{Client, ClientStream};
+use super::{send, subscribe, Client, EventStream};
 
 /// The TCP client.
 ///
 /// This [`Client`] uses the TCP protocol to connect to a listener, to
-/// read responses and write requests.
+/// read responses and write requests. Connections can be
+/// authenticated using a shared token, and encrypted using TLS (see
+/// [`TcpConfig`]).
 pub struct TcpClient {
     /// The TCP host the client should connect to.
     pub host: String,
 
     /// The TCP port the client should connect to.
     pub port: u16,
+
+    /// The shared token sent to the server.
+    pub token: Option<String>,
+
+    /// The TLS configuration of the connection.
+    pub tls: Option<TcpTlsConfig>,
+
+    /// The name of the timer requests are sent to, or [`None`] for
+    /// the default timer.
+    pub timer: Option<String>,
 }
 
 impl TcpClient {
     /// Create a new TCP client using the given host and port.
+    pub fn new(host: impl ToString, port: u16) -> Self {
+        Self::new_with_config(TcpConfig::new(host, port))
+    }
+
+    /// Create a new TCP client using the given configuration.
+    pub fn new_with_config(config: TcpConfig) -> Self {
+        Self {
+            host: config.host,
+            port: config.port,
+            token: config.token,
+            tls: config.tls,
+            timer: None,
+        }
+    }
+
+    /// Create a new boxed TCP client using the given host and port.
     pub fn new_boxed(host: impl ToString, port: u16) -> Box<dyn Client> {
-        Box::new(Self {
-            host: host.to_string(),
-            port,
-        })
+        Box::new(Self::new(host, port))
+    }
+
+    /// Send requests to the timer matching the given name.
+    pub fn with_timer(mut self, name: impl ToString) -> Self {
+        self.timer = Some(name.to_string());
+        self
+    }
+
+    /// Connect to the server.
+    async fn connect(&self) -> Result<TcpStream> {
+        #[cfg(not(feature = "tcp-tls"))]
+        if self.tls.is_some() {
+            return Err(crate::tcp::tls_unsupported());
+        }
+
+        TcpStream::connect((self.host.as_str(), self.port)).await
+    }
+
+    /// Connect to the server, then establish the TLS session.
+    #[cfg(feature = "tcp-tls")]
+    async fn connect_tls(
+        &self,
+        tls: &TcpTlsConfig,
+    ) -> Result<tokio_rustls::client::TlsStream<TcpStream>> {
+        use std::io::{Error, ErrorKind};
+        use tokio_rustls::rustls::ServerName;
+
+        let name = ServerName::try_from(self.host.as_str())
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, err))?;
+        let stream = self.connect().await?;
+        tls.connector()?.connect(name, stream).await
     }
 }
 
@@ -45,26 +100,46 @@ pub fn new_boxed(host: impl ToString, port: u16) -> Box<dyn Client> {
 impl Client for TcpClient {
     /// Send the given request to the TCP server.
     async fn send(&self, req: Request) -> Result<Response> {
-        let stream = TcpStream::connect((self.host.as_str(), self.port)).await?;
-        let mut handler = TcpHandler::from(stream);
-        handler.handle(req).await
+        let token = self.token.as_deref();
+        let timer = self.timer.as_deref();
+
+        #[cfg(feature = "tcp-tls")]
+        if let Some(tls) = &self.tls {
+            let stream = self.connect_tls(tls).await?;
+            let (reader, mut writer) = tokio::io::split(stream);
+            let mut reader = tokio::io::BufReader::new(reader);
+            return send(&mut reader, &mut writer, token, timer, req).await;
+        }
+
+        let TcpHandler {
+            mut reader,
+            mut writer,
+        } = TcpHandler::from(self.connect().await?);
+        send(&mut reader, &mut writer, token, timer, req).await
+    }
+
+    /// Subscribe to the events of the TCP server.
+    async fn subscribe(&self) -> Result<EventStream> {
+        let token = self.token.as_deref();
+        let timer = self.timer.as_deref();
+
+        #[cfg(feature = "tcp-tls")]
+        if let Some(tls) = &self.tls {
+            let stream = self.connect_tls(tls).await?;
+            let (reader, writer) = tokio::io::split(stream);
+            let reader = tokio::io::BufReader::new(reader);
+            return subscribe(reader, writer, token, timer).await;
+        }
+
+        let TcpHandler { reader, writer } = TcpHandler::from(self.connect().await?);
+        subscribe(reader, writer, token, timer).await
     }
 }
 
 #[async_trait]
 impl RequestWriter for TcpHandler {
     async fn write(&mut self, req: Request) -> Result<()> {
-        let req = match req {
-            Request::Start => "start\n".to_owned(),
-            Request::Get => "get\n".to_owned(),
-            Request::Set(duration) => format!("set {duration}\n"),
-            Request::Pause => "pause\n".to_owned(),
-            Request::Resume => "resume\n".to_owned(),
-            Request::Stop => "stop\n".to_owned(),
-        };
-
-        self.writer.write_all(req.as_bytes()).await?;
-
+        self.writer.write_all(format!("{req}\n").as_bytes()).await?;
         Ok(())
     }
 }
@@ -74,29 +149,6 @@ impl ResponseReader for TcpHandler {
     async fn read(&mut self) -> Result<Response> {
         let mut res = String::new();
         self.reader.read_line(&mut res).await?;
-
-        let mut tokens = res.split_whitespace();
-        match tokens.next() {
-            Some("ok") => Ok(Response::Ok),
-            Some("timer") => match tokens.next().map(serde_json::from_str::<Timer>) {
-                Some(Ok(timer)) => Ok(Response::Timer(timer)),
-                Some(Err(err)) => Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    format!("invalid timer: {err}"),
-                )),
-                None => Err(Error::new(
-                    ErrorKind::InvalidInput,
-                    "missing timer".to_owned(),
-                )),
-            },
-            Some(res) => Err(Error::new(
-                ErrorKind::InvalidInput,
-                format!("invalid response: {res}"),
-            )),
-            None => Err(Error::new(
-                ErrorKind::InvalidInput,
-                "missing response".to_owned(),
-            )),
-        }
+        res.parse()
     }
 }
@@ -0,0 +1,97 @@
+//! # Unix client
+//!
+//! This module contains the implementation of the Unix domain socket
+//! client, based on [`tokio::net::UnixStream`].
+
+use async_trait::async_trait;
+use std::{io::Result, path::PathBuf};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt},
+    net::UnixStream,
+};
+
+use crate::{
+    request::{Request, RequestWriter},
+    response::{Response, ResponseReader},
+    unix::{UnixConfig, UnixHandler},
+};
+
+use super::{send, subscribe, Client, EventStream};
+
+/// The Unix domain socket client.
+///
+/// This [`Client`] uses a Unix domain socket to connect to a
+/// listener, to read responses and write requests.
+pub struct UnixClient {
+    /// The path of the socket the client should connect to.
+    pub path: PathBuf,
+
+    /// The name of the timer requests are sent to, or [`None`] for
+    /// the default timer.
+    pub timer: Option<String>,
+}
+
+impl UnixClient {
+    /// Create a new Unix client using the given socket path.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self::new_with_config(UnixConfig::new(path))
+    }
+
+    /// Create a new Unix client using the given configuration.
+    ///
+    /// The socket file permissions are ignored.
+    pub fn new_with_config(config: UnixConfig) -> Self {
+        Self {
+            path: config.path,
+            timer: None,
+        }
+    }
+
+    /// Create a new boxed Unix client using the given socket path.
+    pub fn new_boxed(path: impl Into<PathBuf>) -> Box<dyn Client> {
+        Box::new(Self::new(path))
+    }
+
+    /// Send requests to the timer matching the given name.
+    pub fn with_timer(mut self, name: impl ToString) -> Self {
+        self.timer = Some(name.to_string());
+        self
+    }
+}
+
+#[async_trait]
+impl Client for UnixClient {
+    /// Send the given request to the Unix server.
+    async fn send(&self, req: Request) -> Result<Response> {
+        let stream = UnixStream::connect(&self.path).await?;
+        let UnixHandler {
+            mut reader,
+            mut writer,
+        } = UnixHandler::from(stream);
+        send(&mut reader, &mut writer, None, self.timer.as_deref(), req).await
+    }
+
+    /// Subscribe to the events of the Unix server.
+    async fn subscribe(&self) -> Result<EventStream> {
+        let stream = UnixStream::connect(&self.path).await?;
+        let UnixHandler { reader, writer } = UnixHandler::from(stream);
+        subscribe(reader, writer, None, self.timer.as_deref()).await
+    }
+}
+
+#[async_trait]
+impl RequestWriter for UnixHandler {
+    async fn write(&mut self, req: Request) -> Result<()> {
+        self.writer.write_all(format!("{req}\n").as_bytes()).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ResponseReader for UnixHandler {
+    async fn read(&mut self) -> Result<Response> {
+        let mut res = String::new();
+        self.reader.read_line(&mut res).await?;
+        res.parse()
+    }
+}
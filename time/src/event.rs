@@ -0,0 +1,37 @@
+//! # Event
+//!
+//! This module contains the events emitted by servers and timers,
+//! which clients can subscribe to using [`Request::Subscribe`].
+//!
+//! [`Request::Subscribe`]: crate::request::Request::Subscribe
+
+use serde::{Deserialize, Serialize};
+
+use crate::timer::TimerEvent;
+
+/// The server state changed event.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub enum ServerEvent {
+    /// The server just started.
+    Started,
+
+    /// The server is stopping.
+    Stopping,
+
+    /// The server has stopped.
+    Stopped,
+}
+
+/// The event pushed to subscribed clients.
+///
+/// In the JSON protocol, events are serialized as `{"timer":…}` or
+/// `{"server":…}`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Event {
+    /// A timer event.
+    Timer(TimerEvent),
+
+    /// A server event.
+    Server(ServerEvent),
+}
@@ -0,0 +1,264 @@
+//! # History
+//!
+//! Every cycle run by the timer can be recorded to an append-only
+//! history file (see [`TimerConfig::history_file`]). This module
+//! contains the history entry structure, as well as the [`History`]
+//! store which allows to query totals per day, per cycle name and
+//! per tag.
+//!
+//! The history file contains one JSON entry per line.
+//!
+//! [`TimerConfig::history_file`]: crate::timer::TimerConfig::history_file
+
+use log::debug;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Result, Write},
+    path::{Path, PathBuf},
+};
+
+/// The history entry.
+///
+/// An entry describes a cycle run by the timer, from the moment it
+/// began to the moment it ended or the timer stopped.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The name of the cycle.
+    pub name: String,
+
+    /// The tags of the cycle.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// The configured duration of the cycle, in seconds.
+    pub planned: usize,
+
+    /// The time actually spent running the cycle, in seconds.
+    ///
+    /// Pauses are not included. This duration is lower than the
+    /// planned one when the timer stopped before the cycle ended.
+    pub duration: usize,
+
+    /// The time spent paused during the cycle, in seconds.
+    pub paused: usize,
+
+    /// The number of times the cycle has been paused.
+    pub pauses: usize,
+
+    /// When the cycle began, in seconds since the Unix epoch.
+    pub started_at: u64,
+
+    /// When the cycle ended, in seconds since the Unix epoch.
+    pub ended_at: u64,
+}
+
+/// The totals of a group of history entries.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Totals {
+    /// The number of cycles.
+    pub cycles: usize,
+
+    /// The time actually spent running the cycles, in seconds.
+    pub duration: usize,
+
+    /// The time spent paused during the cycles, in seconds.
+    pub paused: usize,
+}
+
+impl Totals {
+    fn add(&mut self, entry: &HistoryEntry) {
+        self.cycles += 1;
+        self.duration += entry.duration;
+        self.paused += entry.paused;
+    }
+}
+
+/// The cycle being recorded.
+///
+/// The timer keeps track of the cycle it is running, so that a
+/// [`HistoryEntry`] can be built when the cycle ends.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct CycleRecord {
+    /// When the cycle began, in seconds since the Unix epoch.
+    pub started_at: u64,
+
+    /// The timer elapsed time when the cycle began.
+    pub elapsed: usize,
+
+    /// When the cycle has been paused, if it is paused.
+    pub paused_at: Option<u64>,
+
+    /// The time spent paused so far, in seconds.
+    pub paused: usize,
+
+    /// The number of times the cycle has been paused so far.
+    pub pauses: usize,
+}
+
+impl CycleRecord {
+    pub fn new(started_at: u64, elapsed: usize) -> Self {
+        Self {
+            started_at,
+            elapsed,
+            ..Default::default()
+        }
+    }
+
+    pub fn pause(&mut self, now: u64) {
+        if self.paused_at.is_none() {
+            self.paused_at = Some(now);
+            self.pauses += 1;
+        }
+    }
+
+    pub fn resume(&mut self, now: u64) {
+        if let Some(paused_at) = self.paused_at.take() {
+            self.paused += now.saturating_sub(paused_at) as usize;
+        }
+    }
+
+    /// Build the history entry of the recorded cycle, given the
+    /// planned duration and the current timer elapsed time.
+    pub fn finish(
+        mut self,
+        name: impl ToString,
+        tags: Vec<String>,
+        planned: usize,
+        elapsed: usize,
+        now: u64,
+    ) -> HistoryEntry {
+        self.resume(now);
+
+        HistoryEntry {
+            name: name.to_string(),
+            tags,
+            planned,
+            duration: elapsed.saturating_sub(self.elapsed),
+            paused: self.paused,
+            pauses: self.pauses,
+            started_at: self.started_at,
+            ended_at: now,
+        }
+    }
+}
+
+/// The history store.
+///
+/// Entries are appended to a file, one JSON entry per line.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct History {
+    path: PathBuf,
+}
+
+impl History {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append the given entry to the history file.
+    pub fn append(&self, entry: &HistoryEntry) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(&line)
+    }
+
+    /// Read all the entries of the history file.
+    ///
+    /// A missing history file is considered empty. Invalid lines,
+    /// for example a line truncated by a crash, are skipped.
+    pub fn entries(&self) -> Result<Vec<HistoryEntry>> {
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err),
+        };
+
+        let entries = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| match serde_json::from_str(line) {
+                Ok(entry) => Some(entry),
+                Err(err) => {
+                    debug!("skipping invalid history entry {line}: {err}");
+                    None
+                }
+            })
+            .collect();
+
+        Ok(entries)
+    }
+
+    /// Compute the totals per day, using the `YYYY-MM-DD` format.
+    ///
+    /// Days are in UTC, and entries are counted on the day their
+    /// cycle began.
+    pub fn totals_per_day(&self) -> Result<BTreeMap<String, Totals>> {
+        Ok(totals_by(&self.entries()?, |entry| {
+            vec![format_day(entry.started_at)]
+        }))
+    }
+
+    /// Compute the totals per cycle name.
+    pub fn totals_per_cycle(&self) -> Result<BTreeMap<String, Totals>> {
+        Ok(totals_by(&self.entries()?, |entry| {
+            vec![entry.name.clone()]
+        }))
+    }
+
+    /// Compute the totals per tag.
+    ///
+    /// Entries with multiple tags are counted once per tag, and
+    /// entries without tags are not counted.
+    pub fn totals_per_tag(&self) -> Result<BTreeMap<String, Totals>> {
+        Ok(totals_by(&self.entries()?, |entry| entry.tags.clone()))
+    }
+}
+
+/// Group the given entries using the given keys function, then
+/// compute the totals of each group.
+fn totals_by(
+    entries: &[HistoryEntry],
+    keys: impl Fn(&HistoryEntry) -> Vec<String>,
+) -> BTreeMap<String, Totals> {
+    let mut totals = BTreeMap::<String, Totals>::new();
+
+    for entry in entries {
+        for key in keys(entry) {
+            totals.entry(key).or_default().add(entry);
+        }
+    }
+
+    totals
+}
+
+/// Format the given Unix timestamp as a `YYYY-MM-DD` UTC date.
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>.
+fn format_day(secs: u64) -> String {
+    let days = (secs / 86_400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{year:04}-{month:02}-{day:02}")
+}
@@ -0,0 +1,161 @@
+//! # Hook
+//!
+//! Hooks are shell commands executed when timer events are fired,
+//! so that the timer can be customized without writing a
+//! [`TimerConfig::handler`]. Hooks can be deserialized, for example
+//! from a TOML configuration file:
+//!
+//! ```toml
+//! [[hooks]]
+//! event = "ended"
+//! cycle = "Work"
+//! cmd = "notify-send {cycle} ended"
+//!
+//! [[hooks]]
+//! event = "began"
+//! cmd = "echo {cycle} began for {duration} seconds >> ~/timer.log"
+//! ```
+//!
+//! Commands are rendered as command templates (see
+//! [`TemplateVars`]), using the following placeholders:
+//!
+//! - `{event}`: the kebab-case name of the event, like `began`
+//! - `{cycle}`: the name of the cycle, or an empty string if the
+//!   event is not related to a cycle
+//! - `{duration}`: the remaining duration of the cycle, in seconds,
+//!   or an empty string if the event is not related to a cycle
+//!
+//! Values are quoted, so that cycle names cannot inject shell
+//! code. Placeholders must therefore not be quoted.
+//!
+//! [`TimerConfig::handler`]: crate::timer::TimerConfig::handler
+
+use process::{Command, TemplateVars};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::timer::{TimerCycle, TimerEvent};
+
+/// The timer event kind.
+///
+/// Enumeration of all the [`TimerEvent`] variants, without their
+/// data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TimerEventKind {
+    Started,
+    Began,
+    Running,
+    Set,
+    Paused,
+    Resumed,
+    Ended,
+    Stopped,
+}
+
+impl From<&TimerEvent> for TimerEventKind {
+    fn from(event: &TimerEvent) -> Self {
+        match event {
+            TimerEvent::Started => Self::Started,
+            TimerEvent::Began(_) => Self::Began,
+            TimerEvent::Running(_) => Self::Running,
+            TimerEvent::Set(_) => Self::Set,
+            TimerEvent::Paused(_) => Self::Paused,
+            TimerEvent::Resumed(_) => Self::Resumed,
+            TimerEvent::Ended(_) => Self::Ended,
+            TimerEvent::Stopped => Self::Stopped,
+        }
+    }
+}
+
+impl fmt::Display for TimerEventKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Started => write!(f, "started"),
+            Self::Began => write!(f, "began"),
+            Self::Running => write!(f, "running"),
+            Self::Set => write!(f, "set"),
+            Self::Paused => write!(f, "paused"),
+            Self::Resumed => write!(f, "resumed"),
+            Self::Ended => write!(f, "ended"),
+            Self::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+/// The timer hook.
+///
+/// A hook executes a command when events of the given kind are
+/// fired, optionally only for the cycle matching the given name.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TimerHook {
+    /// The kind of events triggering the hook.
+    pub event: TimerEventKind,
+
+    /// The name of the cycle triggering the hook.
+    ///
+    /// When defined, events not related to this cycle, including
+    /// events not related to any cycle, do not trigger the hook.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<String>,
+
+    /// The command executed by the hook.
+    pub cmd: Command,
+}
+
+impl TimerHook {
+    pub fn new(event: TimerEventKind, cmd: impl Into<Command>) -> Self {
+        Self {
+            event,
+            cycle: None,
+            cmd: cmd.into(),
+        }
+    }
+
+    pub fn with_cycle(mut self, name: impl ToString) -> Self {
+        self.cycle = Some(name.to_string());
+        self
+    }
+
+    /// Return the command to execute for the given event, with its
+    /// placeholders rendered, or [`None`] if the event does not
+    /// trigger the hook.
+    pub fn command(&self, event: &TimerEvent) -> process::Result<Option<Command>> {
+        let kind = TimerEventKind::from(event);
+
+        if kind != self.event {
+            return Ok(None);
+        }
+
+        let cycle = match event {
+            TimerEvent::Began(cycle)
+            | TimerEvent::Running(cycle)
+            | TimerEvent::Set(cycle)
+            | TimerEvent::Paused(cycle)
+            | TimerEvent::Resumed(cycle)
+            | TimerEvent::Ended(cycle) => Some(cycle),
+            TimerEvent::Started | TimerEvent::Stopped => None,
+        };
+
+        match (&self.cycle, cycle) {
+            (Some(name), Some(cycle)) if name != &cycle.name => return Ok(None),
+            (Some(_), None) => return Ok(None),
+            _ => (),
+        }
+
+        let (name, duration) = match cycle {
+            Some(TimerCycle { name, duration, .. }) => (name.clone(), duration.to_string()),
+            None => (String::new(), String::new()),
+        };
+
+        let vars = TemplateVars::new()
+            .with_var("event", kind)
+            .with_var("cycle", name)
+            .with_var("duration", duration);
+
+        let cmd = self.cmd.clone().render(&vars)?;
+
+        Ok(Some(cmd))
+    }
+}
@@ -52,7 +52,12 @@
 
 #[cfg(feature = "client")]
 pub mod client;
+pub mod event;
 pub(crate) mod handler;
+pub mod history;
+#[cfg(feature = "hooks")]
+pub mod hook;
+pub mod protocol;
 pub mod request;
 pub mod response;
 #[cfg(feature = "server")]
@@ -60,3 +65,5 @@
 #[cfg(feature = "tcp-any")]
 pub mod tcp;
 pub mod timer;
+#[cfg(all(unix, feature = "unix-any"))]
+pub mod unix;
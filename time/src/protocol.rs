@@ -0,0 +1,237 @@
+//! # Protocol
+//!
+//! This module contains the versioned JSON protocol spoken by the
+//! built-in binders and clients.
+//!
+//! Frames are JSON objects, one per line. A session starts with a
+//! handshake: the client sends the highest protocol version it
+//! supports, and the server answers with the version used for the
+//! rest of the session. The client can then send as many requests as
+//! it wants, each one identified by an ID chosen by the client. The
+//! server answers each request with a response holding the same ID:
+//!
+//! ```text,ignore
+//! → {"hello":{"version":1}}
+//! ← {"hello":{"version":1}}
+//! → {"id":1,"request":"start"}
+//! ← {"id":1,"response":"ok"}
+//! → {"id":2,"request":{"set":60}}
+//! ← {"id":2,"response":"ok"}
+//! → {"id":3,"request":"get"}
+//! ← {"id":3,"response":{"timer":{"state":"Running","cycle":{"name":"Work","duration":60},"cycles_count":"Infinite","elapsed":0}}}
+//! → {"id":4,"request":"unknown"}
+//! ← {"response":{"error":"invalid frame: …"}}
+//! ```
+//!
+//! A client sending the `subscribe` request keeps receiving events
+//! (see [`Event`](crate::event::Event)) as responses holding the ID
+//! of the subscribe request, while it can still send other
+//! requests. The connection is closed by the server after the
+//! `Stopped` server event:
+//!
+//! ```text,ignore
+//! → {"id":5,"request":"subscribe"}
+//! ← {"id":5,"response":"ok"}
+//! ← {"id":5,"response":{"event":{"timer":{"Running":{"name":"Work","duration":59}}}}}
+//! ← {"id":5,"response":{"event":{"server":"Stopping"}}}
+//! ← {"id":5,"response":{"event":{"server":"Stopped"}}}
+//! ```
+//!
+//! Servers can run multiple named timers. Requests are sent to the
+//! timer named by the optional `timer` field, or to the default
+//! timer (see [`DEFAULT_TIMER`](crate::timer::DEFAULT_TIMER)).
+//! Subscriptions receive the events of the addressed timer only,
+//! plus server events. The `list` request returns all the timers:
+//!
+//! ```text,ignore
+//! → {"id":6,"timer":"meeting","request":"start"}
+//! ← {"id":6,"response":"ok"}
+//! → {"id":7,"request":"list"}
+//! ← {"id":7,"response":{"timers":{"default":{…},"meeting":{…}}}}
+//! ```
+//!
+//! Requests are serialized as their kebab-case name, or as an object
+//! when they hold data (see [`Request`]). Responses are either `"ok"`,
+//! a timer, all the timers, history totals or an error (see
+//! [`Response`]). Responses to frames that cannot be parsed have no
+//! ID. Unknown fields are ignored, so that new fields can be added
+//! without bumping the protocol version.
+//!
+//! For backward compatibility, servers still accept the legacy line
+//! protocol (see the [`FromStr`](std::str::FromStr) implementations
+//! of [`Request`] and [`Response`]): a connection whose first line
+//! does not start with `{` is handled as a single legacy request,
+//! sent to the default timer.
+//!
+//! Binders can require a shared token, sent by the client in its
+//! hello frame. Sessions with a missing or invalid token are closed
+//! right after an error response, and legacy requests are rejected:
+//!
+//! ```text,ignore
+//! → {"hello":{"version":1,"token":"secret"}}
+//! ← {"hello":{"version":1}}
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::{request::Request, response::Response};
+
+/// The current version of the protocol.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The maximum length of frames sent by clients, new line
+/// included. Servers close connections sending longer frames.
+pub const MAX_FRAME_LEN: u64 = 64 * 1024;
+
+/// The handshake content.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Hello {
+    /// The protocol version.
+    ///
+    /// Sent by the client, it is the highest version supported by
+    /// the client. Sent back by the server, it is the version used
+    /// for the rest of the session.
+    pub version: u32,
+
+    /// The shared token authenticating the client.
+    ///
+    /// Only sent by clients, when the server requires it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+}
+
+impl Default for Hello {
+    fn default() -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            token: None,
+        }
+    }
+}
+
+/// The handshake frame, sent by both clients and servers.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Deserialize, Serialize)]
+pub struct HelloFrame {
+    pub hello: Hello,
+}
+
+/// The request frame, sent by clients.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct RequestFrame {
+    /// The request ID, chosen by the client.
+    pub id: u64,
+
+    /// The name of the timer the request is sent to, or [`None`]
+    /// for the default timer.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timer: Option<String>,
+
+    /// The request itself.
+    pub request: Request,
+}
+
+/// The response frame, sent by servers.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct ResponseFrame {
+    /// The ID of the request the response answers, or [`None`] if
+    /// the request frame could not be parsed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<u64>,
+
+    /// The response itself.
+    pub response: Response,
+}
+
+impl ResponseFrame {
+    /// Create a response frame without ID, used for errors not
+    /// related to a specific request.
+    pub fn error(err: impl ToString) -> Self {
+        Self {
+            id: None,
+            response: Response::Error(err.to_string()),
+        }
+    }
+}
+
+/// The frames sent by clients.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ClientFrame {
+    Hello(HelloFrame),
+    Request(RequestFrame),
+}
+
+/// The frames sent by servers.
+// frames only live the time of a read or a write
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum ServerFrame {
+    Hello(HelloFrame),
+    Response(ResponseFrame),
+}
+
+/// Read one line, or [`None`] at the end of the stream.
+#[cfg(any(feature = "tcp-client", feature = "unix-client"))]
+pub(crate) async fn read_line(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+) -> std::io::Result<Option<String>> {
+    use tokio::io::AsyncBufReadExt;
+
+    let mut line = String::new();
+    let n = reader.read_line(&mut line).await?;
+    Ok(if n == 0 { None } else { Some(line) })
+}
+
+/// Read the rest of one frame line into the given buffer, returning
+/// the number of bytes read, or 0 at the end of the stream.
+///
+/// The line is bounded by [`MAX_FRAME_LEN`], longer lines return an
+/// error. Like [`read_until`], this function is cancel safe: bytes
+/// read before the cancellation stay in the buffer, and the next call
+/// continues from there.
+///
+/// [`read_until`]: tokio::io::AsyncBufReadExt::read_until
+#[cfg(any(feature = "tcp-binder", feature = "unix-binder"))]
+pub(crate) async fn read_frame_line(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+    line: &mut Vec<u8>,
+) -> std::io::Result<usize> {
+    use std::io::{Error, ErrorKind};
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt};
+
+    let limit = MAX_FRAME_LEN.saturating_sub(line.len() as u64);
+    let n = (&mut *reader).take(limit).read_until(b'\n', line).await?;
+
+    if line.len() as u64 >= MAX_FRAME_LEN && !line.ends_with(b"\n") {
+        let err = format!("invalid frame: longer than {MAX_FRAME_LEN} bytes");
+        return Err(Error::new(ErrorKind::InvalidData, err));
+    }
+
+    Ok(n)
+}
+
+/// Write the given frame as a line of JSON.
+#[cfg(any(feature = "tcp-any", feature = "unix-any"))]
+pub(crate) async fn write_frame(
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    frame: &impl Serialize,
+) -> std::io::Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut frame = serde_json::to_vec(frame)?;
+    frame.push(b'\n');
+    writer.write_all(&frame).await?;
+    writer.flush().await
+}
+
+/// Parse the given line as a JSON frame.
+#[cfg(any(feature = "tcp-any", feature = "unix-any"))]
+pub(crate) fn parse_frame<T: serde::de::DeserializeOwned>(line: &str) -> std::io::Result<T> {
+    use std::io::{Error, ErrorKind};
+
+    serde_json::from_str(line).map_err(|err| {
+        let err = format!("invalid frame: {err}");
+        Error::new(ErrorKind::InvalidData, err)
+    })
+}
@@ -5,12 +5,21 @@
 //! structure as well as trait to read and write a request.
 
 use async_trait::async_trait;
-use std::io::Result;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
 
 /// The client request struct.
 ///
-/// Requests are sent by clients and received by servers.
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Requests are sent by clients and received by servers. In the JSON
+/// protocol, requests are serialized as their kebab-case name, or as
+/// an object when they hold data, for example `"start"` or
+/// `{"set":60}`.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Request {
     /// Request the timer to start with the first configured cycle.
     Start,
@@ -21,6 +30,18 @@ pub enum Request {
     /// Request to change the current timer duration.
     Set(usize),
 
+    /// Request to add the given duration to the current cycle.
+    Add(usize),
+
+    /// Request to subtract the given duration from the current
+    /// cycle.
+    Subtract(usize),
+
+    /// Request to skip the current cycle and begin the next one.
+    ///
+    /// Has no effect if the timer is stopped.
+    Next,
+
     /// Request to pause the timer.
     ///
     /// A paused timer freezes, which means it keeps its state, cycle
@@ -36,6 +57,97 @@ pub enum Request {
     ///
     /// Stopping the timer resets the state, the cycle and the value.
     Stop,
+
+    /// Request to receive events as they happen.
+    ///
+    /// The connection stays open, and the server pushes every
+    /// [`Event`](crate::event::Event) to the client until it
+    /// disconnects.
+    Subscribe,
+
+    /// Request the history totals per day.
+    TotalsPerDay,
+
+    /// Request the history totals per cycle name.
+    TotalsPerCycle,
+
+    /// Request the history totals per tag.
+    TotalsPerTag,
+
+    /// Request all the timers of the server, by name.
+    List,
+}
+
+/// Parse a request from a line of the text protocol, for example
+/// `set 60`.
+impl FromStr for Request {
+    type Err = Error;
+
+    fn from_str(req: &str) -> Result<Self> {
+        let mut tokens = req.split_whitespace();
+        match tokens.next() {
+            Some("start") => Ok(Request::Start),
+            Some("get") => Ok(Request::Get),
+            Some("set") => parse_duration(tokens.next()).map(Request::Set),
+            Some("add") => parse_duration(tokens.next()).map(Request::Add),
+            Some("subtract") => parse_duration(tokens.next()).map(Request::Subtract),
+            Some("next") => Ok(Request::Next),
+            Some("pause") => Ok(Request::Pause),
+            Some("resume") => Ok(Request::Resume),
+            Some("stop") => Ok(Request::Stop),
+            Some("subscribe") => Ok(Request::Subscribe),
+            Some("totals-per-day") => Ok(Request::TotalsPerDay),
+            Some("totals-per-cycle") => Ok(Request::TotalsPerCycle),
+            Some("totals-per-tag") => Ok(Request::TotalsPerTag),
+            Some("list") => Ok(Request::List),
+            Some(req) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid request: {req}"),
+            )),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "missing request".to_owned(),
+            )),
+        }
+    }
+}
+
+/// Parse the duration argument of a request line.
+fn parse_duration(duration: Option<&str>) -> Result<usize> {
+    match duration.map(|duration| duration.parse::<usize>()) {
+        Some(Ok(duration)) => Ok(duration),
+        Some(Err(err)) => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("invalid duration: {err}"),
+        )),
+        None => Err(Error::new(
+            ErrorKind::InvalidInput,
+            "missing duration".to_owned(),
+        )),
+    }
+}
+
+/// Format a request as a line of the text protocol, without the
+/// trailing new line.
+impl fmt::Display for Request {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::Start => write!(f, "start"),
+            Request::Get => write!(f, "get"),
+            Request::Set(duration) => write!(f, "set {duration}"),
+            Request::Add(duration) => write!(f, "add {duration}"),
+            Request::Subtract(duration) => write!(f, "subtract {duration}"),
+            Request::Next => write!(f, "next"),
+            Request::Pause => write!(f, "pause"),
+            Request::Resume => write!(f, "resume"),
+            Request::Stop => write!(f, "stop"),
+            Request::Subscribe => write!(f, "subscribe"),
+            Request::TotalsPerDay => write!(f, "totals-per-day"),
+            Request::TotalsPerCycle => write!(f, "totals-per-cycle"),
+            Request::TotalsPerTag => write!(f, "totals-per-tag"),
+            Request::List => write!(f, "list"),
+        }
+    }
 }
 
 /// Trait to read a client request.
@@ -5,20 +5,116 @@
 //! and write a response.
 
 use async_trait::async_trait;
-use std::io::Result;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    str::FromStr,
+};
 
-use crate::timer::Timer;
+use crate::{event::Event, history::Totals, timer::Timer};
 
 /// The server response struct.
 ///
-/// Responses are sent by servers and received by clients.
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Responses are sent by servers and received by clients. In the
+/// JSON protocol, responses are serialized as `"ok"`, `{"timer":…}`
+/// or `{"error":"…"}`.
+// responses only live the time of a read or a write
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Response {
     /// Default response when everything goes as expected.
     Ok,
 
     /// Response containing the current timer.
     Timer(Timer),
+
+    /// Response sent when the request could not be processed,
+    /// containing the error message.
+    Error(String),
+
+    /// Response pushed to subscribed clients, containing an event.
+    Event(Event),
+
+    /// Response containing history totals, grouped by day, cycle
+    /// name or tag.
+    Totals(BTreeMap<String, Totals>),
+
+    /// Response containing all the timers of the server, by name.
+    Timers(BTreeMap<String, Timer>),
+}
+
+/// Parse a response from a line of the text protocol, for example
+/// `ok`.
+impl FromStr for Response {
+    type Err = Error;
+
+    fn from_str(res: &str) -> Result<Self> {
+        let mut tokens = res.split_whitespace();
+        match tokens.next() {
+            Some("ok") => Ok(Response::Ok),
+            Some("timer") => parse_json(res, "timer").map(Response::Timer),
+            Some("event") => parse_json(res, "event").map(Response::Event),
+            Some("totals") => parse_json(res, "totals").map(Response::Totals),
+            Some("timers") => parse_json(res, "timers").map(Response::Timers),
+            Some("error") => {
+                let err = tokens.collect::<Vec<_>>().join(" ");
+                Ok(Response::Error(err))
+            }
+            Some(res) => Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid response: {res}"),
+            )),
+            None => Err(Error::new(
+                ErrorKind::InvalidInput,
+                "missing response".to_owned(),
+            )),
+        }
+    }
+}
+
+/// Format a response as a line of the text protocol, without the
+/// trailing new line.
+impl fmt::Display for Response {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Response::Ok => write!(f, "ok"),
+            Response::Timer(timer) => {
+                let timer = serde_json::to_string(timer).map_err(|_| fmt::Error)?;
+                write!(f, "timer {timer}")
+            }
+            Response::Error(err) => write!(f, "error {err}"),
+            Response::Event(event) => {
+                let event = serde_json::to_string(event).map_err(|_| fmt::Error)?;
+                write!(f, "event {event}")
+            }
+            Response::Totals(totals) => {
+                let totals = serde_json::to_string(totals).map_err(|_| fmt::Error)?;
+                write!(f, "totals {totals}")
+            }
+            Response::Timers(timers) => {
+                let timers = serde_json::to_string(timers).map_err(|_| fmt::Error)?;
+                write!(f, "timers {timers}")
+            }
+        }
+    }
+}
+
+/// Parse the JSON following the given response name.
+///
+/// JSON values may contain spaces, like cycle names or map keys, so
+/// the whole remaining line is parsed.
+fn parse_json<T: DeserializeOwned>(res: &str, name: &str) -> Result<T> {
+    match res.trim_start()[name.len()..].trim() {
+        "" => Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("missing {name}"),
+        )),
+        json => serde_json::from_str(json)
+            .map_err(|err| Error::new(ErrorKind::InvalidInput, format!("invalid {name}: {err}"))),
+    }
 }
 
 /// Trait to read a server response.
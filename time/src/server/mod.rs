@@ -1,32 +1,45 @@
 //! # Server
 //!
 //! This module contains everything related to servers. The server
-//! runs the timer, accepts connections from clients and sends
+//! runs the timers, accepts connections from clients and sends
 //! responses. It accepts connections using server binders. A server
 //! should have at least one binder, otherwise it stops by itself.
 //!
+//! A server runs a default timer, plus any number of named timers
+//! (see [`ServerBuilder::with_timer`]).
+//!
 //!
 
 #[cfg(feature = "tcp-binder")]
 pub mod tcp;
+#[cfg(all(unix, feature = "unix-binder"))]
+pub mod unix;
 
 use async_trait::async_trait;
 use log::{debug, trace};
 use std::{
+    collections::BTreeMap,
     fmt::Debug,
     future::Future,
     io::{Error, ErrorKind, Result},
     ops::{Deref, DerefMut},
+    path::PathBuf,
     sync::Arc,
     time::Duration,
 };
 use tokio::{sync::Mutex, task, time};
 
+#[doc(inline)]
+pub use crate::event::ServerEvent;
 use crate::{
+    event::Event,
     handler::{self, Handler},
     request::{Request, RequestReader},
     response::{Response, ResponseWriter},
-    timer::{ThreadSafeTimer, TimerConfig, TimerCycle, TimerEvent, TimerLoop},
+    timer::{
+        ThreadSafeTimer, ThreadSafeTimers, TimerConfig, TimerCycle, TimerEvent, TimerLoop,
+        DEFAULT_TIMER,
+    },
 };
 
 /// The server state enum.
@@ -63,19 +76,6 @@ fn default() -> Self {
     }
 }
 
-/// The server state changed event.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum ServerEvent {
-    /// The server just started.
-    Started,
-
-    /// The server is stopping.
-    Stopping,
-
-    /// The server has stopped.
-    Stopped,
-}
-
 /// Thread safe version of the server state.
 #[derive(Clone, Debug, Default)]
 pub struct ThreadSafeState(Arc<Mutex<ServerState>>);
@@ -129,7 +129,7 @@ fn deref_mut(&mut self) -> &mut Self::Target {
 pub trait ServerBind: Debug + Send + Sync {
     /// Describe how the server should bind to accept connections from
     /// clients.
-    async fn bind(&self, timer: ThreadSafeTimer) -> Result<()>;
+    async fn bind(&self, timers: ThreadSafeTimers) -> Result<()>;
 }
 
 /// The server stream trait.
@@ -138,47 +138,364 @@ pub trait ServerBind: Debug + Send + Sync {
 #[async_trait]
 pub trait ServerStream: RequestReader + ResponseWriter {
     /// Read the request, process it then write the response.
-    async fn handle(&mut self, timer: ThreadSafeTimer) -> Result<()> {
+    ///
+    /// Requests are sent to the default timer.
+    async fn handle(&mut self, timers: ThreadSafeTimers) -> Result<()> {
         let req = self.read().await?;
+        let res = process(&timers, None, req).await;
+        self.write(res).await?;
+        Ok(())
+    }
+}
+
+impl<T: RequestReader + ResponseWriter> ServerStream for T {}
+
+/// Process the given request sent to the timer matching the given
+/// name, then return the response.
+///
+/// Errors are returned as [`Response::Error`].
+pub(crate) async fn process(
+    timers: &ThreadSafeTimers,
+    name: Option<&str>,
+    req: Request,
+) -> Response {
+    let res = match req {
+        Request::List => {
+            debug!("listing timers");
+            Ok(Response::Timers(timers.get_all().await))
+        }
+        req => match timers.timer(name) {
+            Ok(timer) => process_timer(&timer, req).await,
+            Err(err) => Err(err),
+        },
+    };
+
+    res.unwrap_or_else(|err| {
+        debug!("cannot process request: {err}");
+        Response::Error(err.to_string())
+    })
+}
+
+/// Process the given request sent to the given timer.
+async fn process_timer(timer: &ThreadSafeTimer, req: Request) -> Result<Response> {
+    match req {
+        Request::Start => {
+            debug!("starting timer");
+            timer.start().await.map(|()| Response::Ok)
+        }
+        Request::Get => {
+            debug!("getting timer");
+            let timer = timer.get().await;
+            trace!("{timer:#?}");
+            Ok(Response::Timer(timer))
+        }
+        Request::Set(duration) => {
+            debug!("setting timer");
+            timer.set(duration).await.map(|()| Response::Ok)
+        }
+        Request::Add(duration) => {
+            debug!("adding time to timer");
+            timer.add(duration).await.map(|()| Response::Ok)
+        }
+        Request::Subtract(duration) => {
+            debug!("subtracting time from timer");
+            timer.subtract(duration).await.map(|()| Response::Ok)
+        }
+        Request::Next => {
+            debug!("skipping timer cycle");
+            timer.next().await.map(|()| Response::Ok)
+        }
+        Request::Pause => {
+            debug!("pausing timer");
+            timer.pause().await.map(|()| Response::Ok)
+        }
+        Request::Resume => {
+            debug!("resuming timer");
+            timer.resume().await.map(|()| Response::Ok)
+        }
+        Request::Stop => {
+            debug!("stopping timer");
+            timer.stop().await.map(|()| Response::Ok)
+        }
+        Request::Subscribe => Err(Error::new(
+            ErrorKind::Unsupported,
+            "cannot subscribe from this stream",
+        )),
+        Request::TotalsPerDay => {
+            debug!("computing history totals per day");
+            let history = timer.history().await;
+            history
+                .and_then(|h| h.totals_per_day())
+                .map(Response::Totals)
+        }
+        Request::TotalsPerCycle => {
+            debug!("computing history totals per cycle");
+            let history = timer.history().await;
+            history
+                .and_then(|h| h.totals_per_cycle())
+                .map(Response::Totals)
+        }
+        Request::TotalsPerTag => {
+            debug!("computing history totals per tag");
+            let history = timer.history().await;
+            history
+                .and_then(|h| h.totals_per_tag())
+                .map(Response::Totals)
+        }
+        Request::List => Err(Error::new(
+            ErrorKind::Unsupported,
+            "cannot list timers from a timer",
+        )),
+    }
+}
+
+/// The maximum duration clients of built-in binders have to send
+/// their hello frame (or their legacy request), after which they are
+/// disconnected.
+#[cfg(any(feature = "tcp-binder", feature = "unix-binder"))]
+pub const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Serve a client connection of a built-in binder, using either the
+/// JSON protocol or the legacy line protocol.
+///
+/// When a token is given, clients must send it in their hello frame,
+/// and legacy requests are rejected. Clients must send their first
+/// frame within [`HELLO_TIMEOUT`], and frames cannot be longer than
+/// [`MAX_FRAME_LEN`](crate::protocol::MAX_FRAME_LEN).
+///
+/// See the [`protocol`](crate::protocol) module.
+#[cfg(any(feature = "tcp-binder", feature = "unix-binder"))]
+pub(crate) async fn serve(
+    reader: &mut (impl tokio::io::AsyncBufRead + Unpin),
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    timers: ThreadSafeTimers,
+    token: Option<&str>,
+) -> Result<()> {
+    use tokio::sync::broadcast::{self, error::RecvError};
+
+    use crate::protocol::{
+        self, ClientFrame, Hello, HelloFrame, ResponseFrame, ServerFrame, PROTOCOL_VERSION,
+    };
+
+    let mut line = Vec::new();
+
+    match time::timeout(HELLO_TIMEOUT, protocol::read_frame_line(reader, &mut line)).await {
+        Ok(Ok(0)) => return Ok(()),
+        Ok(Ok(_)) => (),
+        Ok(Err(err)) => return Err(err),
+        Err(_) => {
+            let err = "client did not send its hello frame in time";
+            return Err(Error::new(ErrorKind::TimedOut, err));
+        }
+    }
+
+    let line = String::from_utf8(line).map_err(|err| {
+        let err = format!("invalid frame: {err}");
+        Error::new(ErrorKind::InvalidData, err)
+    })?;
+
+    if !line.trim_start().starts_with('{') {
+        if token.is_some() {
+            debug!("rejecting unauthenticated legacy request");
+            let res = Response::Error(String::from("authentication required"));
+            return write_legacy_response(writer, res).await;
+        }
+
+        debug!("serving legacy request");
+
+        let req = line.parse::<Request>();
+        let mut events = None;
+
         let res = match req {
-            Request::Start => {
-                debug!("starting timer");
-                timer.start().await?;
-                Response::Ok
-            }
-            Request::Get => {
-                debug!("getting timer");
-                let timer = timer.get().await;
-                trace!("{timer:#?}");
-                Response::Timer(timer)
-            }
-            Request::Set(duration) => {
-                debug!("setting timer");
-                timer.set(duration).await?;
-                Response::Ok
-            }
-            Request::Pause => {
-                debug!("pausing timer");
-                timer.pause().await?;
-                Response::Ok
+            Ok(Request::Subscribe) => match timers.timer(None) {
+                Ok(timer) => {
+                    events = Some(timer.subscribe());
+                    Response::Ok
+                }
+                Err(err) => Response::Error(err.to_string()),
+            },
+            Ok(req) => process(&timers, None, req).await,
+            Err(err) => Response::Error(err.to_string()),
+        };
+
+        write_legacy_response(writer, res).await?;
+
+        // subscribed clients receive events till they disconnect or
+        // the server stops
+        if let Some(mut events) = events {
+            loop {
+                match events.recv().await {
+                    Ok(event) => {
+                        let stopped = is_server_stopped(&event);
+                        write_legacy_response(writer, Response::Event(event)).await?;
+                        if stopped {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(n)) => debug!("subscriber lagged, skipping {n} events"),
+                    Err(RecvError::Closed) => break,
+                }
             }
-            Request::Resume => {
-                debug!("resuming timer");
-                timer.resume().await?;
-                Response::Ok
+        }
+
+        return Ok(());
+    }
+
+    let hello = match protocol::parse_frame(&line) {
+        Ok(ClientFrame::Hello(HelloFrame { hello })) if hello.version > 0 => hello,
+        Ok(ClientFrame::Hello(HelloFrame { hello })) => {
+            let err = format!("unsupported protocol version {}", hello.version);
+            let frame = ServerFrame::Response(ResponseFrame::error(err));
+            return protocol::write_frame(writer, &frame).await;
+        }
+        Ok(ClientFrame::Request(_)) => {
+            let frame = ServerFrame::Response(ResponseFrame::error("expected hello frame"));
+            return protocol::write_frame(writer, &frame).await;
+        }
+        Err(err) => {
+            let frame = ServerFrame::Response(ResponseFrame::error(err));
+            return protocol::write_frame(writer, &frame).await;
+        }
+    };
+
+    if !verify_token(token, hello.token.as_deref()) {
+        debug!("rejecting session with invalid token");
+        let frame = ServerFrame::Response(ResponseFrame::error("invalid token"));
+        return protocol::write_frame(writer, &frame).await;
+    }
+
+    let version = hello.version.min(PROTOCOL_VERSION);
+    debug!("serving protocol version {version}");
+    let hello = HelloFrame {
+        hello: Hello {
+            version,
+            token: None,
+        },
+    };
+    protocol::write_frame(writer, &ServerFrame::Hello(hello)).await?;
+
+    // the subscription ID and the events receiver, once the client
+    // subscribed
+    let mut subscription: Option<(u64, broadcast::Receiver<Event>)> = None;
+
+    // the line buffer is kept across iterations: reading until a new
+    // line is cancel safe, bytes read before an event arrives stay in
+    // the buffer and the next read continues from there
+    let mut line = Vec::new();
+
+    loop {
+        let n = match &mut subscription {
+            None => protocol::read_frame_line(reader, &mut line).await?,
+            Some((id, events)) => tokio::select! {
+                n = protocol::read_frame_line(reader, &mut line) => n?,
+                event = events.recv() => {
+                    let event = match event {
+                        Ok(event) => event,
+                        Err(RecvError::Lagged(n)) => {
+                            debug!("subscriber lagged, skipping {n} events");
+                            continue;
+                        }
+                        Err(RecvError::Closed) => return Ok(()),
+                    };
+                    let stopped = is_server_stopped(&event);
+                    let response = Response::Event(event);
+                    let frame = ResponseFrame { id: Some(*id), response };
+                    protocol::write_frame(writer, &ServerFrame::Response(frame)).await?;
+                    if stopped {
+                        return Ok(());
+                    }
+                    continue;
+                }
+            },
+        };
+
+        if n == 0 {
+            break;
+        }
+
+        let frame = match std::str::from_utf8(&line) {
+            Ok(line) if line.trim().is_empty() => None,
+            Ok(line) => Some(protocol::parse_frame(line)),
+            Err(err) => {
+                let err = format!("invalid frame: {err}");
+                Some(Err(Error::new(ErrorKind::InvalidData, err)))
             }
-            Request::Stop => {
-                debug!("stopping timer");
-                timer.stop().await?;
-                Response::Ok
+        };
+
+        line.clear();
+
+        let Some(frame) = frame else {
+            continue;
+        };
+
+        let frame = match frame {
+            Ok(ClientFrame::Request(frame)) if frame.request == Request::Subscribe => {
+                let response = match timers.timer(frame.timer.as_deref()) {
+                    Ok(timer) => {
+                        subscription = Some((frame.id, timer.subscribe()));
+                        Response::Ok
+                    }
+                    Err(err) => Response::Error(err.to_string()),
+                };
+                ResponseFrame {
+                    id: Some(frame.id),
+                    response,
+                }
             }
+            Ok(ClientFrame::Request(frame)) => ResponseFrame {
+                id: Some(frame.id),
+                response: process(&timers, frame.timer.as_deref(), frame.request).await,
+            },
+            Ok(ClientFrame::Hello(_)) => ResponseFrame::error("unexpected hello frame"),
+            Err(err) => ResponseFrame::error(err),
         };
-        self.write(res).await?;
-        Ok(())
+
+        protocol::write_frame(writer, &ServerFrame::Response(frame)).await?;
     }
+
+    Ok(())
 }
 
-impl<T: RequestReader + ResponseWriter> ServerStream for T {}
+/// Return `true` if the given event is the last one sent by a
+/// stopping server, which ends subscriptions.
+#[cfg(any(feature = "tcp-binder", feature = "unix-binder"))]
+fn is_server_stopped(event: &Event) -> bool {
+    matches!(event, Event::Server(ServerEvent::Stopped))
+}
+
+/// Check the token sent by the client against the expected one, if
+/// any.
+///
+/// Tokens are hashed to fixed-size digests, which are then compared
+/// in constant time. This makes guessing the expected token by
+/// measuring response times harder.
+#[cfg(any(feature = "tcp-binder", feature = "unix-binder"))]
+fn verify_token(expected: Option<&str>, token: Option<&str>) -> bool {
+    use sha2::{Digest, Sha256};
+    use subtle::ConstantTimeEq;
+
+    match (expected, token) {
+        (None, _) => true,
+        (Some(_), None) => false,
+        (Some(expected), Some(token)) => {
+            let expected = Sha256::digest(expected);
+            let token = Sha256::digest(token);
+            expected.as_slice().ct_eq(token.as_slice()).into()
+        }
+    }
+}
+
+/// Write the given response using the legacy line protocol.
+#[cfg(any(feature = "tcp-binder", feature = "unix-binder"))]
+async fn write_legacy_response(
+    writer: &mut (impl tokio::io::AsyncWrite + Unpin),
+    res: Response,
+) -> Result<()> {
+    use tokio::io::AsyncWriteExt;
+
+    let res = res.to_string().replace('\n', " ");
+    writer.write_all(format!("{res}\n").as_bytes()).await
+}
 
 /// The server struct.
 #[derive(Default)]
@@ -189,12 +506,12 @@ pub struct Server {
     /// The current server state.
     state: ThreadSafeState,
 
-    /// The current server timer.
-    timer: ThreadSafeTimer,
+    /// The current server timers.
+    timers: ThreadSafeTimers,
 }
 
 impl Server {
-    /// Start the server by running the timer in a dedicated thread as
+    /// Start the server by running the timers in a dedicated thread as
     /// well as all the binders in dedicated threads.
     ///
     /// The main thread is then blocked by the given `wait` closure.
@@ -205,8 +522,10 @@ pub async fn bind_with<F: Future<Output = Result<()>>>(
         debug!("starting server");
 
         let handler = &self.config.handler;
+        let timers = &self.timers;
         let fire_event = |event: ServerEvent| async move {
             debug!("firing server event {event:?}");
+            timers.notify(Event::Server(event.clone()));
             if let Err(err) = handler(event.clone()).await {
                 debug!("error while firing server event, skipping it");
                 debug!("{err:?}");
@@ -216,9 +535,9 @@ pub async fn bind_with<F: Future<Output = Result<()>>>(
         self.state.set_running().await;
         fire_event(ServerEvent::Started).await;
 
-        // the tick represents the timer running in a separated thread
+        // the tick represents the timers running in a separated thread
         let state = self.state.clone();
-        let timer = self.timer.clone();
+        let timers = self.timers.clone();
         let tick = task::spawn(async move {
             loop {
                 let mut state = state.lock().await;
@@ -231,7 +550,7 @@ pub async fn bind_with<F: Future<Output = Result<()>>>(
                         break;
                     }
                     ServerState::Running => {
-                        timer.update().await;
+                        timers.update().await;
                     }
                 };
                 drop(state);
@@ -243,10 +562,10 @@ pub async fn bind_with<F: Future<Output = Result<()>>>(
         // start all binders in dedicated threads in order not to
         // block the main thread
         for binder in self.config.binders {
-            let timer = self.timer.clone();
+            let timers = self.timers.clone();
             task::spawn(async move {
                 debug!("binding {binder:?}");
-                if let Err(err) = binder.bind(timer).await {
+                if let Err(err) = binder.bind(timers).await {
                     debug!("error while binding, skipping it");
                     debug!("{err:?}");
                 }
@@ -288,8 +607,11 @@ pub struct ServerBuilder {
     /// The server configuration.
     server_config: ServerConfig,
 
-    /// The timer configuration.
+    /// The default timer configuration.
     timer_config: TimerConfig,
+
+    /// The named timers configurations.
+    named_timer_configs: BTreeMap<String, TimerConfig>,
 }
 
 impl ServerBuilder {
@@ -391,18 +713,80 @@ pub fn with_cycles<C, I>(mut self, cycles: I) -> Self
         self
     }
 
+    /// Persist the timer to the given state file, and restore it
+    /// from this file when the server starts.
+    ///
+    /// See [`TimerConfig::state_file`].
+    pub fn with_state_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.timer_config.state_file = Some(path.into());
+        self
+    }
+
+    /// Record every cycle run by the timer to the given history
+    /// file.
+    ///
+    /// See [`TimerConfig::history_file`].
+    pub fn with_history_file(mut self, path: impl Into<PathBuf>) -> Self {
+        self.timer_config.history_file = Some(path.into());
+        self
+    }
+
     /// Set the timer cycles count.
     pub fn with_cycles_count(mut self, count: impl Into<TimerLoop>) -> Self {
         self.timer_config.cycles_count = count.into();
         self
     }
 
+    /// Push the given timer hook.
+    #[cfg(feature = "hooks")]
+    pub fn with_hook(mut self, hook: crate::hook::TimerHook) -> Self {
+        self.timer_config.hooks.push(hook);
+        self
+    }
+
+    /// Push the given timer hooks.
+    #[cfg(feature = "hooks")]
+    pub fn with_hooks(mut self, hooks: impl IntoIterator<Item = crate::hook::TimerHook>) -> Self {
+        self.timer_config.hooks.extend(hooks);
+        self
+    }
+
+    /// Add a named timer with its own configuration, next to the
+    /// default timer.
+    ///
+    /// Other `with_*` functions configure the default timer only.
+    pub fn with_timer(mut self, name: impl ToString, config: TimerConfig) -> Self {
+        self.named_timer_configs.insert(name.to_string(), config);
+        self
+    }
+
     /// Build the final server.
+    ///
+    /// The default timer is omitted if it has no cycle while named
+    /// timers are defined.
     pub fn build(self) -> Result<Server> {
+        let mut timers = Vec::new();
+
+        if !self.timer_config.cycles.is_empty() || self.named_timer_configs.is_empty() {
+            let timer = ThreadSafeTimer::new(self.timer_config)?;
+            timers.push((DEFAULT_TIMER.to_owned(), timer));
+        }
+
+        for (name, config) in self.named_timer_configs {
+            if name == DEFAULT_TIMER {
+                return Err(Error::new(
+                    ErrorKind::AlreadyExists,
+                    format!("timer {name} is reserved for the default timer"),
+                ));
+            }
+
+            timers.push((name, ThreadSafeTimer::new(config)?));
+        }
+
         Ok(Server {
             config: self.server_config,
             state: ThreadSafeState::new(),
-            timer: ThreadSafeTimer::new(self.timer_config)?,
+            timers: ThreadSafeTimers::new(timers),
         })
     }
 }
@@ -5,57 +5,142 @@
 
 use async_trait::async_trait;
 use log::debug;
-use std::io;
+#[cfg(feature = "tcp-tls")]
+use std::time::Duration;
+use std::{fmt, io};
 use tokio::{
     io::{AsyncBufReadExt, AsyncWriteExt},
     net::TcpListener,
+    task,
 };
 
 use crate::{
     request::{Request, RequestReader},
     response::{Response, ResponseWriter},
-    tcp::TcpHandler,
-    timer::ThreadSafeTimer,
+    tcp::{TcpConfig, TcpHandler, TcpTlsConfig},
+    timer::ThreadSafeTimers,
 };
 
-use super::{ServerBind, ServerStream};
+use super::{serve, ServerBind};
+
+/// The maximum duration of TLS handshakes, after which clients are
+/// disconnected.
+#[cfg(feature = "tcp-tls")]
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// The TCP server binder.
 ///
 /// This [`ServerBind`]er uses the TCP protocol to bind a listener, to
-/// read requests and write responses.
-#[derive(Debug, Clone, Eq, PartialEq)]
+/// read requests and write responses. Clients can be authenticated
+/// using a shared token, and connections can be encrypted using TLS
+/// (see [`TcpConfig`]).
+#[derive(Clone, Eq, PartialEq)]
 pub struct TcpBind {
     /// The TCP host of the listener.
     pub host: String,
 
     /// The TCP port of the listener.
     pub port: u16,
+
+    /// The shared token clients must send.
+    pub token: Option<String>,
+
+    /// The TLS configuration of the listener.
+    pub tls: Option<TcpTlsConfig>,
 }
 
 impl TcpBind {
     /// Create a new TCP binder using the given host and port.
     pub fn new(host: impl ToString, port: u16) -> Box<dyn ServerBind> {
+        Self::new_with_config(TcpConfig::new(host, port))
+    }
+
+    /// Create a new TCP binder using the given configuration.
+    pub fn new_with_config(config: TcpConfig) -> Box<dyn ServerBind> {
         Box::new(Self {
-            host: host.to_string(),
-            port,
+            host: config.host,
+            port: config.port,
+            token: config.token,
+            tls: config.tls,
         })
     }
 }
 
+/// The token is omitted, so that binders can be logged without
+/// leaking it.
+impl fmt::Debug for TcpBind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("TcpBind");
+        s.field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls);
+        s.finish_non_exhaustive()
+    }
+}
+
 #[async_trait]
 impl ServerBind for TcpBind {
-    async fn bind(&self, timer: ThreadSafeTimer) -> io::Result<()> {
+    async fn bind(&self, timers: ThreadSafeTimers) -> io::Result<()> {
+        #[cfg(not(feature = "tcp-tls"))]
+        if self.tls.is_some() {
+            return Err(crate::tcp::tls_unsupported());
+        }
+
+        #[cfg(feature = "tcp-tls")]
+        let acceptor = match &self.tls {
+            Some(tls) => Some(tls.acceptor()?),
+            None => None,
+        };
+
         let listener = TcpListener::bind((self.host.as_str(), self.port)).await?;
 
         loop {
             match listener.accept().await {
                 Ok((stream, _)) => {
-                    let mut handler = TcpHandler::from(stream);
-                    if let Err(err) = handler.handle(timer.clone()).await {
-                        debug!("cannot handle request");
-                        debug!("{err:?}");
-                    }
+                    let timers = timers.clone();
+                    let token = self.token.clone();
+                    #[cfg(feature = "tcp-tls")]
+                    let acceptor = acceptor.clone();
+
+                    task::spawn(async move {
+                        #[cfg(feature = "tcp-tls")]
+                        if let Some(acceptor) = acceptor {
+                            let handshake = acceptor.accept(stream);
+                            let res = match tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, handshake)
+                                .await
+                            {
+                                Ok(Ok(stream)) => {
+                                    let (reader, mut writer) = tokio::io::split(stream);
+                                    let mut reader = tokio::io::BufReader::new(reader);
+                                    serve(&mut reader, &mut writer, timers, token.as_deref()).await
+                                }
+                                Ok(Err(err)) => Err(err),
+                                Err(_) => Err(io::Error::new(
+                                    io::ErrorKind::TimedOut,
+                                    "TLS handshake timed out",
+                                )),
+                            };
+
+                            if let Err(err) = res {
+                                debug!("cannot handle TLS request");
+                                debug!("{err:?}");
+                            }
+
+                            return;
+                        }
+
+                        let TcpHandler {
+                            mut reader,
+                            mut writer,
+                        } = TcpHandler::from(stream);
+
+                        if let Err(err) =
+                            serve(&mut reader, &mut writer, timers, token.as_deref()).await
+                        {
+                            debug!("cannot handle request");
+                            debug!("{err:?}");
+                        }
+                    });
                 }
                 Err(err) => {
                     debug!("cannot get stream from client");
@@ -71,49 +156,14 @@ impl RequestReader for TcpHandler {
     async fn read(&mut self) -> io::Result<Request> {
         let mut req = String::new();
         self.reader.read_line(&mut req).await?;
-
-        let mut tokens = req.split_whitespace();
-        match tokens.next() {
-            Some("start") => Ok(Request::Start),
-            Some("get") => Ok(Request::Get),
-            Some("set") => match tokens.next().map(|duration| duration.parse::<usize>()) {
-                Some(Ok(duration)) => Ok(Request::Set(duration)),
-                Some(Err(err)) => Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    format!("invalid duration: {err}"),
-                )),
-                None => Err(io::Error::new(
-                    io::ErrorKind::InvalidInput,
-                    "missing duration".to_owned(),
-                )),
-            },
-            Some("pause") => Ok(Request::Pause),
-            Some("resume") => Ok(Request::Resume),
-            Some("stop") => Ok(Request::Stop),
-            Some(req) => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                format!("invalid request: {req}"),
-            )),
-            None => Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "missing request".to_owned(),
-            )),
-        }
+        req.parse()
     }
 }
 
 #[async_trait]
 impl ResponseWriter for TcpHandler {
     async fn write(&mut self, res: Response) -> io::Result<()> {
-        let res = match res {
-            Response::Ok => "ok\n".to_string(),
-            Response::Timer(timer) => {
-                format!("timer {}\n", serde_json::to_string(&timer).unwrap())
-            }
-        };
-
-        self.writer.write_all(res.as_bytes()).await?;
-
+        self.writer.write_all(format!("{res}\n").as_bytes()).await?;
         Ok(())
     }
 }
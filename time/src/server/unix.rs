@@ -0,0 +1,158 @@
+//! # Unix binder
+//!
+//! This module contains the implementation of the Unix domain socket
+//! server binder, based on [`tokio::net::UnixListener`].
+
+use async_trait::async_trait;
+use log::debug;
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+    process,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt},
+    net::{UnixListener, UnixStream},
+    task,
+};
+
+use crate::{
+    request::{Request, RequestReader},
+    response::{Response, ResponseWriter},
+    timer::ThreadSafeTimers,
+    unix::{UnixConfig, UnixHandler},
+};
+
+use super::{serve, ServerBind};
+
+/// The default permissions of the socket file.
+pub const DEFAULT_MODE: u32 = 0o600;
+
+/// The Unix domain socket server binder.
+///
+/// This [`ServerBind`]er uses a Unix domain socket to bind a
+/// listener, to read requests and write responses. Unlike TCP, the
+/// socket is only reachable from the local machine, and access can
+/// be restricted using file permissions.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UnixBind {
+    /// The path of the socket.
+    pub path: PathBuf,
+
+    /// The permissions of the socket file.
+    pub mode: u32,
+}
+
+impl UnixBind {
+    /// Create a new Unix binder using the given socket path.
+    ///
+    /// Only the owner can connect to the socket, see
+    /// [`DEFAULT_MODE`].
+    pub fn new(path: impl Into<PathBuf>) -> Box<dyn ServerBind> {
+        Self::new_with_config(UnixConfig::new(path))
+    }
+
+    /// Create a new Unix binder using the given socket path and
+    /// socket file permissions.
+    pub fn new_with_mode(path: impl Into<PathBuf>, mode: u32) -> Box<dyn ServerBind> {
+        Self::new_with_config(UnixConfig::new(path).with_mode(mode))
+    }
+
+    /// Create a new Unix binder using the given configuration.
+    ///
+    /// The socket file permissions default to [`DEFAULT_MODE`].
+    pub fn new_with_config(config: UnixConfig) -> Box<dyn ServerBind> {
+        Box::new(Self {
+            path: config.path,
+            mode: config.mode.unwrap_or(DEFAULT_MODE),
+        })
+    }
+
+    /// Bind the listener.
+    ///
+    /// The socket is first bound to a temporary path, then renamed
+    /// once its permissions are set, so that clients cannot connect
+    /// in between.
+    async fn listen(&self) -> io::Result<UnixListener> {
+        remove_stale_socket(&self.path).await?;
+
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let name = self.path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp_path = self
+            .path
+            .with_file_name(format!(".{name}.{}.tmp", process::id()));
+        let _ = fs::remove_file(&tmp_path);
+
+        let listener = UnixListener::bind(&tmp_path)?;
+        fs::set_permissions(&tmp_path, fs::Permissions::from_mode(self.mode))?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        debug!("listening on unix socket {}", self.path.display());
+        Ok(listener)
+    }
+}
+
+#[async_trait]
+impl ServerBind for UnixBind {
+    async fn bind(&self, timers: ThreadSafeTimers) -> io::Result<()> {
+        let listener = self.listen().await?;
+
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let mut handler = UnixHandler::from(stream);
+                    let timers = timers.clone();
+                    task::spawn(async move {
+                        let UnixHandler { reader, writer } = &mut handler;
+                        if let Err(err) = serve(reader, writer, timers, None).await {
+                            debug!("cannot handle request");
+                            debug!("{err:?}");
+                        }
+                    });
+                }
+                Err(err) => {
+                    debug!("cannot get stream from client");
+                    debug!("{err:?}");
+                }
+            }
+        }
+    }
+}
+
+/// Remove the socket file at the given path if no server listens to
+/// it anymore.
+async fn remove_stale_socket(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+
+    if UnixStream::connect(path).await.is_ok() {
+        let err = format!("unix socket {} already in use", path.display());
+        return Err(io::Error::new(ErrorKind::AddrInUse, err));
+    }
+
+    debug!("removing stale unix socket {}", path.display());
+    fs::remove_file(path)
+}
+
+#[async_trait]
+impl RequestReader for UnixHandler {
+    async fn read(&mut self) -> io::Result<Request> {
+        let mut req = String::new();
+        self.reader.read_line(&mut req).await?;
+        req.parse()
+    }
+}
+
+#[async_trait]
+impl ResponseWriter for UnixHandler {
+    async fn write(&mut self, res: Response) -> io::Result<()> {
+        self.writer.write_all(format!("{res}\n").as_bytes()).await?;
+        Ok(())
+    }
+}
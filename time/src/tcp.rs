@@ -2,12 +2,22 @@
 //!
 //! This module contains shared TCP code for both server and
 //! client.
+//!
+//! TCP connections can be authenticated using a shared token (see
+//! [`TcpConfig::token`]), and encrypted using TLS behind the
+//! `tcp-tls` cargo feature (see [`TcpConfig::tls`]).
 
 use serde::{Deserialize, Serialize};
+use std::{fmt, path::PathBuf};
 use tokio::{
     io::{self, BufReader, ReadHalf, WriteHalf},
     net::TcpStream,
 };
+#[cfg(all(
+    feature = "tcp-tls",
+    any(feature = "tcp-binder", feature = "tcp-client")
+))]
+use tokio_rustls::rustls;
 
 /// The TCP stream handler struct.
 ///
@@ -29,11 +39,180 @@ fn from(stream: TcpStream) -> Self {
 }
 
 /// The TCP shared configuration between clients and servers.
-#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[derive(Clone, Eq, PartialEq, Deserialize, Serialize)]
 pub struct TcpConfig {
     /// The TCP host name.
     pub host: String,
 
     /// The TCP port.
     pub port: u16,
+
+    /// The shared token authenticating clients.
+    ///
+    /// When defined, servers reject clients that do not send this
+    /// token, as well as legacy requests.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// The TLS configuration.
+    ///
+    /// When defined, connections are encrypted using TLS. Binding
+    /// and connecting fail if the `tcp-tls` cargo feature is
+    /// disabled.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TcpTlsConfig>,
+}
+
+/// The token is omitted, so that configurations can be logged
+/// without leaking it.
+impl fmt::Debug for TcpConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("TcpConfig");
+        s.field("host", &self.host)
+            .field("port", &self.port)
+            .field("tls", &self.tls);
+        s.finish_non_exhaustive()
+    }
+}
+
+impl TcpConfig {
+    /// Create a new TCP configuration using the given host and port,
+    /// without token nor TLS.
+    pub fn new(host: impl ToString, port: u16) -> Self {
+        Self {
+            host: host.to_string(),
+            port,
+            token: None,
+            tls: None,
+        }
+    }
+
+    /// Authenticate clients using the given shared token.
+    pub fn with_token(mut self, token: impl ToString) -> Self {
+        self.token = Some(token.to_string());
+        self
+    }
+
+    /// Encrypt connections using the given TLS configuration.
+    pub fn with_tls(mut self, tls: TcpTlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+/// The TCP TLS shared configuration between clients and servers.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct TcpTlsConfig {
+    /// The path of the PEM certificate chain.
+    ///
+    /// Servers present this chain to clients, and clients trust it
+    /// as root, which allows to use self-signed certificates.
+    pub cert: PathBuf,
+
+    /// The path of the PEM private key of the certificate, only
+    /// used by servers.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<PathBuf>,
+}
+
+impl TcpTlsConfig {
+    /// Create a new TLS configuration using the given PEM
+    /// certificate chain, without private key.
+    pub fn new(cert: impl Into<PathBuf>) -> Self {
+        Self {
+            cert: cert.into(),
+            key: None,
+        }
+    }
+
+    /// Use the given PEM private key, required by servers.
+    pub fn with_key(mut self, key: impl Into<PathBuf>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+}
+
+#[cfg(all(
+    feature = "tcp-tls",
+    any(feature = "tcp-binder", feature = "tcp-client")
+))]
+impl TcpTlsConfig {
+    /// Read the certificate chain.
+    fn certs(&self) -> io::Result<Vec<rustls::Certificate>> {
+        let file = std::fs::File::open(&self.cert)?;
+        let certs = rustls_pemfile::certs(&mut std::io::BufReader::new(file))?;
+
+        if certs.is_empty() {
+            let err = format!("cannot find certificate in {}", self.cert.display());
+            return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+        }
+
+        Ok(certs.into_iter().map(rustls::Certificate).collect())
+    }
+
+    /// Read the first private key.
+    #[cfg(feature = "tcp-binder")]
+    fn key(&self) -> io::Result<rustls::PrivateKey> {
+        use rustls_pemfile::Item;
+
+        let path = self.key.as_ref().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "cannot find TLS private key")
+        })?;
+
+        let file = std::fs::File::open(path)?;
+        let mut reader = std::io::BufReader::new(file);
+
+        while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+            match item {
+                Item::PKCS8Key(key) | Item::RSAKey(key) | Item::ECKey(key) => {
+                    return Ok(rustls::PrivateKey(key))
+                }
+                _ => continue,
+            }
+        }
+
+        let err = format!("cannot find private key in {}", path.display());
+        Err(io::Error::new(io::ErrorKind::InvalidData, err))
+    }
+
+    /// Build the TLS acceptor used by servers.
+    #[cfg(feature = "tcp-binder")]
+    pub(crate) fn acceptor(&self) -> io::Result<tokio_rustls::TlsAcceptor> {
+        let config = rustls::ServerConfig::builder()
+            .with_safe_defaults()
+            .with_no_client_auth()
+            .with_single_cert(self.certs()?, self.key()?)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+
+        Ok(std::sync::Arc::new(config).into())
+    }
+
+    /// Build the TLS connector used by clients.
+    #[cfg(feature = "tcp-client")]
+    pub(crate) fn connector(&self) -> io::Result<tokio_rustls::TlsConnector> {
+        let mut roots = rustls::RootCertStore::empty();
+
+        for cert in self.certs()? {
+            roots
+                .add(&cert)
+                .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        }
+
+        let config = rustls::ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(roots)
+            .with_no_client_auth();
+
+        Ok(std::sync::Arc::new(config).into())
+    }
+}
+
+/// Build the error returned when binding or connecting with a TLS
+/// configuration while the `tcp-tls` cargo feature is disabled.
+#[cfg(not(feature = "tcp-tls"))]
+pub(crate) fn tls_unsupported() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "TLS requires the tcp-tls cargo feature",
+    )
 }
@@ -4,25 +4,49 @@
 //! be identified by a state (running or stopped), a cycle and a
 //! cycles count (infinite or finite). During the lifetime of the
 //! timer, timer events are triggered.
+//!
+//! Cycles can follow loop rules, for example to run a long break
+//! every 4 loops only (see [`TimerCycleRule`]). The current cycle can
+//! be skipped, shortened or extended while the timer runs.
+//!
+//! The timer can be persisted to a state file on every transition,
+//! so that it can be restored when the server restarts (see
+//! [`TimerConfig::state_file`]), and every cycle can be recorded to
+//! a history file (see [`TimerConfig::history_file`]).
 
 use log::debug;
 #[cfg(all(feature = "server", test))]
 use mock_instant::Instant;
 use serde::{Deserialize, Serialize};
-#[cfg(feature = "server")]
-use std::io::{Error, ErrorKind};
 #[cfg(all(feature = "server", not(test)))]
 use std::time::Instant;
+#[cfg(feature = "server")]
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{Error, ErrorKind},
+    path::Path,
+    sync::Weak,
+    time::{SystemTime, UNIX_EPOCH},
+};
 use std::{
     fmt,
     io::Result,
     ops::{Deref, DerefMut},
+    path::PathBuf,
     sync::Arc,
 };
 #[cfg(feature = "server")]
-use tokio::sync::Mutex;
+use tokio::sync::{broadcast, Mutex};
 
 use crate::handler::{self, Handler};
+#[cfg(feature = "hooks")]
+use crate::hook::TimerHook;
+#[cfg(feature = "server")]
+use crate::{
+    event::Event,
+    history::{CycleRecord, History},
+};
 
 /// The timer loop.
 ///
@@ -52,10 +76,55 @@ fn from(count: usize) -> Self {
     }
 }
 
+/// The timer cycle rule.
+///
+/// Defines the loops a cycle runs in. Loops are counted from 1, so
+/// that a cycle with the rule `Every(4)` runs during the 4th, the 8th,
+/// the 12th loop etc.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub enum TimerCycleRule {
+    /// The cycle runs in every loop.
+    #[default]
+    Always,
+
+    /// The cycle runs every given number of loops only.
+    Every(usize),
+
+    /// The cycle runs in every loop, except every given number of
+    /// loops.
+    Except(usize),
+}
+
+impl TimerCycleRule {
+    pub fn is_always(&self) -> bool {
+        matches!(self, Self::Always)
+    }
+
+    /// Return `true` if the cycle runs in the loop matching the given
+    /// index, starting from 0.
+    pub fn matches(&self, loop_index: usize) -> bool {
+        let loop_number = loop_index + 1;
+        match self {
+            Self::Always => true,
+            Self::Every(n) => *n != 0 && loop_number % n == 0,
+            Self::Except(n) => *n == 0 || loop_number % n != 0,
+        }
+    }
+
+    /// Return the number of loops after which the rule repeats.
+    #[cfg(feature = "server")]
+    fn period(&self) -> usize {
+        match self {
+            Self::Always => 1,
+            Self::Every(n) | Self::Except(n) => *n,
+        }
+    }
+}
+
 /// The timer cycle.
 ///
-/// A cycle is a step in the timer lifetime, represented by a name and
-/// a duration.
+/// A cycle is a step in the timer lifetime, represented by a name, a
+/// duration and a rule.
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct TimerCycle {
     /// The name of the timer cycle.
@@ -69,6 +138,19 @@ pub struct TimerCycle {
     /// view*, the duration represents the amount of time remaining
     /// before the cycle ends.
     pub duration: usize,
+
+    /// The tags of the timer cycle.
+    ///
+    /// Tags are recorded in the history, which allows to compute
+    /// totals per tag (see [`History::totals_per_tag`]).
+    ///
+    /// [`History::totals_per_tag`]: crate::history::History::totals_per_tag
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+
+    /// The loops the timer cycle runs in.
+    #[serde(default, skip_serializing_if = "TimerCycleRule::is_always")]
+    pub rule: TimerCycleRule,
 }
 
 impl TimerCycle {
@@ -76,8 +158,20 @@ pub fn new(name: impl ToString, duration: usize) -> Self {
         Self {
             name: name.to_string(),
             duration,
+            tags: Vec::new(),
+            rule: TimerCycleRule::Always,
         }
     }
+
+    pub fn with_rule(mut self, rule: TimerCycleRule) -> Self {
+        self.rule = rule;
+        self
+    }
+
+    pub fn with_tags(mut self, tags: impl IntoIterator<Item = impl ToString>) -> Self {
+        self.tags = tags.into_iter().map(|tag| tag.to_string()).collect();
+        self
+    }
 }
 
 impl<T: ToString> From<(T, usize)> for TimerCycle {
@@ -110,6 +204,38 @@ fn deref_mut(&mut self) -> &mut Self::Target {
     }
 }
 
+/// The timer hooks list.
+///
+/// The list is always part of the [`TimerConfig`], so that timer
+/// configurations are built the same way whatever the enabled cargo
+/// features, but hooks can only be added and run with the `hooks`
+/// cargo feature.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct TimerHooks(#[cfg(feature = "hooks")] Vec<TimerHook>);
+
+#[cfg(feature = "hooks")]
+impl<T: IntoIterator<Item = TimerHook>> From<T> for TimerHooks {
+    fn from(hooks: T) -> Self {
+        Self(hooks.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "hooks")]
+impl Deref for TimerHooks {
+    type Target = Vec<TimerHook>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(feature = "hooks")]
+impl DerefMut for TimerHooks {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
 /// The timer state.
 ///
 /// Enumeration of all the possible state of a timer: running, paused
@@ -131,7 +257,7 @@ pub enum TimerState {
 ///
 /// Enumeration of all possible events that can be triggered during
 /// the timer lifecycle.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum TimerEvent {
     /// The timer started.
     Started,
@@ -169,6 +295,22 @@ pub struct TimerConfig {
 
     /// The timer event handler.
     pub handler: Arc<Handler<TimerEvent>>,
+
+    /// The path of the file the timer is persisted to.
+    ///
+    /// When defined, the timer is saved to this file on every
+    /// transition, and restored from it when created. The time
+    /// spent while the server was down is accounted for.
+    pub state_file: Option<PathBuf>,
+
+    /// The path of the history file.
+    ///
+    /// When defined, every cycle run by the timer is appended to
+    /// this file (see [`History`](crate::history::History)).
+    pub history_file: Option<PathBuf>,
+
+    /// The hooks executed when timer events are fired.
+    pub hooks: TimerHooks,
 }
 
 impl Default for TimerConfig {
@@ -177,19 +319,71 @@ fn default() -> Self {
             cycles: Default::default(),
             cycles_count: Default::default(),
             handler: handler::default(),
+            state_file: None,
+            history_file: None,
+            hooks: Default::default(),
         }
     }
 }
 
 #[cfg(feature = "server")]
 impl TimerConfig {
-    fn clone_first_cycle(&self) -> Result<TimerCycle> {
-        self.cycles.first().cloned().ok_or_else(|| {
-            Error::new(
-                ErrorKind::NotFound,
-                "cannot find first cycle from timer config",
-            )
-        })
+    /// Return the first cycle run by the timer, with its index and
+    /// the index of its loop.
+    fn first_cycle(&self) -> Result<(usize, usize, TimerCycle)> {
+        self.find_cycle(0, 0)
+            .map(|(index, loop_index)| (index, loop_index, self.cycles[index].clone()))
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    "cannot find first cycle from timer config",
+                )
+            })
+    }
+
+    /// Find the first cycle running from the given cycle index and
+    /// loop index, following cycle rules.
+    ///
+    /// Returns [`None`] if the timer reached the end of its loops, or
+    /// if no cycle can run.
+    fn find_cycle(&self, mut index: usize, mut loop_index: usize) -> Option<(usize, usize)> {
+        if self.cycles.is_empty() {
+            return None;
+        }
+
+        for _ in 0..=self.max_steps() {
+            if index >= self.cycles.len() {
+                index = 0;
+                loop_index += 1;
+            }
+
+            if let TimerLoop::Fixed(count) = self.cycles_count {
+                if loop_index >= count {
+                    return None;
+                }
+            }
+
+            if self.cycles[index].rule.matches(loop_index) {
+                return Some((index, loop_index));
+            }
+
+            index += 1;
+        }
+
+        None
+    }
+
+    /// Return the number of cycles after which every cycle that can
+    /// run has been found at least once.
+    fn max_steps(&self) -> usize {
+        let period = self
+            .cycles
+            .iter()
+            .map(|cycle| cycle.rule.period())
+            .max()
+            .unwrap_or_default();
+
+        self.cycles.len() * (period + 2)
     }
 }
 
@@ -215,6 +409,32 @@ pub struct Timer {
 
     #[cfg(feature = "server")]
     pub elapsed: usize,
+
+    /// The current cycle being recorded for the history.
+    #[cfg(feature = "server")]
+    #[serde(skip)]
+    pub record: Option<CycleRecord>,
+
+    /// The index of the current cycle in the configured cycles.
+    #[cfg(feature = "server")]
+    #[serde(skip)]
+    pub cycle_index: usize,
+
+    /// The index of the current loop, starting from 0.
+    #[cfg(feature = "server")]
+    #[serde(skip)]
+    pub loop_index: usize,
+
+    /// The elapsed time at which the current cycle began.
+    #[cfg(feature = "server")]
+    #[serde(skip)]
+    pub cycle_offset: usize,
+
+    /// The time added to, or subtracted from, the configured duration
+    /// of the current cycle.
+    #[cfg(feature = "server")]
+    #[serde(skip)]
+    pub cycle_shift: isize,
 }
 
 impl fmt::Debug for Timer {
@@ -240,8 +460,142 @@ fn eq(&self, other: &Self) -> bool {
     }
 }
 
+/// The persisted part of the timer.
+#[cfg(feature = "server")]
+#[derive(Debug, Serialize, Deserialize)]
+struct TimerSnapshot {
+    state: TimerState,
+    cycle: TimerCycle,
+    cycles_count: TimerLoop,
+    elapsed: usize,
+    #[serde(default)]
+    record: Option<CycleRecord>,
+    #[serde(default)]
+    cycle_index: usize,
+    #[serde(default)]
+    loop_index: usize,
+    #[serde(default)]
+    cycle_offset: usize,
+    #[serde(default)]
+    cycle_shift: isize,
+
+    /// When the snapshot was saved, in seconds since the Unix epoch.
+    saved_at: u64,
+}
+
+#[cfg(feature = "server")]
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
 #[cfg(feature = "server")]
 impl Timer {
+    /// Save the timer to the state file, if any.
+    ///
+    /// Errors are logged but not returned, so that the timer keeps
+    /// running even if the state file cannot be written.
+    fn persist(&self) {
+        let Some(path) = &self.config.state_file else {
+            return;
+        };
+
+        let snapshot = TimerSnapshot {
+            state: self.state.clone(),
+            cycle: self.cycle.clone(),
+            cycles_count: self.cycles_count.clone(),
+            elapsed: self.elapsed(),
+            record: self.record.clone(),
+            cycle_index: self.cycle_index,
+            loop_index: self.loop_index,
+            cycle_offset: self.cycle_offset,
+            cycle_shift: self.cycle_shift,
+            saved_at: now_secs(),
+        };
+
+        if let Err(err) = write_snapshot(path, &snapshot) {
+            debug!("cannot persist timer to {}, skipping it", path.display());
+            debug!("{err:?}");
+        }
+    }
+
+    /// Restore the timer from the state file, if any.
+    ///
+    /// If the timer was running, the time elapsed since it was saved
+    /// is added to its elapsed time. The cycle is updated on the next
+    /// tick.
+    fn restore(&mut self) {
+        let Some(path) = &self.config.state_file else {
+            return;
+        };
+
+        let snapshot = match read_snapshot(path) {
+            Ok(Some(snapshot)) => snapshot,
+            Ok(None) => return,
+            Err(err) => {
+                debug!("cannot restore timer from {}, skipping it", path.display());
+                debug!("{err:?}");
+                return;
+            }
+        };
+
+        debug!("restoring timer from {}: {snapshot:?}", path.display());
+
+        self.state = snapshot.state;
+        self.cycle = snapshot.cycle;
+        self.cycles_count = snapshot.cycles_count;
+        self.elapsed = snapshot.elapsed;
+        self.record = snapshot.record;
+        self.cycle_index = snapshot.cycle_index;
+        self.loop_index = snapshot.loop_index;
+        self.cycle_offset = snapshot.cycle_offset;
+        self.cycle_shift = snapshot.cycle_shift;
+        self.started_at = None;
+
+        if let TimerState::Running = self.state {
+            let downtime = now_secs().saturating_sub(snapshot.saved_at);
+            self.elapsed += downtime as usize;
+            self.started_at = Some(Instant::now());
+        }
+    }
+
+    /// Append the cycle being recorded to the history file, if any.
+    ///
+    /// Like for persistence, errors are logged but not returned.
+    fn record_cycle(&mut self) {
+        let Some(record) = self.record.take() else {
+            return;
+        };
+
+        let Some(path) = &self.config.history_file else {
+            return;
+        };
+
+        let planned = self.planned_duration();
+
+        let entry = record.finish(
+            &self.cycle.name,
+            self.cycle.tags.clone(),
+            planned,
+            self.elapsed(),
+            now_secs(),
+        );
+
+        if entry.duration == 0 {
+            return;
+        }
+
+        if let Err(err) = History::new(path).append(&entry) {
+            debug!(
+                "cannot append cycle to history {}, skipping it",
+                path.display()
+            );
+            debug!("{err:?}");
+        }
+    }
+
     pub fn elapsed(&self) -> usize {
         self.started_at
             .map(|i| i.elapsed().as_secs() as usize)
@@ -249,56 +603,121 @@ pub fn elapsed(&self) -> usize {
             + self.elapsed
     }
 
-    pub async fn update(&mut self) {
-        let mut elapsed = self.elapsed();
+    /// Return the configured duration of the current cycle.
+    fn planned_duration(&self) -> usize {
+        self.config
+            .cycles
+            .get(self.cycle_index)
+            .map(|cycle| cycle.duration)
+            .unwrap_or_default()
+    }
 
-        match self.state {
-            TimerState::Running => {
-                let (cycles, total_duration) = self.config.cycles.iter().cloned().fold(
-                    (Vec::new(), 0),
-                    |(mut cycles, mut sum), mut cycle| {
-                        cycle.duration += sum;
-                        sum = cycle.duration;
-                        cycles.push(cycle);
-                        (cycles, sum)
-                    },
-                );
-
-                if let TimerLoop::Fixed(cycles_count) = self.cycles_count {
-                    if elapsed >= (total_duration * cycles_count) {
-                        self.state = TimerState::Stopped;
-                        return;
-                    }
+    /// Return the elapsed time at which the current cycle ends.
+    fn cycle_end(&self) -> usize {
+        (self.cycle_offset + self.planned_duration()).saturating_add_signed(self.cycle_shift)
+    }
+
+    /// Shift the current cycle so that it ends at the given elapsed
+    /// time.
+    fn set_cycle_end(&mut self, end: usize) {
+        self.cycle_shift = end as isize - (self.cycle_offset + self.planned_duration()) as isize;
+        self.cycle.duration = end.saturating_sub(self.elapsed());
+    }
+
+    /// Find the cycle the timer should run at the given elapsed
+    /// time, as a cycle index, a loop index and a cycle offset.
+    ///
+    /// Returns [`None`] if the timer reached the end of its loops.
+    fn position_at(&self, elapsed: usize) -> Option<(usize, usize, usize)> {
+        let mut position = (self.cycle_index, self.loop_index, self.cycle_offset);
+        let mut end = self.cycle_end();
+        let mut steps_without_progress = 0;
+
+        while elapsed >= end {
+            let (index, loop_index) = self.config.find_cycle(position.0 + 1, position.1)?;
+            let duration = self.config.cycles[index].duration;
+
+            // prevent infinite loops when no cycle has a duration
+            if duration == 0 {
+                steps_without_progress += 1;
+                if steps_without_progress > self.config.max_steps() {
+                    return None;
                 }
+            } else {
+                steps_without_progress = 0;
+            }
+
+            position = (index, loop_index, end);
+            end += duration;
+        }
 
-                elapsed %= total_duration;
+        Some(position)
+    }
 
-                let last_cycle = cycles[cycles.len() - 1].clone();
-                let next_cycle = cycles
-                    .into_iter()
-                    .fold(None, |next_cycle, mut cycle| match next_cycle {
-                        None if elapsed < cycle.duration => {
-                            cycle.duration -= elapsed;
-                            Some(cycle)
-                        }
-                        _ => next_cycle,
-                    })
-                    .unwrap_or(last_cycle);
+    /// Move the timer to the given position, as returned by
+    /// [`Timer::position_at`].
+    ///
+    /// If the position differs from the current one, the current
+    /// cycle ends and the new one begins.
+    async fn move_to(
+        &mut self,
+        (index, loop_index, offset): (usize, usize, usize),
+        elapsed: usize,
+    ) {
+        let cycle_changed = (index, loop_index) != (self.cycle_index, self.loop_index);
+
+        if !cycle_changed {
+            self.cycle.duration = self.cycle_end().saturating_sub(elapsed);
+            return;
+        }
 
-                self.fire_event(TimerEvent::Running(self.cycle.clone()))
-                    .await;
+        self.record_cycle();
+        let mut record = CycleRecord::new(now_secs(), elapsed);
+        if let TimerState::Paused = self.state {
+            record.pause(now_secs());
+        }
+        self.record = Some(record);
+
+        self.cycle_index = index;
+        self.loop_index = loop_index;
+        self.cycle_offset = offset;
+        self.cycle_shift = 0;
+
+        let mut next_cycle = self.config.cycles[index].clone();
+        next_cycle.duration = self.cycle_end().saturating_sub(elapsed);
+
+        let mut prev_cycle = self.cycle.clone();
+        prev_cycle.duration = 0;
+        self.fire_events([
+            TimerEvent::Ended(prev_cycle),
+            TimerEvent::Began(next_cycle.clone()),
+        ])
+        .await;
+
+        self.cycle = next_cycle;
+        self.persist();
+    }
 
-                if self.cycle.name != next_cycle.name {
-                    let mut prev_cycle = self.cycle.clone();
-                    prev_cycle.duration = 0;
-                    self.fire_events([
-                        TimerEvent::Ended(prev_cycle),
-                        TimerEvent::Began(next_cycle.clone()),
-                    ])
+    /// Stop the timer once it reached the end of its loops.
+    fn end_loops(&mut self) {
+        self.record_cycle();
+        self.state = TimerState::Stopped;
+        self.persist();
+    }
+
+    pub async fn update(&mut self) {
+        match self.state {
+            TimerState::Running => {
+                let elapsed = self.elapsed();
+
+                let Some(position) = self.position_at(elapsed) else {
+                    return self.end_loops();
+                };
+
+                self.fire_event(TimerEvent::Running(self.cycle.clone()))
                     .await;
-                }
 
-                self.cycle = next_cycle;
+                self.move_to(position, elapsed).await;
             }
             TimerState::Paused => {
                 // nothing to do
@@ -316,6 +735,36 @@ pub async fn fire_event(&self, event: TimerEvent) {
             debug!("cannot fire timer event, skipping it");
             debug!("{err:?}");
         }
+
+        #[cfg(feature = "hooks")]
+        self.run_hooks(&event);
+    }
+
+    /// Run the hooks triggered by the given event.
+    ///
+    /// Hooks run in dedicated tasks, so that slow commands do not
+    /// block the timer.
+    #[cfg(feature = "hooks")]
+    fn run_hooks(&self, event: &TimerEvent) {
+        for hook in self.config.hooks.iter() {
+            let cmd = match hook.command(event) {
+                Ok(Some(cmd)) => cmd,
+                Ok(None) => continue,
+                Err(err) => {
+                    debug!("cannot render timer hook, skipping it");
+                    debug!("{err:?}");
+                    continue;
+                }
+            };
+
+            tokio::spawn(async move {
+                debug!("running timer hook {}", cmd.to_string());
+                if let Err(err) = cmd.run().await {
+                    debug!("cannot run timer hook, skipping it");
+                    debug!("{err:?}");
+                }
+            });
+        }
     }
 
     pub async fn fire_events(&self, events: impl IntoIterator<Item = TimerEvent>) {
@@ -326,11 +775,18 @@ pub async fn fire_events(&self, events: impl IntoIterator<Item = TimerEvent>) {
 
     pub async fn start(&mut self) -> Result<()> {
         if matches!(self.state, TimerState::Stopped) {
+            let (index, loop_index, cycle) = self.config.first_cycle()?;
             self.state = TimerState::Running;
-            self.cycle = self.config.clone_first_cycle()?;
+            self.cycle = cycle;
+            self.cycle_index = index;
+            self.loop_index = loop_index;
+            self.cycle_offset = 0;
+            self.cycle_shift = 0;
             self.cycles_count = self.config.cycles_count.clone();
             self.started_at = Some(Instant::now());
             self.elapsed = 0;
+            self.record = Some(CycleRecord::new(now_secs(), 0));
+            self.persist();
             self.fire_events([TimerEvent::Started, TimerEvent::Began(self.cycle.clone())])
                 .await;
         }
@@ -338,16 +794,61 @@ pub async fn start(&mut self) -> Result<()> {
     }
 
     pub async fn set(&mut self, duration: usize) -> Result<()> {
-        self.cycle.duration = duration;
+        self.set_cycle_end(self.elapsed() + duration);
+        self.persist();
+        self.fire_event(TimerEvent::Set(self.cycle.clone())).await;
+        Ok(())
+    }
+
+    /// Add the given duration to the current cycle.
+    pub async fn add(&mut self, duration: usize) -> Result<()> {
+        self.set_cycle_end(self.cycle_end() + duration);
+        self.persist();
+        self.fire_event(TimerEvent::Set(self.cycle.clone())).await;
+        Ok(())
+    }
+
+    /// Subtract the given duration from the current cycle.
+    ///
+    /// The cycle cannot end before now: if the given duration exceeds
+    /// the remaining duration, the cycle ends on the next tick.
+    pub async fn subtract(&mut self, duration: usize) -> Result<()> {
+        let end = self.cycle_end().saturating_sub(duration);
+        self.set_cycle_end(end.max(self.elapsed()));
+        self.persist();
         self.fire_event(TimerEvent::Set(self.cycle.clone())).await;
         Ok(())
     }
 
+    /// Skip the current cycle.
+    ///
+    /// The current cycle ends now and the next one begins, following
+    /// cycle rules. Has no effect if the timer is stopped.
+    pub async fn next(&mut self) -> Result<()> {
+        if matches!(self.state, TimerState::Stopped) {
+            return Ok(());
+        }
+
+        let elapsed = self.elapsed();
+        self.set_cycle_end(elapsed);
+
+        match self.position_at(elapsed) {
+            Some(position) => self.move_to(position, elapsed).await,
+            None => self.end_loops(),
+        }
+
+        Ok(())
+    }
+
     pub async fn pause(&mut self) -> Result<()> {
         if matches!(self.state, TimerState::Running) {
             self.state = TimerState::Paused;
             self.elapsed = self.elapsed();
             self.started_at = None;
+            if let Some(record) = &mut self.record {
+                record.pause(now_secs());
+            }
+            self.persist();
             self.fire_event(TimerEvent::Paused(self.cycle.clone()))
                 .await;
         }
@@ -358,6 +859,10 @@ pub async fn resume(&mut self) -> Result<()> {
         if matches!(self.state, TimerState::Paused) {
             self.state = TimerState::Running;
             self.started_at = Some(Instant::now());
+            if let Some(record) = &mut self.record {
+                record.resume(now_secs());
+            }
+            self.persist();
             self.fire_event(TimerEvent::Resumed(self.cycle.clone()))
                 .await;
         }
@@ -366,37 +871,125 @@ pub async fn resume(&mut self) -> Result<()> {
 
     pub async fn stop(&mut self) -> Result<()> {
         if matches!(self.state, TimerState::Running) {
+            self.record_cycle();
             self.state = TimerState::Stopped;
             self.fire_events([TimerEvent::Ended(self.cycle.clone()), TimerEvent::Stopped])
                 .await;
-            self.cycle = self.config.clone_first_cycle()?;
+            let (index, loop_index, cycle) = self.config.first_cycle()?;
+            self.cycle = cycle;
+            self.cycle_index = index;
+            self.loop_index = loop_index;
+            self.cycle_offset = 0;
+            self.cycle_shift = 0;
             self.cycles_count = self.config.cycles_count.clone();
             self.started_at = None;
             self.elapsed = 0;
+            self.persist();
         }
         Ok(())
     }
 }
 
+/// Read the snapshot from the given state file, or [`None`] if the
+/// file does not exist.
+#[cfg(feature = "server")]
+fn read_snapshot(path: &Path) -> Result<Option<TimerSnapshot>> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(serde_json::from_slice(&contents)?)),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Write the snapshot to the given state file.
+///
+/// The snapshot is written to a temporary file first, then renamed,
+/// so that a crash cannot leave a truncated state file.
+#[cfg(feature = "server")]
+fn write_snapshot(path: &Path, snapshot: &TimerSnapshot) -> Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let mut tmp_path = path.as_os_str().to_owned();
+    tmp_path.push(".tmp");
+
+    fs::write(&tmp_path, serde_json::to_vec(snapshot)?)?;
+    fs::rename(&tmp_path, path)
+}
+
+/// The number of events kept for subscribers that are late to
+/// receive them.
+#[cfg(feature = "server")]
+const EVENTS_CAPACITY: usize = 64;
+
 /// Thread safe version of the [`Timer`].
 ///
 /// The server does not manipulate directly the [`Timer`], it uses
 /// this thread safe version instead (mainly because the timer runs in
 /// a [`std::thread::spawn`] loop).
+///
+/// It also broadcasts timer events, plus the events sent using
+/// [`ThreadSafeTimer::notify`], to subscribers. Subscriptions are
+/// closed once all the clones of the thread safe timer are dropped.
+#[cfg(feature = "server")]
+#[derive(Clone, Debug)]
+pub struct ThreadSafeTimer(Arc<Mutex<Timer>>, Arc<broadcast::Sender<Event>>);
+
 #[cfg(feature = "server")]
-#[derive(Clone, Debug, Default)]
-pub struct ThreadSafeTimer(Arc<Mutex<Timer>>);
+impl Default for ThreadSafeTimer {
+    fn default() -> Self {
+        Self::from_timer(Timer::default())
+    }
+}
 
 #[cfg(feature = "server")]
 impl ThreadSafeTimer {
     pub fn new(config: TimerConfig) -> Result<Self> {
-        let mut timer = Timer::default();
+        let (cycle_index, loop_index, cycle) = config.first_cycle()?;
+
+        let mut timer = Timer {
+            cycle,
+            cycle_index,
+            loop_index,
+            cycles_count: config.cycles_count.clone(),
+            config,
+            ..Default::default()
+        };
+        timer.restore();
 
-        timer.config = config;
-        timer.cycle = timer.config.clone_first_cycle()?;
-        timer.cycles_count = timer.config.cycles_count.clone();
+        Ok(Self::from_timer(timer))
+    }
 
-        Ok(Self(Arc::new(Mutex::new(timer))))
+    /// Wrap the given timer, broadcasting its events to subscribers.
+    fn from_timer(mut timer: Timer) -> Self {
+        let (events, _) = broadcast::channel(EVENTS_CAPACITY);
+        let events = Arc::new(events);
+
+        // the handler belongs to the timer, which is owned by the
+        // thread safe timer: holding a strong reference to the sender
+        // would keep the channel open forever
+        let handler = timer.config.handler.clone();
+        let sender = Arc::downgrade(&events);
+        timer.config.handler = Arc::new(move |event: TimerEvent| {
+            if let Some(sender) = Weak::upgrade(&sender) {
+                // no subscriber is not an error
+                let _ = sender.send(Event::Timer(event.clone()));
+            }
+            handler(event)
+        });
+
+        Self(Arc::new(Mutex::new(timer)), events)
+    }
+
+    /// Subscribe to events.
+    pub fn subscribe(&self) -> broadcast::Receiver<Event> {
+        self.1.subscribe()
+    }
+
+    /// Broadcast the given event to subscribers.
+    pub fn notify(&self, event: Event) {
+        let _ = self.1.send(event);
     }
 
     pub async fn update(&self) {
@@ -415,6 +1008,18 @@ pub async fn set(&self, duration: usize) -> Result<()> {
         self.0.lock().await.set(duration).await
     }
 
+    pub async fn add(&self, duration: usize) -> Result<()> {
+        self.0.lock().await.add(duration).await
+    }
+
+    pub async fn subtract(&self, duration: usize) -> Result<()> {
+        self.0.lock().await.subtract(duration).await
+    }
+
+    pub async fn next(&self) -> Result<()> {
+        self.0.lock().await.next().await
+    }
+
     pub async fn pause(&self) -> Result<()> {
         self.0.lock().await.pause().await
     }
@@ -426,6 +1031,84 @@ pub async fn resume(&self) -> Result<()> {
     pub async fn stop(&self) -> Result<()> {
         self.0.lock().await.stop().await
     }
+
+    /// Get the history store of the timer.
+    ///
+    /// Returns an error if the timer has no history file.
+    pub async fn history(&self) -> Result<History> {
+        match &self.0.lock().await.config.history_file {
+            Some(path) => Ok(History::new(path)),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                "cannot find history file from timer config",
+            )),
+        }
+    }
+}
+
+/// The name of the default timer.
+///
+/// Requests that do not address a timer by name are sent to this
+/// timer.
+pub const DEFAULT_TIMER: &str = "default";
+
+/// Thread safe registry of named timers.
+///
+/// A server runs one timer per name, each one with its own
+/// [`TimerConfig`].
+#[cfg(feature = "server")]
+#[derive(Clone, Debug)]
+pub struct ThreadSafeTimers(Arc<BTreeMap<String, ThreadSafeTimer>>);
+
+#[cfg(feature = "server")]
+impl Default for ThreadSafeTimers {
+    fn default() -> Self {
+        Self::new([(DEFAULT_TIMER.to_owned(), ThreadSafeTimer::default())])
+    }
+}
+
+#[cfg(feature = "server")]
+impl ThreadSafeTimers {
+    pub fn new(timers: impl IntoIterator<Item = (String, ThreadSafeTimer)>) -> Self {
+        Self(Arc::new(timers.into_iter().collect()))
+    }
+
+    /// Get the timer matching the given name, or the default timer
+    /// if no name is given.
+    pub fn timer(&self, name: Option<&str>) -> Result<ThreadSafeTimer> {
+        let name = name.unwrap_or(DEFAULT_TIMER);
+        self.0
+            .get(name)
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, format!("cannot find timer {name}")))
+    }
+
+    /// Iterate over the names and the timers of the registry.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &ThreadSafeTimer)> {
+        self.0.iter()
+    }
+
+    /// Get a copy of all the timers, by name.
+    pub async fn get_all(&self) -> BTreeMap<String, Timer> {
+        let mut timers = BTreeMap::new();
+        for (name, timer) in self.iter() {
+            timers.insert(name.clone(), timer.get().await);
+        }
+        timers
+    }
+
+    /// Broadcast the given event to the subscribers of all timers.
+    pub fn notify(&self, event: Event) {
+        for (_, timer) in self.iter() {
+            timer.notify(event.clone());
+        }
+    }
+
+    pub async fn update(&self) {
+        for (_, timer) in self.iter() {
+            timer.update().await;
+        }
+    }
 }
 
 #[cfg(feature = "server")]
@@ -449,6 +1132,7 @@ mod tests {
     use mock_instant::{Instant, MockClock};
     use once_cell::sync::Lazy;
     use std::{sync::Arc, time::Duration};
+    use tempfile::tempdir;
 
     use super::*;
 
@@ -658,4 +1342,238 @@ async fn thread_safe_timer() {
             ]
         );
     }
+
+    #[tokio::test]
+    async fn persisted_paused_timer() {
+        let dir = tempdir().unwrap();
+        let state_file = dir.path().join("state.json");
+
+        let mut timer = testing_timer();
+        timer.config.state_file = Some(state_file.clone());
+        timer.elapsed = 2;
+        timer.started_at = None;
+        timer.cycle = TimerCycle::new("a", 1);
+        timer.pause().await.unwrap();
+
+        let timer = ThreadSafeTimer::new(timer.config.clone()).unwrap();
+        let timer = timer.lock().await;
+
+        assert_eq!(timer.state, TimerState::Paused);
+        assert_eq!(timer.cycle, TimerCycle::new("a", 1));
+        assert_eq!(timer.elapsed(), 2);
+    }
+
+    #[tokio::test]
+    async fn persisted_running_timer_accounts_for_downtime() {
+        let dir = tempdir().unwrap();
+        let state_file = dir.path().join("state.json");
+
+        let snapshot = TimerSnapshot {
+            state: TimerState::Running,
+            cycle: TimerCycle::new("a", 2),
+            cycles_count: TimerLoop::Infinite,
+            elapsed: 1,
+            record: None,
+            cycle_index: 0,
+            loop_index: 0,
+            cycle_offset: 0,
+            cycle_shift: 0,
+            saved_at: now_secs() - 2,
+        };
+        write_snapshot(&state_file, &snapshot).unwrap();
+
+        let config = TimerConfig {
+            state_file: Some(state_file.clone()),
+            ..testing_timer().config
+        };
+
+        let timer = ThreadSafeTimer::new(config).unwrap();
+        let mut timer = timer.lock().await;
+
+        assert_eq!(timer.state, TimerState::Running);
+        assert!(timer.elapsed() >= 3);
+
+        // the restored timer should switch to the cycle matching the
+        // time spent down
+        timer.update().await;
+        assert_eq!(timer.cycle.name, "b");
+    }
+
+    #[tokio::test]
+    async fn missing_state_file() {
+        let dir = tempdir().unwrap();
+        let config = TimerConfig {
+            state_file: Some(dir.path().join("state.json")),
+            ..testing_timer().config
+        };
+
+        let timer = ThreadSafeTimer::new(config).unwrap();
+        let timer = timer.lock().await;
+
+        assert_eq!(timer.state, TimerState::Stopped);
+        assert_eq!(timer.cycle, TimerCycle::new("a", 3));
+    }
+
+    #[tokio::test]
+    async fn recorded_cycles() {
+        let dir = tempdir().unwrap();
+        let history_file = dir.path().join("history.jsonl");
+
+        let mut timer = testing_timer();
+        timer.config.cycles[1].tags = vec![String::from("break")];
+        timer.config.history_file = Some(history_file.clone());
+        timer.state = TimerState::Stopped;
+        timer.start().await.unwrap();
+
+        // from a3 to b2
+        MockClock::advance(Duration::from_secs(3));
+        timer.update().await;
+        assert_eq!(timer.cycle.name, "b");
+
+        // stop in the middle of b
+        MockClock::advance(Duration::from_secs(1));
+        timer.pause().await.unwrap();
+        timer.resume().await.unwrap();
+        timer.stop().await.unwrap();
+
+        let entries = History::new(&history_file).entries().unwrap();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].name, "a");
+        assert_eq!(entries[0].planned, 3);
+        assert_eq!(entries[0].duration, 3);
+        assert_eq!(entries[0].pauses, 0);
+
+        assert_eq!(entries[1].name, "b");
+        assert_eq!(entries[1].tags, vec![String::from("break")]);
+        assert_eq!(entries[1].planned, 2);
+        assert_eq!(entries[1].duration, 1);
+        assert_eq!(entries[1].pauses, 1);
+    }
+
+    #[tokio::test]
+    async fn cycle_rules() {
+        let mut timer = Timer {
+            config: TimerConfig {
+                cycles: TimerCycles::from([
+                    TimerCycle::new("work", 2),
+                    TimerCycle::new("short", 1).with_rule(TimerCycleRule::Except(2)),
+                    TimerCycle::new("long", 3).with_rule(TimerCycleRule::Every(2)),
+                ]),
+                cycles_count: TimerLoop::Fixed(2),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        timer.start().await.unwrap();
+        assert_eq!(timer.cycle, TimerCycle::new("work", 2));
+
+        // first loop: the long break is skipped
+        MockClock::advance(Duration::from_secs(2));
+        timer.update().await;
+        assert_eq!(timer.cycle.name, "short");
+
+        MockClock::advance(Duration::from_secs(1));
+        timer.update().await;
+        assert_eq!(timer.cycle.name, "work");
+        assert_eq!(timer.loop_index, 1);
+
+        // second loop: the short break is skipped
+        MockClock::advance(Duration::from_secs(2));
+        timer.update().await;
+        assert_eq!(
+            timer.cycle,
+            TimerCycle::new("long", 3).with_rule(TimerCycleRule::Every(2))
+        );
+
+        // end of the second loop: the timer stops by itself
+        MockClock::advance(Duration::from_secs(3));
+        timer.update().await;
+        assert_eq!(timer.state, TimerState::Stopped);
+    }
+
+    #[tokio::test]
+    async fn cycle_rules_after_multiple_cycles() {
+        let mut timer = testing_timer();
+        timer.config.cycles[1].rule = TimerCycleRule::Every(2);
+
+        // a3, c1 then a3 again, in a single tick
+        MockClock::advance(Duration::from_secs(5));
+        timer.update().await;
+
+        assert_eq!(timer.cycle, TimerCycle::new("a", 2));
+        assert_eq!(timer.loop_index, 1);
+    }
+
+    #[tokio::test]
+    async fn next_cycle() {
+        static EVENTS: Lazy<Mutex<Vec<TimerEvent>>> = Lazy::new(|| Mutex::const_new(Vec::new()));
+
+        let mut timer = testing_timer();
+
+        timer.config.handler = Arc::new(|evt| {
+            Box::pin(async {
+                EVENTS.lock().await.push(evt);
+                Ok(())
+            })
+        });
+
+        MockClock::advance(Duration::from_secs(1));
+        timer.next().await.unwrap();
+        assert_eq!(timer.cycle, TimerCycle::new("b", 2));
+
+        // paused timers can skip cycles too
+        timer.pause().await.unwrap();
+        timer.next().await.unwrap();
+        assert_eq!(timer.state, TimerState::Paused);
+        assert_eq!(timer.cycle, TimerCycle::new("c", 1));
+
+        // the next cycle starts from where the previous one was skipped
+        timer.resume().await.unwrap();
+        MockClock::advance(Duration::from_secs(1));
+        timer.update().await;
+        assert_eq!(timer.cycle, TimerCycle::new("a", 3));
+
+        assert_eq!(
+            *EVENTS.lock().await,
+            vec![
+                TimerEvent::Ended(TimerCycle::new("a", 0)),
+                TimerEvent::Began(TimerCycle::new("b", 2)),
+                TimerEvent::Paused(TimerCycle::new("b", 2)),
+                TimerEvent::Ended(TimerCycle::new("b", 0)),
+                TimerEvent::Began(TimerCycle::new("c", 1)),
+                TimerEvent::Resumed(TimerCycle::new("c", 1)),
+                TimerEvent::Running(TimerCycle::new("c", 1)),
+                TimerEvent::Ended(TimerCycle::new("c", 0)),
+                TimerEvent::Began(TimerCycle::new("a", 3)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn add_and_subtract_time() {
+        let mut timer = testing_timer();
+
+        timer.add(2).await.unwrap();
+        assert_eq!(timer.cycle, TimerCycle::new("a", 5));
+
+        // the added time survives ticks
+        MockClock::advance(Duration::from_secs(4));
+        timer.update().await;
+        assert_eq!(timer.cycle, TimerCycle::new("a", 1));
+
+        // the cycle cannot end before now
+        timer.subtract(5).await.unwrap();
+        assert_eq!(timer.cycle, TimerCycle::new("a", 0));
+
+        // the next cycle is not impacted
+        timer.update().await;
+        assert_eq!(timer.cycle, TimerCycle::new("b", 2));
+
+        timer.set(10).await.unwrap();
+        MockClock::advance(Duration::from_secs(1));
+        timer.update().await;
+        assert_eq!(timer.cycle, TimerCycle::new("b", 9));
+    }
 }
@@ -0,0 +1,60 @@
+//! # Unix
+//!
+//! This module contains shared Unix domain socket code for both
+//! server and client.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use tokio::{
+    io::{self, BufReader, ReadHalf, WriteHalf},
+    net::UnixStream,
+};
+
+/// The Unix socket stream handler struct.
+///
+/// Wrapper around a Unix socket stream reader and writer.
+pub struct UnixHandler {
+    /// The Unix socket stream reader.
+    pub reader: BufReader<ReadHalf<UnixStream>>,
+
+    /// The Unix socket stream writer.
+    pub writer: WriteHalf<UnixStream>,
+}
+
+impl From<UnixStream> for UnixHandler {
+    fn from(stream: UnixStream) -> Self {
+        let (reader, writer) = io::split(stream);
+        let reader = BufReader::new(reader);
+        Self { reader, writer }
+    }
+}
+
+/// The Unix socket shared configuration between clients and servers.
+#[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+pub struct UnixConfig {
+    /// The path of the Unix socket.
+    pub path: PathBuf,
+
+    /// The permissions of the Unix socket file, only used by
+    /// servers. Defaults to `0o600`, so that only the owner can
+    /// connect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<u32>,
+}
+
+impl UnixConfig {
+    /// Create a new Unix configuration using the given socket path,
+    /// with the default permissions.
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            mode: None,
+        }
+    }
+
+    /// Set the permissions of the socket file.
+    pub fn with_mode(mut self, mode: u32) -> Self {
+        self.mode = Some(mode);
+        self
+    }
+}
@@ -0,0 +1,103 @@
+use std::{env, fs, time::Duration};
+use time::{
+    client::tcp::TcpClient,
+    history::{History, HistoryEntry, Totals},
+    response::Response,
+    server::{tcp::TcpBind, ServerBuilder},
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3003;
+
+fn entry(name: &str, tags: &[&str], duration: usize, started_at: u64) -> HistoryEntry {
+    HistoryEntry {
+        name: name.to_owned(),
+        tags: tags.iter().map(ToString::to_string).collect(),
+        planned: duration,
+        duration,
+        started_at,
+        ended_at: started_at + duration as u64,
+        ..Default::default()
+    }
+}
+
+fn totals(cycles: usize, duration: usize) -> Totals {
+    Totals {
+        cycles,
+        duration,
+        paused: 0,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn history_totals() {
+    env_logger::builder().is_test(true).init();
+
+    let history_file = env::temp_dir().join(format!("time-lib-history-{}", std::process::id()));
+    let _ = fs::remove_file(&history_file);
+
+    let history = History::new(&history_file);
+    // 2024-02-03T23:59:00Z
+    history
+        .append(&entry("Work", &["deep work"], 60, 1_707_004_740))
+        .unwrap();
+    // 2024-02-04T00:01:00Z
+    history
+        .append(&entry("Break", &[], 10, 1_707_004_860))
+        .unwrap();
+    history
+        .append(&entry("Work", &["deep work", "email"], 30, 1_707_004_870))
+        .unwrap();
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, PORT))
+        .with_cycle(("Work", 10))
+        .with_history_file(&history_file)
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let client = TcpClient::new_boxed(HOST, PORT);
+
+            let per_day = client.totals_per_day().await.unwrap();
+            assert_eq!(
+                per_day.into_iter().collect::<Vec<_>>(),
+                vec![
+                    (String::from("2024-02-03"), totals(1, 60)),
+                    (String::from("2024-02-04"), totals(2, 40)),
+                ]
+            );
+
+            let per_cycle = client.totals_per_cycle().await.unwrap();
+            assert_eq!(
+                per_cycle.into_iter().collect::<Vec<_>>(),
+                vec![
+                    (String::from("Break"), totals(1, 10)),
+                    (String::from("Work"), totals(2, 90)),
+                ]
+            );
+
+            let per_tag = client.totals_per_tag().await.unwrap();
+            assert_eq!(
+                per_tag.clone().into_iter().collect::<Vec<_>>(),
+                vec![
+                    (String::from("deep work"), totals(2, 90)),
+                    (String::from("email"), totals(1, 30)),
+                ]
+            );
+
+            // keys containing spaces survive the legacy line protocol
+            let res = Response::Totals(per_tag);
+            assert_eq!(res.to_string().parse::<Response>().unwrap(), res);
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    fs::remove_file(history_file).unwrap();
+}
@@ -0,0 +1,113 @@
+#![cfg(feature = "hooks")]
+
+use std::{env, fs, time::Duration};
+use time::{
+    hook::{TimerEventKind, TimerHook},
+    timer::{ThreadSafeTimer, TimerConfig, TimerCycle, TimerEvent},
+};
+
+#[test]
+fn deserialize_hooks() {
+    let hooks: Vec<TimerHook> = serde_json::from_str(
+        r#"[
+            { "event": "ended", "cycle": "Work", "cmd": "notify-send {cycle} ended" },
+            { "event": "started", "cmd": ["echo started", "cat"] }
+        ]"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        hooks,
+        vec![
+            TimerHook::new(TimerEventKind::Ended, "notify-send {cycle} ended").with_cycle("Work"),
+            TimerHook::new(TimerEventKind::Started, vec!["echo started", "cat"]),
+        ]
+    );
+}
+
+#[test]
+fn hook_command() {
+    let hook = TimerHook::new(TimerEventKind::Began, "echo {event} {cycle} {duration}");
+
+    assert_eq!(
+        hook.command(&TimerEvent::Began(TimerCycle::new("Work", 60)))
+            .unwrap(),
+        Some("echo 'began' 'Work' '60'".into())
+    );
+    assert_eq!(
+        hook.command(&TimerEvent::Ended(TimerCycle::new("Work", 0)))
+            .unwrap(),
+        None
+    );
+
+    let hook = hook.with_cycle("Break");
+
+    assert_eq!(
+        hook.command(&TimerEvent::Began(TimerCycle::new("Work", 60)))
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        hook.command(&TimerEvent::Began(TimerCycle::new("Break", 10)))
+            .unwrap(),
+        Some("echo 'began' 'Break' '10'".into())
+    );
+
+    let hook = TimerHook::new(TimerEventKind::Stopped, "echo {event} {cycle}");
+
+    assert_eq!(
+        hook.command(&TimerEvent::Stopped).unwrap(),
+        Some("echo 'stopped' ''".into())
+    );
+
+    // cycle names are quoted
+    let hook = TimerHook::new(TimerEventKind::Began, "notify-send {cycle} began");
+
+    assert_eq!(
+        hook.command(&TimerEvent::Began(TimerCycle::new("Tom's; rm -rf", 10)))
+            .unwrap(),
+        Some(r"notify-send 'Tom'\''s; rm -rf' began".into())
+    );
+
+    // quoted placeholders are rejected
+    let hook = TimerHook::new(TimerEventKind::Began, "notify-send '{cycle} began'");
+
+    assert!(hook
+        .command(&TimerEvent::Began(TimerCycle::new("Work", 10)))
+        .is_err());
+}
+
+#[tokio::test]
+async fn run_hooks() {
+    let log = env::temp_dir().join(format!("time-lib-hooks-{}", std::process::id()));
+    let _ = fs::remove_file(&log);
+    let log_path = log.to_string_lossy();
+
+    let config = TimerConfig {
+        cycles: [TimerCycle::new("Work", 60)].into(),
+        hooks: vec![
+            TimerHook::new(
+                TimerEventKind::Began,
+                format!("echo {{event}} {{cycle}} {{duration}} >> '{log_path}'"),
+            )
+            .with_cycle("Work"),
+            TimerHook::new(
+                TimerEventKind::Began,
+                format!("echo 'never' >> '{log_path}'"),
+            )
+            .with_cycle("Break"),
+        ]
+        .into(),
+        ..Default::default()
+    };
+
+    let timer = ThreadSafeTimer::new(config).unwrap();
+    timer.start().await.unwrap();
+
+    // hooks run in dedicated tasks
+    tokio::time::sleep(Duration::from_secs(1)).await;
+
+    assert_eq!(fs::read_to_string(&log).unwrap(), "began Work 60\n");
+
+    fs::remove_file(log).unwrap();
+}
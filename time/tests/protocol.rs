@@ -0,0 +1,116 @@
+use std::time::Duration;
+use time::{
+    client::tcp::TcpClient,
+    event::Event,
+    response::Response,
+    server::{tcp::TcpBind, ServerBuilder},
+    timer::{Timer, TimerCycle, TimerEvent, TimerState},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3001;
+
+async fn exchange(lines: &[&str]) -> Vec<String> {
+    let stream = TcpStream::connect((HOST, PORT)).await.unwrap();
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader).lines();
+    let mut responses = Vec::new();
+
+    for line in lines {
+        writer
+            .write_all(format!("{line}\n").as_bytes())
+            .await
+            .unwrap();
+        if let Some(res) = reader.next_line().await.unwrap() {
+            responses.push(res);
+        }
+    }
+
+    responses
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn json_and_legacy_protocols() {
+    env_logger::builder().is_test(true).init();
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, PORT))
+        .with_cycle(("Work", 10))
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let responses = exchange(&[
+                r#"{"hello":{"version":1}}"#,
+                r#"{"id":1,"request":{"set":60}}"#,
+                r#"{"id":2,"request":"get"}"#,
+                r#"{"id":3,"request":"unknown"}"#,
+                r#"{"id":4,"request":"stop"}"#,
+            ])
+            .await;
+
+            assert_eq!(responses[0], r#"{"hello":{"version":1}}"#);
+            assert_eq!(responses[1], r#"{"id":1,"response":"ok"}"#);
+            assert_eq!(
+                responses[2],
+                r#"{"id":2,"response":{"timer":{"state":"Stopped","cycle":{"name":"Work","duration":60},"cycles_count":"Infinite","elapsed":0}}}"#
+            );
+            assert!(responses[3].starts_with(r#"{"response":{"error":"invalid frame: "#));
+            assert_eq!(responses[4], r#"{"id":4,"response":"ok"}"#);
+
+            // clients supporting newer versions fall back to the
+            // server version
+            let responses = exchange(&[r#"{"hello":{"version":42}}"#]).await;
+            assert_eq!(responses, vec![r#"{"hello":{"version":1}}"#]);
+
+            let responses = exchange(&[r#"{"id":1,"request":"get"}"#]).await;
+            assert_eq!(
+                responses,
+                vec![r#"{"response":{"error":"expected hello frame"}}"#]
+            );
+
+            // legacy line protocol
+            assert_eq!(exchange(&["start"]).await, vec!["ok"]);
+            let responses = exchange(&["unknown"]).await;
+            assert_eq!(responses, vec!["error invalid request: unknown"]);
+
+            let client = TcpClient::new_boxed(HOST, PORT);
+            client.pause().await.unwrap();
+            assert_eq!(
+                client.get().await.unwrap(),
+                Timer {
+                    state: TimerState::Paused,
+                    cycle: TimerCycle::new("Work", 10),
+                    ..Timer::default()
+                }
+            );
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+}
+
+#[test]
+fn legacy_responses_with_spaces() {
+    let cycle = TimerCycle::new("Short break", 5);
+    let responses = [
+        Response::Timer(Timer {
+            cycle: cycle.clone(),
+            ..Timer::default()
+        }),
+        Response::Event(Event::Timer(TimerEvent::Began(cycle))),
+    ];
+
+    for res in responses {
+        assert_eq!(res.to_string().parse::<Response>().unwrap(), res);
+    }
+}
@@ -0,0 +1,60 @@
+use std::time::Duration;
+use time::{
+    client::tcp::TcpClient,
+    request::Request,
+    server::{tcp::TcpBind, ServerBuilder},
+    timer::{TimerCycle, TimerCycleRule},
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3007;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn schedule() {
+    env_logger::builder().is_test(true).init();
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, PORT))
+        .with_cycle(("Work", 60))
+        .with_cycle(TimerCycle::new("Short break", 10).with_rule(TimerCycleRule::Except(2)))
+        .with_cycle(TimerCycle::new("Long break", 30).with_rule(TimerCycleRule::Every(2)))
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let client = TcpClient::new_boxed(HOST, PORT);
+
+            client.start().await.unwrap();
+
+            client.add(60).await.unwrap();
+            let timer = client.get().await.unwrap();
+            assert_eq!(timer.cycle.name, "Work");
+            assert!(timer.cycle.duration > 100);
+
+            client.subtract(100).await.unwrap();
+            let timer = client.get().await.unwrap();
+            assert_eq!(timer.cycle.name, "Work");
+            assert!(timer.cycle.duration <= 20);
+
+            // the long break only runs every 2 loops
+            let mut cycles = Vec::new();
+            for _ in 0..4 {
+                client.next().await.unwrap();
+                cycles.push(client.get().await.unwrap().cycle.name);
+            }
+            assert_eq!(cycles, vec!["Short break", "Work", "Long break", "Work"]);
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    // requests survive the legacy line protocol
+    for req in [Request::Add(60), Request::Subtract(60), Request::Next] {
+        assert_eq!(req.to_string().parse::<Request>().unwrap(), req);
+    }
+}
@@ -0,0 +1,215 @@
+use std::time::Duration;
+use time::{
+    client::tcp::TcpClient,
+    event::{Event, ServerEvent},
+    server::{tcp::TcpBind, ServerBuilder},
+    timer::{TimerCycle, TimerEvent},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines},
+    net::{tcp::OwnedReadHalf, TcpStream},
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3002;
+static STREAMING_PORT: u16 = 3008;
+static SHUTDOWN_PORT: u16 = 3009;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscribe() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, PORT))
+        .with_cycle(("Work", 10))
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let client = TcpClient::new_boxed(HOST, PORT);
+            let mut events = client.subscribe().await.unwrap();
+
+            let stream = TcpStream::connect((HOST, PORT)).await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut legacy_events = BufReader::new(reader).lines();
+            writer.write_all(b"subscribe\n").await.unwrap();
+            assert_eq!(legacy_events.next_line().await.unwrap().unwrap(), "ok");
+
+            client.start().await.unwrap();
+
+            assert_eq!(
+                events.next().await.unwrap(),
+                Some(Event::Timer(TimerEvent::Started))
+            );
+            assert_eq!(
+                events.next().await.unwrap(),
+                Some(Event::Timer(TimerEvent::Began(TimerCycle::new("Work", 10))))
+            );
+
+            assert_eq!(
+                legacy_events.next_line().await.unwrap().unwrap(),
+                r#"event {"timer":"Started"}"#
+            );
+
+            // ticks are pushed every second
+            match events.next().await.unwrap() {
+                Some(Event::Timer(TimerEvent::Running(cycle))) => assert_eq!(cycle.name, "Work"),
+                event => panic!("unexpected event: {event:?}"),
+            }
+
+            client.pause().await.unwrap();
+
+            loop {
+                match events.next().await.unwrap() {
+                    Some(Event::Timer(TimerEvent::Running(_))) => continue,
+                    Some(Event::Timer(TimerEvent::Paused(cycle))) => {
+                        assert_eq!(cycle.name, "Work");
+                        break;
+                    }
+                    event => panic!("unexpected event: {event:?}"),
+                }
+            }
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+}
+
+/// Read lines till the response of the given request, skipping
+/// events.
+async fn read_response(lines: &mut Lines<BufReader<OwnedReadHalf>>, id: u64) -> String {
+    let prefix = format!(r#"{{"id":{id},"response":"#);
+
+    loop {
+        let line = lines.next_line().await.unwrap().unwrap();
+        if line.starts_with(&prefix) && !line.contains(r#""event""#) {
+            return line;
+        }
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn requests_while_streaming() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, STREAMING_PORT))
+        .with_cycle(("Work", 10))
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let stream = TcpStream::connect((HOST, STREAMING_PORT)).await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut lines = BufReader::new(reader).lines();
+
+            let frames = [
+                r#"{"hello":{"version":1}}"#,
+                r#"{"id":1,"request":"subscribe"}"#,
+                r#"{"id":2,"request":"start"}"#,
+            ];
+            writer
+                .write_all(format!("{}\n", frames.join("\n")).as_bytes())
+                .await
+                .unwrap();
+
+            assert_eq!(
+                read_response(&mut lines, 2).await,
+                r#"{"id":2,"response":"ok"}"#
+            );
+
+            // send a request in two halves, separated by events
+            writer.write_all(br#"{"id":3,"req"#).await.unwrap();
+            tokio::time::sleep(Duration::from_millis(2500)).await;
+            writer.write_all(b"uest\":\"pause\"}\n").await.unwrap();
+
+            assert_eq!(
+                read_response(&mut lines, 3).await,
+                r#"{"id":3,"response":"ok"}"#
+            );
+
+            writer
+                .write_all(b"{\"id\":4,\"request\":\"get\"}\n")
+                .await
+                .unwrap();
+
+            let res = read_response(&mut lines, 4).await;
+            assert!(res.contains(r#""state":"Paused""#), "{res}");
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn subscriptions_end_on_shutdown() {
+    let _ = env_logger::builder().is_test(true).try_init();
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, SHUTDOWN_PORT))
+        .with_cycle(("Work", 10))
+        .build()
+        .unwrap();
+
+    let (tx, rx) = tokio::sync::oneshot::channel();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let client = TcpClient::new_boxed(HOST, SHUTDOWN_PORT);
+            let mut events = client.subscribe().await.unwrap();
+
+            // collect events in the background, till the end of the
+            // subscription
+            tokio::spawn(async move {
+                let mut received = Vec::new();
+                while let Some(event) = events.next().await.unwrap() {
+                    received.push(event);
+                }
+                let _ = tx.send(received);
+            });
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    let events = tokio::time::timeout(Duration::from_secs(5), rx)
+        .await
+        .expect("subscription should end after the server stopped")
+        .unwrap();
+
+    assert_eq!(
+        events,
+        vec![
+            Event::Server(ServerEvent::Stopping),
+            Event::Server(ServerEvent::Stopped),
+        ]
+    );
+}
+
+#[test]
+fn event_serialization() {
+    let event = Event::Server(ServerEvent::Stopping);
+    assert_eq!(
+        serde_json::to_string(&event).unwrap(),
+        r#"{"server":"Stopping"}"#
+    );
+
+    let event = Event::Timer(TimerEvent::Began(TimerCycle::new("Work", 10)));
+    let json = r#"{"timer":{"Began":{"name":"Work","duration":10}}}"#;
+    assert_eq!(serde_json::to_string(&event).unwrap(), json);
+    assert_eq!(serde_json::from_str::<Event>(json).unwrap(), event);
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "tcp-tls")]
+
+use std::{path::PathBuf, time::Duration};
+use time::{
+    client::{tcp::TcpClient, Client},
+    server::{tcp::TcpBind, ServerBuilder},
+    tcp::{TcpConfig, TcpTlsConfig},
+    timer::TimerState,
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3006;
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("tls")
+        .join(name)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tcp_tls() {
+    env_logger::builder().is_test(true).init();
+
+    let tls = TcpTlsConfig::new(fixture("cert.pem")).with_key(fixture("key.pem"));
+    let config = TcpConfig::new(HOST, PORT)
+        .with_token("secret")
+        .with_tls(tls);
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new_with_config(config))
+        .with_cycle(("Work", 10))
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            // clients only need the certificate to trust the server
+            let tls = TcpTlsConfig::new(fixture("cert.pem"));
+            let config = TcpConfig::new(HOST, PORT)
+                .with_token("secret")
+                .with_tls(tls);
+            let client = TcpClient::new_with_config(config);
+
+            let mut events = client.subscribe().await.unwrap();
+            client.start().await.unwrap();
+            assert!(events.next().await.unwrap().is_some());
+            assert_eq!(client.get().await.unwrap().state, TimerState::Running);
+
+            // plain TCP clients cannot talk to a TLS server
+            let client =
+                TcpClient::new_with_config(TcpConfig::new(HOST, PORT).with_token("secret"));
+            assert!(client.get().await.is_err());
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+}
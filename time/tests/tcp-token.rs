@@ -0,0 +1,83 @@
+use std::time::Duration;
+use time::{
+    client::{tcp::TcpClient, Client},
+    protocol::MAX_FRAME_LEN,
+    server::{tcp::TcpBind, ServerBuilder, HELLO_TIMEOUT},
+    tcp::TcpConfig,
+    timer::TimerState,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3005;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn tcp_token() {
+    env_logger::builder().is_test(true).init();
+
+    let config = TcpConfig::new(HOST, PORT).with_token("secret");
+
+    // tokens are not leaked by debug logs
+    assert!(!format!("{config:?}").contains("secret"));
+    let binder = TcpBind::new_with_config(config.clone());
+    assert!(!format!("{binder:?}").contains("secret"));
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new_with_config(config.clone()))
+        .with_cycle(("Work", 10))
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            // valid tokens are accepted
+            let client = TcpClient::new_with_config(config);
+            client.start().await.unwrap();
+            assert_eq!(client.get().await.unwrap().state, TimerState::Running);
+
+            // invalid tokens are rejected
+            let client = TcpClient::new_with_config(TcpConfig::new(HOST, PORT).with_token("wrong"));
+            let err = client.get().await.unwrap_err();
+            assert_eq!(err.to_string(), "invalid token");
+
+            // missing tokens are rejected
+            let client = TcpClient::new(HOST, PORT);
+            let err = client.get().await.unwrap_err();
+            assert_eq!(err.to_string(), "invalid token");
+
+            // the legacy line protocol cannot be authenticated
+            let stream = TcpStream::connect((HOST, PORT)).await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader).lines();
+            writer.write_all(b"get\n").await.unwrap();
+            assert_eq!(
+                reader.next_line().await.unwrap().unwrap(),
+                "error authentication required"
+            );
+
+            // frames longer than the limit close the connection
+            let stream = TcpStream::connect((HOST, PORT)).await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader).lines();
+            let frame = vec![b'{'; MAX_FRAME_LEN as usize];
+            writer.write_all(&frame).await.unwrap();
+            assert!(matches!(reader.next_line().await, Ok(None) | Err(_)));
+
+            // silent clients are disconnected
+            let stream = TcpStream::connect((HOST, PORT)).await.unwrap();
+            let mut reader = BufReader::new(stream).lines();
+            let timeout = HELLO_TIMEOUT + Duration::from_secs(2);
+            let line = tokio::time::timeout(timeout, reader.next_line()).await;
+            assert!(matches!(line, Ok(Ok(None) | Err(_))));
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+}
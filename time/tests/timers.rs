@@ -0,0 +1,92 @@
+use std::time::Duration;
+use time::{
+    client::{tcp::TcpClient, Client},
+    event::Event,
+    server::{tcp::TcpBind, ServerBuilder},
+    timer::{TimerConfig, TimerCycle, TimerEvent, TimerState},
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+};
+
+static HOST: &str = "127.0.0.1";
+static PORT: u16 = 3004;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn named_timers() {
+    env_logger::builder().is_test(true).init();
+
+    let meeting = TimerConfig {
+        cycles: [TimerCycle::new("Meeting", 60)].into(),
+        ..Default::default()
+    };
+
+    let server = ServerBuilder::new()
+        .with_binder(TcpBind::new(HOST, PORT))
+        .with_cycle(("Work", 10))
+        .with_timer("meeting", meeting)
+        .build()
+        .unwrap();
+
+    server
+        .bind_with(|| async {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let default = TcpClient::new_boxed(HOST, PORT);
+            let meeting = TcpClient::new(HOST, PORT).with_timer("meeting");
+            let unknown = TcpClient::new(HOST, PORT).with_timer("unknown");
+
+            let mut events = meeting.subscribe().await.unwrap();
+
+            meeting.start().await.unwrap();
+
+            assert_eq!(
+                events.next().await.unwrap(),
+                Some(Event::Timer(TimerEvent::Started))
+            );
+            assert_eq!(
+                events.next().await.unwrap(),
+                Some(Event::Timer(TimerEvent::Began(TimerCycle::new(
+                    "Meeting", 60
+                ))))
+            );
+
+            // timers are independent
+            let timer = meeting.get().await.unwrap();
+            assert_eq!(timer.state, TimerState::Running);
+            assert_eq!(timer.cycle.name, "Meeting");
+
+            let timer = default.get().await.unwrap();
+            assert_eq!(timer.state, TimerState::Stopped);
+            assert_eq!(timer.cycle.name, "Work");
+
+            // timers are reported separately
+            let timers = default.list().await.unwrap();
+            assert_eq!(
+                timers.keys().collect::<Vec<_>>(),
+                vec!["default", "meeting"]
+            );
+            assert_eq!(timers["default"].state, TimerState::Stopped);
+            assert_eq!(timers["meeting"].state, TimerState::Running);
+
+            // unknown timers are errors
+            let err = unknown.get().await.unwrap_err();
+            assert_eq!(err.to_string(), "cannot find timer unknown");
+
+            // the legacy line protocol addresses the default timer
+            let stream = TcpStream::connect((HOST, PORT)).await.unwrap();
+            let (reader, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(reader).lines();
+            writer.write_all(b"start\n").await.unwrap();
+            assert_eq!(reader.next_line().await.unwrap().unwrap(), "ok");
+
+            let timers = default.list().await.unwrap();
+            assert_eq!(timers["default"].state, TimerState::Running);
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+}
@@ -0,0 +1,59 @@
+#![cfg(all(unix, feature = "unix"))]
+
+use std::{env, fs, os::unix::fs::PermissionsExt, time::Duration};
+use time::{
+    client::unix::UnixClient,
+    server::{unix::UnixBind, ServerBuilder},
+    timer::{Timer, TimerCycle, TimerState},
+    unix::UnixConfig,
+};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn unix_client() {
+    env_logger::builder().is_test(true).init();
+
+    let dir = env::temp_dir().join(format!("time-lib-unix-{}", std::process::id()));
+    let path = dir.join("timer.sock");
+
+    // a stale socket left by a previous server should be replaced
+    fs::create_dir_all(&dir).unwrap();
+    drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+    let server = ServerBuilder::new()
+        .with_binder(UnixBind::new_with_config(UnixConfig::new(&path)))
+        .with_cycle(("Work", 3))
+        .build()
+        .unwrap();
+
+    let socket_path = path.clone();
+    server
+        .bind_with(|| async move {
+            // wait for the binder to be ready
+            tokio::time::sleep(Duration::from_secs(1)).await;
+
+            let mode = fs::metadata(&socket_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+
+            let client = UnixClient::new_boxed(&socket_path);
+
+            client.start().await.unwrap();
+            client.pause().await.unwrap();
+
+            assert_eq!(
+                client.get().await.unwrap(),
+                Timer {
+                    state: TimerState::Paused,
+                    cycle: TimerCycle::new("Work", 3),
+                    ..Timer::default()
+                }
+            );
+
+            client.stop().await.unwrap();
+
+            Ok(())
+        })
+        .await
+        .unwrap();
+
+    fs::remove_dir_all(&dir).unwrap();
+}